@@ -11,8 +11,12 @@ use crate::manager::ManagerExt;
 use std::path::PathBuf;
 use std::time::Duration;
 use tauri::async_runtime::spawn_blocking;
+use tauri::ipc::{Request, Response};
 use tauri::{AppHandle, Manager, Runtime, WebviewWindow};
-use tauri_store::{Result, SaveStrategy, StoreId, StoreOptions, StoreState};
+use tauri_store::{
+  io_err, read_blob_upload, AtomicOp, BatchOp, Json, Result, SaveStrategy, StoreId, StoreOptions,
+  StoreState,
+};
 
 #[tauri::command]
 pub(crate) async fn allow_save<R>(app: AppHandle<R>, ids: Vec<StoreId>)
@@ -32,6 +36,51 @@ where
   ids.iter().for_each(|id| valtio.allow_sync(id));
 }
 
+#[tauri::command]
+pub(crate) async fn apply_op<R>(
+  window: WebviewWindow<R>,
+  id: StoreId,
+  key: String,
+  op: AtomicOp,
+) -> Result<Json>
+where
+  R: Runtime,
+{
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app
+    .valtio()
+    .with_store(id, move |store| store.apply_op_with_source(key, op, label))?
+}
+
+#[tauri::command]
+pub(crate) async fn batch<R>(window: WebviewWindow<R>, id: StoreId, ops: Vec<BatchOp>) -> Result<()>
+where
+  R: Runtime,
+{
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app.valtio().with_store(id, move |store| {
+    store.batch_with_source(|tx| tx.extend(ops), label)
+  })?
+}
+
+#[tauri::command]
+pub(crate) async fn can_redo<R>(app: AppHandle<R>, id: StoreId) -> Result<bool>
+where
+  R: Runtime,
+{
+  app.valtio().can_redo(id)
+}
+
+#[tauri::command]
+pub(crate) async fn can_undo<R>(app: AppHandle<R>, id: StoreId) -> Result<bool>
+where
+  R: Runtime,
+{
+  app.valtio().can_undo(id)
+}
+
 #[tauri::command]
 pub(crate) async fn clear_autosave<R>(app: AppHandle<R>)
 where
@@ -40,6 +89,14 @@ where
   app.valtio().clear_autosave();
 }
 
+#[tauri::command]
+pub(crate) async fn clear_history<R>(app: AppHandle<R>, id: StoreId) -> Result<()>
+where
+  R: Runtime,
+{
+  app.valtio().clear_history(id)
+}
+
 #[tauri::command]
 pub(crate) async fn deny_save<R>(app: AppHandle<R>, ids: Vec<StoreId>)
 where
@@ -66,6 +123,17 @@ where
   app.valtio().destroy(id)
 }
 
+#[tauri::command]
+pub(crate) async fn get_blob<R>(app: AppHandle<R>, id: StoreId, key: String) -> Result<Response>
+where
+  R: Runtime,
+{
+  match app.valtio().get_blob(id, &key)? {
+    Some(bytes) => Ok(Response::new(bytes)),
+    None => io_err!(NotFound, "blob not found: {key}"),
+  }
+}
+
 #[tauri::command]
 pub(crate) async fn get_default_save_strategy<R>(app: AppHandle<R>) -> SaveStrategy
 where
@@ -82,6 +150,14 @@ where
   app.valtio().path().to_path_buf()
 }
 
+#[tauri::command]
+pub(crate) async fn get_persisted_store_ids<R>(app: AppHandle<R>) -> Result<Vec<StoreId>>
+where
+  R: Runtime,
+{
+  app.valtio().persisted_ids()
+}
+
 #[tauri::command]
 pub(crate) async fn get_store_ids<R>(app: AppHandle<R>) -> Vec<StoreId>
 where
@@ -98,6 +174,14 @@ where
   app.valtio().with_store(id, |store| store.path())
 }
 
+#[tauri::command]
+pub(crate) async fn get_path<R>(app: AppHandle<R>, id: StoreId, pointer: String) -> Option<Json>
+where
+  R: Runtime,
+{
+  app.valtio().get_raw_path(id, pointer)
+}
+
 #[tauri::command]
 pub(crate) async fn get_save_strategy<R>(app: AppHandle<R>, id: StoreId) -> Result<SaveStrategy>
 where
@@ -129,6 +213,22 @@ where
   .await?
 }
 
+#[tauri::command]
+pub(crate) async fn merge_patch<R>(
+  window: WebviewWindow<R>,
+  id: StoreId,
+  patch: StoreState,
+) -> Result<()>
+where
+  R: Runtime,
+{
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app
+    .valtio()
+    .with_store(id, move |store| store.merge_patch_with_source(patch, label))?
+}
+
 #[tauri::command]
 pub(crate) async fn patch<R>(window: WebviewWindow<R>, id: StoreId, state: StoreState) -> Result<()>
 where
@@ -141,6 +241,67 @@ where
     .with_store(id, move |store| store.patch_with_source(state, label))?
 }
 
+#[tauri::command]
+pub(crate) async fn put_blob<R>(window: WebviewWindow<R>, request: Request<'_>) -> Result<()>
+where
+  R: Runtime,
+{
+  let (id, key, bytes) = read_blob_upload(&request)?;
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app.valtio().with_store(id, move |store| {
+    store.put_blob_with_source(key, bytes, label)
+  })?
+}
+
+#[tauri::command]
+pub(crate) async fn redo<R>(app: AppHandle<R>, id: StoreId) -> Result<bool>
+where
+  R: Runtime,
+{
+  app.valtio().redo(id)
+}
+
+#[tauri::command]
+pub(crate) async fn remove_blob<R>(
+  window: WebviewWindow<R>,
+  id: StoreId,
+  key: String,
+) -> Result<bool>
+where
+  R: Runtime,
+{
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app
+    .valtio()
+    .with_store(id, move |store| store.remove_blob_with_source(key, label))?
+}
+
+#[tauri::command]
+pub(crate) async fn remove_path<R>(
+  window: WebviewWindow<R>,
+  id: StoreId,
+  pointer: String,
+) -> Result<Option<Json>>
+where
+  R: Runtime,
+{
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app.valtio().with_store(id, move |store| {
+    store.remove_path_with_source(pointer, label)
+  })?
+}
+
+#[tauri::command]
+pub(crate) async fn reset<R>(app: AppHandle<R>, id: StoreId) -> Result<()>
+where
+  R: Runtime,
+{
+  app.valtio().reset(id)
+}
+
 #[tauri::command]
 pub(crate) async fn save<R>(app: AppHandle<R>, id: StoreId) -> Result<()>
 where
@@ -199,6 +360,23 @@ where
     .set_autosave(Duration::from_millis(interval));
 }
 
+#[tauri::command]
+pub(crate) async fn set_path<R>(
+  window: WebviewWindow<R>,
+  id: StoreId,
+  pointer: String,
+  value: Json,
+) -> Result<()>
+where
+  R: Runtime,
+{
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app.valtio().with_store(id, move |store| {
+    store.set_path_with_source(pointer, value, label)
+  })?
+}
+
 #[tauri::command]
 pub(crate) async fn set_save_strategy<R>(
   app: AppHandle<R>,
@@ -229,6 +407,14 @@ where
   })?
 }
 
+#[tauri::command]
+pub(crate) async fn undo<R>(app: AppHandle<R>, id: StoreId) -> Result<bool>
+where
+  R: Runtime,
+{
+  app.valtio().undo(id)
+}
+
 #[tauri::command]
 pub(crate) async fn unload<R>(app: AppHandle<R>, id: StoreId) -> Result<()>
 where
@@ -10,18 +10,32 @@
 const COMMANDS: &[&str] = &[
   "allow_save",
   "allow_sync",
+  "apply_op",
+  "batch",
+  "can_redo",
+  "can_undo",
   "clear_autosave",
+  "clear_history",
   "deny_save",
   "deny_sync",
   "destroy",
+  "get_blob",
   "get_default_save_strategy",
+  "get_path",
+  "get_persisted_store_ids",
   "get_store_collection_path",
   "get_save_strategy",
   "get_store_ids",
   "get_store_path",
   "get_store_state",
   "load",
+  "merge_patch",
   "patch",
+  "put_blob",
+  "redo",
+  "remove_blob",
+  "remove_path",
+  "reset",
   "save",
   "save_all",
   "save_all_now",
@@ -29,9 +43,11 @@ const COMMANDS: &[&str] = &[
   "save_some",
   "save_some_now",
   "set_autosave",
+  "set_path",
   "set_store_collection_path",
   "set_save_strategy",
   "set_store_options",
+  "undo",
   "unload",
 ];
 
@@ -38,12 +38,21 @@ pub struct Builder<R: Runtime> {
   path_table: HashMap<StoreId, Box<Path>>,
   default_marshaler: Option<Box<dyn Marshaler>>,
   marshaler_table: HashMap<StoreId, Box<dyn Marshaler>>,
+  layout: CollectionLayout,
   default_save_strategy: SaveStrategy,
   autosave: Option<Duration>,
   on_load: Option<Box<OnLoadFn<R, ZustandMarker>>>,
   save_denylist: HashSet<StoreId>,
   sync_denylist: HashSet<StoreId>,
+  journal: HashSet<StoreId>,
+  journal_limit: Option<usize>,
+  sharded: HashSet<StoreId>,
+  crdt: HashSet<StoreId>,
   migrator: Migrator,
+  lock_policy: Option<LockPolicy>,
+  process_sync: Option<Duration>,
+  sync_adapter: Option<Box<dyn SyncAdapter>>,
+  sync_interval: Option<Duration>,
   debug_stores: bool,
 }
 
@@ -126,6 +135,61 @@ impl<R: Runtime> Builder<R> {
     self
   }
 
+  /// Sets a list of stores that should record their changes to a write-ahead log.
+  #[must_use]
+  pub fn journal<I, T>(mut self, stores: I) -> Self
+  where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+  {
+    self.journal.extend(
+      stores
+        .into_iter()
+        .map(|it| StoreId::from(it.as_ref())),
+    );
+
+    self
+  }
+
+  /// Maximum amount of records in a write-ahead log before it is compacted.
+  #[must_use]
+  pub fn journal_limit(mut self, limit: usize) -> Self {
+    self.journal_limit = Some(limit);
+    self
+  }
+
+  /// Sets a list of stores whose keys should each be saved to their own file.
+  #[must_use]
+  pub fn shard<I, T>(mut self, stores: I) -> Self
+  where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+  {
+    self.sharded.extend(
+      stores
+        .into_iter()
+        .map(|it| StoreId::from(it.as_ref())),
+    );
+
+    self
+  }
+
+  /// Sets a list of stores that should be backed by a CRDT.
+  #[must_use]
+  pub fn crdt<I, T>(mut self, stores: I) -> Self
+  where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+  {
+    self.crdt.extend(
+      stores
+        .into_iter()
+        .map(|it| StoreId::from(it.as_ref())),
+    );
+
+    self
+  }
+
   /// Defines how the stores should be serialized and deserialized.
   #[must_use]
   pub fn marshaler(mut self, marshaler: Box<dyn Marshaler>) -> Self {
@@ -141,6 +205,42 @@ impl<R: Runtime> Builder<R> {
     self
   }
 
+  /// Defines how the stores should be laid out on disk.
+  #[must_use]
+  pub fn layout(mut self, layout: CollectionLayout) -> Self {
+    self.layout = layout;
+    self
+  }
+
+  /// Locks the collection directory, so that other processes can't use it at the same time.
+  #[must_use]
+  pub fn lock_policy(mut self, policy: LockPolicy) -> Self {
+    self.lock_policy = Some(policy);
+    self
+  }
+
+  /// Shares the changes made to the stores with other processes using the same directory,
+  /// reading the changes made by the other processes at the given interval.
+  #[must_use]
+  pub fn process_sync(mut self, interval: Duration) -> Self {
+    self.process_sync = Some(interval);
+    self
+  }
+
+  /// Synchronizes the stores with a remote, such as a backend server.
+  #[must_use]
+  pub fn sync_adapter(mut self, adapter: Box<dyn SyncAdapter>) -> Self {
+    self.sync_adapter = Some(adapter);
+    self
+  }
+
+  /// Sets how often the stores are synchronized with the remote.
+  #[must_use]
+  pub fn sync_interval(mut self, interval: Duration) -> Self {
+    self.sync_interval = Some(interval);
+    self
+  }
+
   /// Adds a `.dev` suffix to the store files when in development mode.
   ///
   /// This is enabled by default.
@@ -183,8 +283,12 @@ impl<R: Runtime> Builder<R> {
   fn build_collection(self, handle: Handle<R>) -> Result<()> {
     let mut builder = StoreCollection::<R, ZustandMarker>::builder()
       .default_save_strategy(self.default_save_strategy)
+      .layout(self.layout)
       .save_denylist(&self.save_denylist)
       .sync_denylist(&self.sync_denylist)
+      .journal(&self.journal)
+      .shard(&self.sharded)
+      .crdt(&self.crdt)
       .migrator(self.migrator)
       .enable_debug_stores(self.debug_stores);
 
@@ -200,6 +304,26 @@ impl<R: Runtime> Builder<R> {
       builder = builder.autosave(duration);
     }
 
+    if let Some(policy) = self.lock_policy {
+      builder = builder.lock_policy(policy);
+    }
+
+    if let Some(interval) = self.process_sync {
+      builder = builder.process_sync(interval);
+    }
+
+    if let Some(adapter) = self.sync_adapter {
+      builder = builder.sync_adapter(adapter);
+    }
+
+    if let Some(interval) = self.sync_interval {
+      builder = builder.sync_interval(interval);
+    }
+
+    if let Some(limit) = self.journal_limit {
+      builder = builder.journal_limit(limit);
+    }
+
     if let Some(marshaler) = self.default_marshaler {
       builder = builder.marshaler(marshaler);
     }
@@ -223,18 +347,32 @@ impl<R: Runtime> Builder<R> {
       .invoke_handler(tauri::generate_handler![
         command::allow_save,
         command::allow_sync,
+        command::apply_op,
+        command::batch,
+        command::can_redo,
+        command::can_undo,
         command::clear_autosave,
+        command::clear_history,
         command::deny_save,
         command::deny_sync,
         command::destroy,
+        command::get_blob,
         command::get_default_save_strategy,
+        command::get_path,
+        command::get_persisted_store_ids,
         command::get_store_collection_path,
         command::get_save_strategy,
         command::get_store_ids,
         command::get_store_path,
         command::get_store_state,
         command::load,
+        command::merge_patch,
         command::patch,
+        command::put_blob,
+        command::redo,
+        command::remove_blob,
+        command::remove_path,
+        command::reset,
         command::save,
         command::save_all,
         command::save_all_now,
@@ -242,8 +380,10 @@ impl<R: Runtime> Builder<R> {
         command::save_some,
         command::save_some_now,
         command::set_autosave,
+        command::set_path,
         command::set_save_strategy,
         command::set_store_options,
+        command::undo,
         command::unload
       ])
       .build()
@@ -257,12 +397,21 @@ impl<R: Runtime> Default for Builder<R> {
       path_table: HashMap::new(),
       default_marshaler: None,
       marshaler_table: HashMap::new(),
+      layout: CollectionLayout::default(),
       default_save_strategy: SaveStrategy::default(),
       autosave: None,
       on_load: None,
       save_denylist: HashSet::default(),
       sync_denylist: HashSet::default(),
+      journal: HashSet::default(),
+      journal_limit: None,
+      sharded: HashSet::default(),
+      crdt: HashSet::default(),
       migrator: Migrator::default(),
+      lock_policy: None,
+      process_sync: None,
+      sync_adapter: None,
+      sync_interval: None,
       debug_stores: true,
     }
   }
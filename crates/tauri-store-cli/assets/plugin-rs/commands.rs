@@ -1,241 +1,425 @@
-use crate::manager::ManagerExt;
-use __IMPORT_SOURCE__::{Result, SaveStrategy, StoreId, StoreOptions, StoreState};
-use std::path::PathBuf;
-use std::time::Duration;
-use tauri::async_runtime::spawn_blocking;
-use tauri::{AppHandle, Manager, Runtime, WebviewWindow};
-
-#[tauri::command]
-pub(crate) async fn allow_save<R>(app: AppHandle<R>, ids: Vec<StoreId>)
-where
-  R: Runtime,
-{
-  let __STORE_COLLECTION__ = app.__STORE_COLLECTION__();
-  ids
-    .iter()
-    .for_each(|id| __STORE_COLLECTION__.allow_save(id));
-}
-
-#[tauri::command]
-pub(crate) async fn allow_sync<R>(app: AppHandle<R>, ids: Vec<StoreId>)
-where
-  R: Runtime,
-{
-  let __STORE_COLLECTION__ = app.__STORE_COLLECTION__();
-  ids
-    .iter()
-    .for_each(|id| __STORE_COLLECTION__.allow_sync(id));
-}
-
-#[tauri::command]
-pub(crate) async fn clear_autosave<R>(app: AppHandle<R>)
-where
-  R: Runtime,
-{
-  app.__STORE_COLLECTION__().clear_autosave();
-}
-
-#[tauri::command]
-pub(crate) async fn deny_save<R>(app: AppHandle<R>, ids: Vec<StoreId>)
-where
-  R: Runtime,
-{
-  let __STORE_COLLECTION__ = app.__STORE_COLLECTION__();
-  ids
-    .iter()
-    .for_each(|id| __STORE_COLLECTION__.deny_save(id));
-}
-
-#[tauri::command]
-pub(crate) async fn deny_sync<R>(app: AppHandle<R>, ids: Vec<StoreId>)
-where
-  R: Runtime,
-{
-  let __STORE_COLLECTION__ = app.__STORE_COLLECTION__();
-  ids
-    .iter()
-    .for_each(|id| __STORE_COLLECTION__.deny_sync(id));
-}
-
-#[tauri::command]
-pub(crate) async fn destroy<R>(app: AppHandle<R>, id: StoreId) -> Result<()>
-where
-  R: Runtime,
-{
-  app.__STORE_COLLECTION__().destroy(id)
-}
-
-#[tauri::command]
-pub(crate) async fn get_default_save_strategy<R>(app: AppHandle<R>) -> SaveStrategy
-where
-  R: Runtime,
-{
-  app.__STORE_COLLECTION__().default_save_strategy()
-}
-
-#[tauri::command]
-pub(crate) async fn get_store_collection_path<R>(app: AppHandle<R>) -> PathBuf
-where
-  R: Runtime,
-{
-  app.__STORE_COLLECTION__().path().to_path_buf()
-}
-
-#[tauri::command]
-pub(crate) async fn get_store_ids<R>(app: AppHandle<R>) -> Vec<StoreId>
-where
-  R: Runtime,
-{
-  app.__STORE_COLLECTION__().ids()
-}
-
-#[tauri::command]
-pub(crate) async fn get_store_path<R>(app: AppHandle<R>, id: StoreId) -> Result<PathBuf>
-where
-  R: Runtime,
-{
-  app
-    .__STORE_COLLECTION__()
-    .with_store(id, |store| store.path())
-}
-
-#[tauri::command]
-pub(crate) async fn get_save_strategy<R>(app: AppHandle<R>, id: StoreId) -> Result<SaveStrategy>
-where
-  R: Runtime,
-{
-  app
-    .__STORE_COLLECTION__()
-    .with_store(id, |store| store.save_strategy())
-}
-
-#[tauri::command]
-pub(crate) async fn get_store_state<R>(app: AppHandle<R>, id: StoreId) -> Result<StoreState>
-where
-  R: Runtime,
-{
-  app.__STORE_COLLECTION__().raw_state(id)
-}
-
-#[tauri::command]
-pub(crate) async fn load<R>(app: AppHandle<R>, id: StoreId) -> Result<StoreState>
-where
-  R: Runtime,
-{
-  spawn_blocking(move || {
-    app
-      .__STORE_COLLECTION__()
-      .with_store(id, |store| store.raw_state().clone())
-  })
-  .await?
-}
-
-#[tauri::command]
-pub(crate) async fn patch<R>(window: WebviewWindow<R>, id: StoreId, state: StoreState) -> Result<()>
-where
-  R: Runtime,
-{
-  let app = window.app_handle();
-  let label = window.label().to_owned();
-  app
-    .__STORE_COLLECTION__()
-    .with_store(id, move |store| store.patch_with_source(state, label))?
-}
-
-#[tauri::command]
-pub(crate) async fn save<R>(app: AppHandle<R>, id: StoreId) -> Result<()>
-where
-  R: Runtime,
-{
-  app.__STORE_COLLECTION__().save(id)
-}
-
-#[tauri::command]
-pub(crate) async fn save_all<R>(app: AppHandle<R>) -> Result<()>
-where
-  R: Runtime,
-{
-  app.__STORE_COLLECTION__().save_all()
-}
-
-#[tauri::command]
-pub(crate) async fn save_all_now<R>(app: AppHandle<R>) -> Result<()>
-where
-  R: Runtime,
-{
-  app.__STORE_COLLECTION__().save_all_now()
-}
-
-#[tauri::command]
-pub(crate) async fn save_now<R>(app: AppHandle<R>, id: StoreId) -> Result<()>
-where
-  R: Runtime,
-{
-  app.__STORE_COLLECTION__().save_now(id)
-}
-
-#[tauri::command]
-pub(crate) async fn save_some<R>(app: AppHandle<R>, ids: Vec<StoreId>) -> Result<()>
-where
-  R: Runtime,
-{
-  app.__STORE_COLLECTION__().save_some(&ids)
-}
-
-#[tauri::command]
-pub(crate) async fn save_some_now<R>(app: AppHandle<R>, ids: Vec<StoreId>) -> Result<()>
-where
-  R: Runtime,
-{
-  app.__STORE_COLLECTION__().save_some_now(&ids)
-}
-
-#[tauri::command]
-pub(crate) async fn set_autosave<R>(app: AppHandle<R>, interval: u64)
-where
-  R: Runtime,
-{
-  app
-    .__STORE_COLLECTION__()
-    .set_autosave(Duration::from_millis(interval));
-}
-
-#[tauri::command]
-pub(crate) async fn set_save_strategy<R>(
-  app: AppHandle<R>,
-  id: StoreId,
-  strategy: SaveStrategy,
-) -> Result<()>
-where
-  R: Runtime,
-{
-  app
-    .__STORE_COLLECTION__()
-    .with_store(id, |store| store.set_save_strategy(strategy))
-}
-
-#[tauri::command]
-pub(crate) async fn set_store_options<R>(
-  window: WebviewWindow<R>,
-  id: StoreId,
-  options: StoreOptions,
-) -> Result<()>
-where
-  R: Runtime,
-{
-  let app = window.app_handle();
-  let label = window.label().to_owned();
-  app
-    .__STORE_COLLECTION__()
-    .with_store(id, move |store| {
-      store.set_options_with_source(options, label)
-    })?
-}
-
-#[tauri::command]
-pub(crate) async fn unload<R>(app: AppHandle<R>, id: StoreId) -> Result<()>
-where
-  R: Runtime,
-{
-  app.__STORE_COLLECTION__().unload_store(&id)
-}
+use crate::manager::ManagerExt;
+use __IMPORT_SOURCE__::{
+  io_err, read_blob_upload, AtomicOp, BatchOp, Json, Result, SaveStrategy, StoreId, StoreOptions,
+  StoreState,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::async_runtime::spawn_blocking;
+use tauri::ipc::{Request, Response};
+use tauri::{AppHandle, Manager, Runtime, WebviewWindow};
+
+#[tauri::command]
+pub(crate) async fn allow_save<R>(app: AppHandle<R>, ids: Vec<StoreId>)
+where
+  R: Runtime,
+{
+  let __STORE_COLLECTION__ = app.__STORE_COLLECTION__();
+  ids
+    .iter()
+    .for_each(|id| __STORE_COLLECTION__.allow_save(id));
+}
+
+#[tauri::command]
+pub(crate) async fn allow_sync<R>(app: AppHandle<R>, ids: Vec<StoreId>)
+where
+  R: Runtime,
+{
+  let __STORE_COLLECTION__ = app.__STORE_COLLECTION__();
+  ids
+    .iter()
+    .for_each(|id| __STORE_COLLECTION__.allow_sync(id));
+}
+
+#[tauri::command]
+pub(crate) async fn apply_op<R>(
+  window: WebviewWindow<R>,
+  id: StoreId,
+  key: String,
+  op: AtomicOp,
+) -> Result<Json>
+where
+  R: Runtime,
+{
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app
+    .__STORE_COLLECTION__()
+    .with_store(id, move |store| store.apply_op_with_source(key, op, label))?
+}
+
+#[tauri::command]
+pub(crate) async fn batch<R>(window: WebviewWindow<R>, id: StoreId, ops: Vec<BatchOp>) -> Result<()>
+where
+  R: Runtime,
+{
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app
+    .__STORE_COLLECTION__()
+    .with_store(id, move |store| store.batch_with_source(|tx| tx.extend(ops), label))?
+}
+
+#[tauri::command]
+pub(crate) async fn can_redo<R>(app: AppHandle<R>, id: StoreId) -> Result<bool>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().can_redo(id)
+}
+
+#[tauri::command]
+pub(crate) async fn can_undo<R>(app: AppHandle<R>, id: StoreId) -> Result<bool>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().can_undo(id)
+}
+
+#[tauri::command]
+pub(crate) async fn clear_autosave<R>(app: AppHandle<R>)
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().clear_autosave();
+}
+
+#[tauri::command]
+pub(crate) async fn clear_history<R>(app: AppHandle<R>, id: StoreId) -> Result<()>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().clear_history(id)
+}
+
+#[tauri::command]
+pub(crate) async fn deny_save<R>(app: AppHandle<R>, ids: Vec<StoreId>)
+where
+  R: Runtime,
+{
+  let __STORE_COLLECTION__ = app.__STORE_COLLECTION__();
+  ids
+    .iter()
+    .for_each(|id| __STORE_COLLECTION__.deny_save(id));
+}
+
+#[tauri::command]
+pub(crate) async fn deny_sync<R>(app: AppHandle<R>, ids: Vec<StoreId>)
+where
+  R: Runtime,
+{
+  let __STORE_COLLECTION__ = app.__STORE_COLLECTION__();
+  ids
+    .iter()
+    .for_each(|id| __STORE_COLLECTION__.deny_sync(id));
+}
+
+#[tauri::command]
+pub(crate) async fn destroy<R>(app: AppHandle<R>, id: StoreId) -> Result<()>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().destroy(id)
+}
+
+#[tauri::command]
+pub(crate) async fn get_blob<R>(app: AppHandle<R>, id: StoreId, key: String) -> Result<Response>
+where
+  R: Runtime,
+{
+  match app.__STORE_COLLECTION__().get_blob(id, &key)? {
+    Some(bytes) => Ok(Response::new(bytes)),
+    None => io_err!(NotFound, "blob not found: {key}"),
+  }
+}
+
+#[tauri::command]
+pub(crate) async fn get_default_save_strategy<R>(app: AppHandle<R>) -> SaveStrategy
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().default_save_strategy()
+}
+
+#[tauri::command]
+pub(crate) async fn get_store_collection_path<R>(app: AppHandle<R>) -> PathBuf
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().path().to_path_buf()
+}
+
+#[tauri::command]
+pub(crate) async fn get_persisted_store_ids<R>(app: AppHandle<R>) -> Result<Vec<StoreId>>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().persisted_ids()
+}
+
+#[tauri::command]
+pub(crate) async fn get_store_ids<R>(app: AppHandle<R>) -> Vec<StoreId>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().ids()
+}
+
+#[tauri::command]
+pub(crate) async fn get_store_path<R>(app: AppHandle<R>, id: StoreId) -> Result<PathBuf>
+where
+  R: Runtime,
+{
+  app
+    .__STORE_COLLECTION__()
+    .with_store(id, |store| store.path())
+}
+
+#[tauri::command]
+pub(crate) async fn get_path<R>(app: AppHandle<R>, id: StoreId, pointer: String) -> Option<Json>
+where
+  R: Runtime,
+{
+  app
+    .__STORE_COLLECTION__()
+    .get_raw_path(id, pointer)
+}
+
+#[tauri::command]
+pub(crate) async fn get_save_strategy<R>(app: AppHandle<R>, id: StoreId) -> Result<SaveStrategy>
+where
+  R: Runtime,
+{
+  app
+    .__STORE_COLLECTION__()
+    .with_store(id, |store| store.save_strategy())
+}
+
+#[tauri::command]
+pub(crate) async fn get_store_state<R>(app: AppHandle<R>, id: StoreId) -> Result<StoreState>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().raw_state(id)
+}
+
+#[tauri::command]
+pub(crate) async fn load<R>(app: AppHandle<R>, id: StoreId) -> Result<StoreState>
+where
+  R: Runtime,
+{
+  spawn_blocking(move || {
+    app
+      .__STORE_COLLECTION__()
+      .with_store(id, |store| store.raw_state().clone())
+  })
+  .await?
+}
+
+#[tauri::command]
+pub(crate) async fn merge_patch<R>(
+  window: WebviewWindow<R>,
+  id: StoreId,
+  patch: StoreState,
+) -> Result<()>
+where
+  R: Runtime,
+{
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app
+    .__STORE_COLLECTION__()
+    .with_store(id, move |store| store.merge_patch_with_source(patch, label))?
+}
+
+#[tauri::command]
+pub(crate) async fn patch<R>(window: WebviewWindow<R>, id: StoreId, state: StoreState) -> Result<()>
+where
+  R: Runtime,
+{
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app
+    .__STORE_COLLECTION__()
+    .with_store(id, move |store| store.patch_with_source(state, label))?
+}
+
+#[tauri::command]
+pub(crate) async fn put_blob<R>(window: WebviewWindow<R>, request: Request<'_>) -> Result<()>
+where
+  R: Runtime,
+{
+  let (id, key, bytes) = read_blob_upload(&request)?;
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app
+    .__STORE_COLLECTION__()
+    .with_store(id, move |store| store.put_blob_with_source(key, bytes, label))?
+}
+
+#[tauri::command]
+pub(crate) async fn redo<R>(app: AppHandle<R>, id: StoreId) -> Result<bool>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().redo(id)
+}
+
+#[tauri::command]
+pub(crate) async fn remove_blob<R>(window: WebviewWindow<R>, id: StoreId, key: String) -> Result<bool>
+where
+  R: Runtime,
+{
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app
+    .__STORE_COLLECTION__()
+    .with_store(id, move |store| store.remove_blob_with_source(key, label))?
+}
+
+#[tauri::command]
+pub(crate) async fn remove_path<R>(
+  window: WebviewWindow<R>,
+  id: StoreId,
+  pointer: String,
+) -> Result<Option<Json>>
+where
+  R: Runtime,
+{
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app
+    .__STORE_COLLECTION__()
+    .with_store(id, move |store| store.remove_path_with_source(pointer, label))?
+}
+
+#[tauri::command]
+pub(crate) async fn reset<R>(app: AppHandle<R>, id: StoreId) -> Result<()>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().reset(id)
+}
+
+#[tauri::command]
+pub(crate) async fn save<R>(app: AppHandle<R>, id: StoreId) -> Result<()>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().save(id)
+}
+
+#[tauri::command]
+pub(crate) async fn save_all<R>(app: AppHandle<R>) -> Result<()>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().save_all()
+}
+
+#[tauri::command]
+pub(crate) async fn save_all_now<R>(app: AppHandle<R>) -> Result<()>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().save_all_now()
+}
+
+#[tauri::command]
+pub(crate) async fn save_now<R>(app: AppHandle<R>, id: StoreId) -> Result<()>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().save_now(id)
+}
+
+#[tauri::command]
+pub(crate) async fn save_some<R>(app: AppHandle<R>, ids: Vec<StoreId>) -> Result<()>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().save_some(&ids)
+}
+
+#[tauri::command]
+pub(crate) async fn save_some_now<R>(app: AppHandle<R>, ids: Vec<StoreId>) -> Result<()>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().save_some_now(&ids)
+}
+
+#[tauri::command]
+pub(crate) async fn set_autosave<R>(app: AppHandle<R>, interval: u64)
+where
+  R: Runtime,
+{
+  app
+    .__STORE_COLLECTION__()
+    .set_autosave(Duration::from_millis(interval));
+}
+
+#[tauri::command]
+pub(crate) async fn set_path<R>(
+  window: WebviewWindow<R>,
+  id: StoreId,
+  pointer: String,
+  value: Json,
+) -> Result<()>
+where
+  R: Runtime,
+{
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app
+    .__STORE_COLLECTION__()
+    .with_store(id, move |store| store.set_path_with_source(pointer, value, label))?
+}
+
+#[tauri::command]
+pub(crate) async fn set_save_strategy<R>(
+  app: AppHandle<R>,
+  id: StoreId,
+  strategy: SaveStrategy,
+) -> Result<()>
+where
+  R: Runtime,
+{
+  app
+    .__STORE_COLLECTION__()
+    .with_store(id, |store| store.set_save_strategy(strategy))
+}
+
+#[tauri::command]
+pub(crate) async fn set_store_options<R>(
+  window: WebviewWindow<R>,
+  id: StoreId,
+  options: StoreOptions,
+) -> Result<()>
+where
+  R: Runtime,
+{
+  let app = window.app_handle();
+  let label = window.label().to_owned();
+  app
+    .__STORE_COLLECTION__()
+    .with_store(id, move |store| {
+      store.set_options_with_source(options, label)
+    })?
+}
+
+#[tauri::command]
+pub(crate) async fn undo<R>(app: AppHandle<R>, id: StoreId) -> Result<bool>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().undo(id)
+}
+
+#[tauri::command]
+pub(crate) async fn unload<R>(app: AppHandle<R>, id: StoreId) -> Result<()>
+where
+  R: Runtime,
+{
+  app.__STORE_COLLECTION__().unload_store(&id)
+}
@@ -11,11 +11,55 @@ export const allowSave = commands.allowSave('__PLUGIN_TITLE__');
  */
 export const allowSync = commands.allowSync('__PLUGIN_TITLE__');
 
+/**
+ * Atomically updates the value of a key in the Rust side,
+ * returning the new value. This is safe even if multiple windows update the key at once.
+ *
+ * @example
+ * ```ts
+ * import { applyOp } from '__PACKAGE_NAME__';
+ *
+ * const count = await applyOp('my-store', 'counter', { op: 'increment', by: 2 });
+ * ```
+ */
+export const applyOp = commands.applyOp('__PLUGIN_TITLE__');
+
+/**
+ * Applies multiple changes to a store at once,
+ * emitting a single change event and saving the store only once.
+ *
+ * @example
+ * ```ts
+ * import { batch } from '__PACKAGE_NAME__';
+ *
+ * await batch('my-store', [
+ *   { op: 'set', key: 'theme', value: 'dark' },
+ *   { op: 'remove', key: 'draft' },
+ * ]);
+ * ```
+ */
+export const batch = commands.batch('__PLUGIN_TITLE__');
+
+/**
+ * Whether there is a change to redo in a store.
+ */
+export const canRedo = commands.canRedo('__PLUGIN_TITLE__');
+
+/**
+ * Whether there is a change to undo in a store.
+ */
+export const canUndo = commands.canUndo('__PLUGIN_TITLE__');
+
 /**
  * Stops the autosave.
  */
 export const clearAutosave = commands.clearAutosave('__PLUGIN_TITLE__');
 
+/**
+ * Discards every change that could be undone or redone in a store.
+ */
+export const clearHistory = commands.clearHistory('__PLUGIN_TITLE__');
+
 /**
  * Adds one or more stores to the save denylist.
  */
@@ -26,12 +70,31 @@ export const denySave = commands.denySave('__PLUGIN_TITLE__');
  */
 export const denySync = commands.denySync('__PLUGIN_TITLE__');
 
+/**
+ * Reads a blob referenced by a key in a store.
+ * Rejects if the key does not reference a blob.
+ */
+export const getBlob = commands.getBlob('__PLUGIN_TITLE__');
+
 /**
  * Gets the default save strategy for the stores.
  * It can be overridden on a per-store basis.
  */
 export const getDefaultSaveStrategy = commands.getDefaultSaveStrategy('__PLUGIN_TITLE__');
 
+/**
+ * Gets the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) of a store,
+ * where the first token is the key, or `null` if it does not exist.
+ *
+ * @example
+ * ```ts
+ * import { getPath } from '__PACKAGE_NAME__';
+ *
+ * const fontSize = await getPath<number>('my-store', '/settings/editor/fontSize');
+ * ```
+ */
+export const getPath = commands.getPath('__PLUGIN_TITLE__');
+
 /**
  * Directory where the stores are saved.
  */
@@ -42,6 +105,11 @@ export const getStoreCollectionPath = commands.getStoreCollectionPath('__PLUGIN_
  **/
 export const getSaveStrategy = commands.getSaveStrategy('__PLUGIN_TITLE__');
 
+/**
+ * Lists the ids of the stores saved on the disk, including those that are not loaded.
+ */
+export const getPersistedStoreIds = commands.getPersistedStoreIds('__PLUGIN_TITLE__');
+
 /**
  * Lists all the store ids.
  */
@@ -57,6 +125,60 @@ export const getStorePath = commands.getStorePath('__PLUGIN_TITLE__');
  */
 export const getStoreState = commands.getStoreState('__PLUGIN_TITLE__');
 
+/**
+ * Applies a [JSON Merge Patch](https://datatracker.ietf.org/doc/html/rfc7396) to a store,
+ * merging nested objects and removing the keys set to `null`.
+ *
+ * @example
+ * ```ts
+ * import { mergePatch } from '__PACKAGE_NAME__';
+ *
+ * await mergePatch('my-store', { settings: { theme: 'dark' }, draft: null });
+ * ```
+ */
+export const mergePatch = commands.mergePatch('__PLUGIN_TITLE__');
+
+/**
+ * Saves a blob to a store and sets a reference to it under the given key.
+ *
+ * @example
+ * ```ts
+ * import { putBlob } from '__PACKAGE_NAME__';
+ *
+ * const response = await fetch('https://example.com/avatar.png');
+ * await putBlob('my-store', 'avatar', await response.arrayBuffer());
+ * ```
+ */
+export const putBlob = commands.putBlob('__PLUGIN_TITLE__');
+
+/**
+ * Reapplies the last change to a store that was undone, returning whether there was anything to redo.
+ */
+export const redo = commands.redo('__PLUGIN_TITLE__');
+
+/**
+ * Removes the reference to a blob from a store, returning whether it existed.
+ */
+export const removeBlob = commands.removeBlob('__PLUGIN_TITLE__');
+
+/**
+ * Removes the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) of a store,
+ * returning it, or `null` if it did not exist.
+ */
+export const removePath = commands.removePath('__PLUGIN_TITLE__');
+
+/**
+ * Restores a store to its default values, removing every key that has none.
+ *
+ * @example
+ * ```ts
+ * import { reset } from '__PACKAGE_NAME__';
+ *
+ * await reset('my-store');
+ * ```
+ */
+export const reset = commands.reset('__PLUGIN_TITLE__');
+
 /**
  * Saves a store to the disk.
  *
@@ -113,6 +235,19 @@ export const saveNow = commands.saveNow('__PLUGIN_TITLE__');
  */
 export const setAutosave = commands.setAutosave('__PLUGIN_TITLE__');
 
+/**
+ * Sets the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) of a store,
+ * creating the intermediate objects that don't exist.
+ *
+ * @example
+ * ```ts
+ * import { setPath } from '__PACKAGE_NAME__';
+ *
+ * await setPath('my-store', '/settings/editor/fontSize', 14);
+ * ```
+ */
+export const setPath = commands.setPath('__PLUGIN_TITLE__');
+
 /**
  * Sets the save strategy for a store.
  * Calling this will abort any pending save operation.
@@ -142,6 +277,20 @@ export const setSaveStrategy = commands.setSaveStrategy('__PLUGIN_TITLE__');
  */
 export const setStoreOptions = commands.setStoreOptions('__PLUGIN_TITLE__');
 
+/**
+ * Reverts the last change to a store, returning whether there was anything to undo.
+ * This requires the store to have a history depth.
+ *
+ * @example
+ * ```ts
+ * import { setStoreOptions, undo } from '__PACKAGE_NAME__';
+ *
+ * await setStoreOptions('my-store', { historyDepth: 50 });
+ * await undo('my-store');
+ * ```
+ */
+export const undo = commands.undo('__PLUGIN_TITLE__');
+
 /////////////////////////
 // INTERNAL COMMANDS
 /////////////////////////
@@ -24,6 +24,21 @@ pub fn impl_collection(ast: &DeriveInput) -> TokenStream {
           self.0.ids()
         }
 
+        /// Lists the ids of the stores saved on the disk, whether they are loaded or not.
+        pub fn persisted_ids(&self) -> Result<Vec<StoreId>> {
+          self.0.persisted_ids()
+        }
+
+        /// Loads the stores in parallel, so that they're ready by the time they're first accessed.
+        pub fn preload(&self, ids: &[impl AsRef<str>]) -> Result<()> {
+          self.0.preload(ids)
+        }
+
+        /// Loads every store saved on the disk in parallel.
+        pub fn preload_all(&self) -> Result<()> {
+          self.0.preload_all()
+        }
+
         /// Directory where the stores are saved.
         pub fn path(&self) -> &Path {
           self.0.path()
@@ -34,6 +49,31 @@ pub fn impl_collection(ast: &DeriveInput) -> TokenStream {
           self.0.path_of(store_id)
         }
 
+        /// Layout used to save the stores.
+        pub fn layout(&self) -> CollectionLayout {
+          self.0.layout()
+        }
+
+        /// Converts the stores saved on disk to another layout, which the collection will use from now on.
+        pub fn convert_layout(&self, layout: CollectionLayout) -> Result<()> {
+          self.0.convert_layout(layout)
+        }
+
+        /// Whether the collection was opened as read-only because another process holds its lock.
+        pub fn is_read_only(&self) -> bool {
+          self.0.is_read_only()
+        }
+
+        /// Whether changes to the stores are shared with other processes using the same directory.
+        pub fn is_process_synced(&self) -> bool {
+          self.0.is_process_synced()
+        }
+
+        /// Whether each key of the store is saved to its own file.
+        pub fn is_sharded(&self, store_id: impl AsRef<str>) -> bool {
+          self.0.is_sharded(store_id)
+        }
+
         /// Calls a closure with a mutable reference to the store with the given id.
         pub fn with_store<F, T>(&self, id: impl AsRef<str>, f: F) -> Result<T>
         where
@@ -107,6 +147,20 @@ pub fn impl_collection(ast: &DeriveInput) -> TokenStream {
           self.0.get(store_id, key)
         }
 
+        /// Gets a raw value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) from a store.
+        pub fn get_raw_path(&self, store_id: impl AsRef<str>, pointer: impl AsRef<str>) -> Option<Json> {
+          self.0.get_raw_path(store_id, pointer)
+        }
+
+        /// Gets a value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) from a store
+        /// and tries to parse it as an instance of type `T`.
+        pub fn get_path<T>(&self, store_id: impl AsRef<str>, pointer: impl AsRef<str>) -> Result<T>
+        where
+          T: DeserializeOwned,
+        {
+          self.0.get_path(store_id, pointer)
+        }
+
         /// Gets a value from a store and tries to parse it as an instance of type `T`.
         ///
         /// If the key does not exist, returns the provided default value.
@@ -160,6 +214,70 @@ pub fn impl_collection(ast: &DeriveInput) -> TokenStream {
           self.0.set(id, key, value)
         }
 
+        /// Restores a key of a store to its default value, removing it if it has none.
+        pub fn reset_key(&self, id: impl AsRef<str>, key: impl AsRef<str>) -> Result<()> {
+          self.0.reset_key(id, key)
+        }
+
+        /// Restores a store to its default values, removing every key that has none.
+        pub fn reset(&self, id: impl AsRef<str>) -> Result<()> {
+          self.0.reset(id)
+        }
+
+        /// Sets a value that takes precedence over every other layer of a layered store.
+        pub fn set_override<K, V>(&self, id: impl AsRef<str>, key: K, value: V) -> Result<()>
+        where
+          K: AsRef<str>,
+          V: Into<Json>,
+        {
+          self.0.set_override(id, key, value)
+        }
+
+        /// Removes an override from a layered store, returning its value, if any.
+        pub fn remove_override(&self, id: impl AsRef<str>, key: impl AsRef<str>) -> Result<Option<Json>> {
+          self.0.remove_override(id, key)
+        }
+
+        /// Removes every override from a layered store.
+        pub fn clear_overrides(&self, id: impl AsRef<str>) -> Result<()> {
+          self.0.clear_overrides(id)
+        }
+
+        /// Sets a key-value pair in a store that is removed once the given duration elapses.
+        pub fn set_with_ttl<K, V>(&self, id: impl AsRef<str>, key: K, value: V, ttl: Duration) -> Result<()>
+        where
+          K: AsRef<str>,
+          V: Into<Json>,
+        {
+          self.0.set_with_ttl(id, key, value, ttl)
+        }
+
+        /// Time left until a key of a store expires, if it was set with a time-to-live.
+        pub fn ttl(&self, id: impl AsRef<str>, key: impl AsRef<str>) -> Result<Option<Duration>> {
+          self.0.ttl(id, key)
+        }
+
+        /// Removes the expired keys of every loaded store, returning how many were removed.
+        pub fn purge_expired(&self) -> Result<usize> {
+          self.0.purge_expired()
+        }
+
+        /// Sets a value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) in a store,
+        /// creating the intermediate objects that don't exist.
+        pub fn set_path<P, V>(&self, store_id: impl AsRef<str>, pointer: P, value: V) -> Result<()>
+        where
+          P: AsRef<str>,
+          V: Into<Json>,
+        {
+          self.0.set_path(store_id, pointer, value)
+        }
+
+        /// Removes the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901)
+        /// from a store, returning it, if any.
+        pub fn remove_path(&self, store_id: impl AsRef<str>, pointer: impl AsRef<str>) -> Result<Option<Json>> {
+          self.0.remove_path(store_id, pointer)
+        }
+
         /// Patches a store state.
         pub fn patch<S>(&self, store_id: impl AsRef<str>, state: S) -> Result<()>
         where
@@ -168,6 +286,126 @@ pub fn impl_collection(ast: &DeriveInput) -> TokenStream {
           self.0.patch(store_id, state)
         }
 
+        /// Applies a [JSON Merge Patch](https://datatracker.ietf.org/doc/html/rfc7396) to a store state.
+        pub fn merge_patch<S>(&self, store_id: impl AsRef<str>, patch: S) -> Result<()>
+        where
+          S: Into<StoreState>,
+        {
+          self.0.merge_patch(store_id, patch)
+        }
+
+        /// Replaces the value of a key with the one returned by the closure, returning the new value.
+        pub fn update<K, F>(&self, store_id: impl AsRef<str>, key: K, f: F) -> Result<Json>
+        where
+          K: AsRef<str>,
+          F: FnOnce(Option<&Json>) -> Result<Json>,
+        {
+          self.0.update(store_id, key, f)
+        }
+
+        /// Applies an atomic operation to the value of a key, returning the new value.
+        pub fn apply_op<K>(&self, store_id: impl AsRef<str>, key: K, op: AtomicOp) -> Result<Json>
+        where
+          K: AsRef<str>,
+        {
+          self.0.apply_op(store_id, key, op)
+        }
+
+        /// Reverts the last change to a store, returning whether there was anything to undo.
+        pub fn undo(&self, store_id: impl AsRef<str>) -> Result<bool> {
+          self.0.undo(store_id)
+        }
+
+        /// Reapplies the last change to a store that was undone, returning whether there was anything to redo.
+        pub fn redo(&self, store_id: impl AsRef<str>) -> Result<bool> {
+          self.0.redo(store_id)
+        }
+
+        /// Whether there is a change to undo in a store.
+        pub fn can_undo(&self, store_id: impl AsRef<str>) -> Result<bool> {
+          self.0.can_undo(store_id)
+        }
+
+        /// Whether there is a change to redo in a store.
+        pub fn can_redo(&self, store_id: impl AsRef<str>) -> Result<bool> {
+          self.0.can_redo(store_id)
+        }
+
+        /// Discards every change that could be undone or redone in a store.
+        pub fn clear_history(&self, store_id: impl AsRef<str>) -> Result<()> {
+          self.0.clear_history(store_id)
+        }
+
+        /// Applies every change made in the closure to a store at once.
+        pub fn batch<F, T>(&self, store_id: impl AsRef<str>, f: F) -> Result<T>
+        where
+          F: FnOnce(&mut Batch<'_>) -> T,
+        {
+          self.0.batch(store_id, f)
+        }
+
+        /// Updates multiple stores atomically.
+        pub fn transaction<I, T, F, U>(&self, ids: I, f: F) -> Result<U>
+        where
+          I: IntoIterator<Item = T>,
+          T: AsRef<str>,
+          F: FnOnce(&mut Transaction<'_>) -> Result<U>,
+        {
+          self.0.transaction(ids, f)
+        }
+
+        /// Saves a blob to a store and sets a reference to it under the given key.
+        pub fn put_blob<K, B>(&self, store_id: impl AsRef<str>, key: K, bytes: B) -> Result<()>
+        where
+          K: AsRef<str>,
+          B: AsRef<[u8]>,
+        {
+          self.0.put_blob(store_id, key, bytes)
+        }
+
+        /// Reads a blob referenced by a key in a store.
+        pub fn get_blob(&self, store_id: impl AsRef<str>, key: impl AsRef<str>) -> Result<Option<Vec<u8>>> {
+          self.0.get_blob(store_id, key)
+        }
+
+        /// Removes the reference to a blob from a store, returning whether it existed.
+        pub fn remove_blob(&self, store_id: impl AsRef<str>, key: impl AsRef<str>) -> Result<bool> {
+          self.0.remove_blob(store_id, key)
+        }
+
+        /// Deletes the blobs that are no longer referenced by a store.
+        pub fn collect_blobs(&self, store_id: impl AsRef<str>) -> Result<usize> {
+          self.0.collect_blobs(store_id)
+        }
+
+        /// Whether the store is backed by a CRDT.
+        pub fn is_crdt(&self, store_id: impl AsRef<str>) -> bool {
+          self.0.is_crdt(store_id)
+        }
+
+        /// Clock of the CRDT of a store.
+        pub fn crdt_clock(&self, store_id: impl AsRef<str>) -> Result<u64> {
+          self.0.crdt_clock(store_id)
+        }
+
+        /// Encodes the changes to a store as an update that can be merged into other replicas.
+        pub fn export_update(&self, store_id: impl AsRef<str>, since: Option<u64>) -> Result<Vec<u8>> {
+          self.0.export_update(store_id, since)
+        }
+
+        /// Merges an update exported by another replica of a store.
+        pub fn import_update<U>(&self, store_id: impl AsRef<str>, update: U) -> Result<()>
+        where
+          U: AsRef<[u8]>,
+        {
+          self.0.import_update(store_id, update)
+        }
+
+        /// Saves and unloads the stores that the eviction policy no longer allows to stay loaded.
+        pub fn evict(&self) -> Result<()> {
+          self.0.evict()
+        }
+
         /// Saves a store to the disk.
         pub fn save(&self, id: impl AsRef<str>) -> Result<()> {
           self.0.save(id)
@@ -188,6 +426,16 @@ pub fn impl_collection(ast: &DeriveInput) -> TokenStream {
           self.0.save_now(id)
         }
 
+        /// Saves a store to the disk immediately, even if it has not changed since it was last saved.
+        pub fn force_save_now(&self, id: impl AsRef<str>) -> Result<()> {
+          self.0.force_save_now(id)
+        }
+
+        /// Whether a store has changed since it was last saved.
+        pub fn is_dirty(&self, id: impl AsRef<str>) -> Result<bool> {
+          self.0.is_dirty(id)
+        }
+
         /// Saves some stores to the disk.
         pub fn save_some(&self, ids: &[impl AsRef<str>]) -> Result<()> {
           self.0.save_some(ids)
@@ -204,6 +452,26 @@ pub fn impl_collection(ast: &DeriveInput) -> TokenStream {
           self.0.default_save_strategy()
         }
 
+        /// How the stores apply a partial state when they are patched.
+        pub fn default_patch_mode(&self) -> PatchMode {
+          self.0.default_patch_mode()
+        }
+
+        /// Pushes the changes waiting to be synchronized with the remote, then pulls the remote ones.
+        pub async fn sync_now(&self) -> Result<()> {
+          self.0.sync_now().await
+        }
+
+        /// Status of the synchronization with the remote, if a sync adapter was set.
+        pub fn sync_status(&self) -> Option<SyncStatus> {
+          self.0.sync_status()
+        }
+
+        /// Amount of changes waiting to be pushed to the remote.
+        pub fn pending_changes(&self) -> usize {
+          self.0.pending_changes()
+        }
+
         /// Saves the stores periodically.
         pub fn set_autosave(&self, duration: Duration) {
           self.0.set_autosave(duration)
@@ -222,6 +490,46 @@ pub fn impl_collection(ast: &DeriveInput) -> TokenStream {
           self.0.watch(store_id, f)
         }
 
+        /// Watches a store for changes, receiving what has changed.
+        pub fn watch_changes<F>(&self, store_id: impl AsRef<str>, f: F) -> Result<WatcherId>
+        where
+          F: Fn(AppHandle<R>, &StoreChange) -> Result<()> + Send + Sync + 'static,
+        {
+          self.0.watch_changes(store_id, f)
+        }
+
+        /// Watches a key of a store for changes.
+        pub fn watch_key<F>(&self, store_id: impl AsRef<str>, key: impl AsRef<str>, f: F) -> Result<WatcherId>
+        where
+          F: Fn(AppHandle<R>, &StoreChange) -> Result<()> + Send + Sync + 'static,
+        {
+          self.0.watch_key(store_id, key, f)
+        }
+
+        /// Watches the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901)
+        /// of a store for changes.
+        pub fn watch_path<F>(&self, store_id: impl AsRef<str>, pointer: impl AsRef<str>, f: F) -> Result<WatcherId>
+        where
+          F: Fn(AppHandle<R>, &StoreChange) -> Result<()> + Send + Sync + 'static,
+        {
+          self.0.watch_path(store_id, pointer, f)
+        }
+
+        /// Watches some keys of a store for changes.
+        pub fn watch_keys<I, T, F>(&self, store_id: impl AsRef<str>, keys: I, f: F) -> Result<WatcherId>
+        where
+          I: IntoIterator<Item = T>,
+          T: AsRef<str>,
+          F: Fn(AppHandle<R>, &StoreChange) -> Result<()> + Send + Sync + 'static,
+        {
+          self.0.watch_keys(store_id, keys, f)
+        }
+
+        /// Subscribes to the changes of a store.
+        pub fn subscribe(&self, store_id: impl AsRef<str>) -> ChangeReceiver {
+          self.0.subscribe(store_id)
+        }
+
         /// Removes a watcher from a store.
         pub fn unwatch(&self, store_id: impl AsRef<str>, watcher_id: impl Into<WatcherId>) -> Result<bool> {
           self.0.unwatch(store_id, watcher_id)
@@ -1,19 +1,32 @@
+use super::container::{CollectionLayout, Container};
 use super::handle::Handle;
+use super::lock::{CollectionLock, LockPolicy};
 use super::marker::CollectionMarker;
+use super::process_sync::ProcessSync;
 use super::{DefaultMarker, OnLoadFn, StoreCollection};
 use crate::collection::autosave::Autosave;
+use crate::collection::eviction::{Eviction, EvictionPolicy};
+use crate::collection::inherit::InheritQueue;
+use crate::collection::parallel::parallelism;
+use crate::collection::sweep::ExpirySweep;
 use crate::collection::table::{MarshalerTable, PathTable};
-use crate::error::Result;
+use crate::collection::writer::Writer;
+use crate::error::{Error, Result};
+use crate::io_err;
 use crate::manager::ManagerExt;
 use crate::migration::{Migration, MigrationContext, Migrator};
-use crate::store::{JsonMarshaler, Marshaler, SaveStrategy, Store, StoreId};
+use crate::remote::{RemoteSync, SyncAdapter};
+use crate::store::{JsonMarshaler, Marshaler, PatchMode, SaveStrategy, Store, StoreId, StoreState};
 use dashmap::{DashMap, DashSet};
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::{Mutex, RwLock};
 use std::time::Duration;
-use tauri::{Manager, Runtime};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager, Runtime};
 
 #[cfg(feature = "plugin")]
 use tauri::plugin::TauriPlugin;
@@ -28,12 +41,31 @@ where
   path_table: HashMap<StoreId, Box<Path>>,
   default_marshaler: Option<Box<dyn Marshaler>>,
   marshaler_table: HashMap<StoreId, Box<dyn Marshaler>>,
+  defaults: HashMap<StoreId, Result<StoreState>>,
+  default_resources: HashMap<StoreId, PathBuf>,
+  layered: DashSet<StoreId>,
+  env_prefixes: HashMap<StoreId, String>,
+  parents: HashMap<StoreId, StoreId>,
+  preload: Vec<StoreId>,
+  preload_all: bool,
+  layout: CollectionLayout,
   default_save_strategy: SaveStrategy,
+  default_patch_mode: PatchMode,
   autosave: Option<Duration>,
+  ttl_sweep_interval: Duration,
+  eviction_policy: EvictionPolicy,
   on_load: Option<Box<OnLoadFn<R, C>>>,
   save_denylist: DashSet<StoreId>,
   sync_denylist: DashSet<StoreId>,
+  journal: DashSet<StoreId>,
+  journal_limit: usize,
+  sharded: DashSet<StoreId>,
+  crdt: DashSet<StoreId>,
   migrator: Migrator,
+  lock_policy: Option<LockPolicy>,
+  process_sync: Option<Duration>,
+  sync_adapter: Option<Box<dyn SyncAdapter>>,
+  sync_interval: Duration,
   debug_stores: bool,
 }
 
@@ -47,6 +79,29 @@ where
     Self::default()
   }
 
+  /// Loads the given stores in parallel while the collection is being built,
+  /// instead of when they're first accessed.
+  #[must_use]
+  pub fn preload<I, T>(mut self, stores: I) -> Self
+  where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+  {
+    let stores = stores
+      .into_iter()
+      .map(|it| StoreId::from(it.as_ref()));
+
+    self.preload.extend(stores);
+    self
+  }
+
+  /// Loads every store saved on the disk in parallel while the collection is being built.
+  #[must_use]
+  pub fn preload_all(mut self) -> Self {
+    self.preload_all = true;
+    self
+  }
+
   /// Sets the autosave interval for all stores.
   #[must_use]
   pub fn autosave(mut self, duration: Duration) -> Self {
@@ -54,6 +109,16 @@ where
     self
   }
 
+  /// Sets how often the keys whose time-to-live has elapsed are removed from the loaded stores.
+  ///
+  /// Expired keys are also removed whenever a store is accessed.
+  /// By default, this happens every minute.
+  #[must_use]
+  pub fn ttl_sweep_interval(mut self, interval: Duration) -> Self {
+    self.ttl_sweep_interval = interval;
+    self
+  }
+
   /// Sets the default save strategy to be used by the stores.
   #[must_use]
   pub fn default_save_strategy(mut self, strategy: SaveStrategy) -> Self {
@@ -61,6 +126,15 @@ where
     self
   }
 
+  /// Sets how the stores apply a partial state when they are patched.
+  ///
+  /// This also applies to the patches sent by the frontend.
+  #[must_use]
+  pub fn default_patch_mode(mut self, mode: PatchMode) -> Self {
+    self.default_patch_mode = mode;
+    self
+  }
+
   /// Registers a closure to be called when a store is loaded.
   #[must_use]
   pub fn on_load<F>(mut self, f: F) -> Self
@@ -87,6 +161,96 @@ where
     self
   }
 
+  /// Sets the default values of a store.
+  ///
+  /// Every key that is missing when the store is loaded is set to its default, which is then
+  /// saved like any other change, and [`Store::reset`] restores them. Anything that serializes to a map can be used,
+  /// including a [`StoreState`].
+  #[must_use]
+  pub fn defaults_of(mut self, id: impl AsRef<str>, defaults: impl Serialize) -> Self {
+    let id = StoreId::from(id.as_ref());
+    let defaults = serde_json::to_value(defaults)
+      .and_then(serde_json::from_value)
+      .map_err(Into::into);
+
+    self.defaults.insert(id, defaults);
+    self
+  }
+
+  /// Reads the default values of a store from a file bundled with the app.
+  ///
+  /// The path is resolved against the resource directory, and the file is deserialized
+  /// with the marshaler of the store. Keys set with [`defaults_of`](Self::defaults_of)
+  /// take precedence over the ones in the file.
+  #[must_use]
+  pub fn defaults_from_resource(mut self, id: impl AsRef<str>, path: impl AsRef<Path>) -> Self {
+    let id = StoreId::from(id.as_ref());
+    self
+      .default_resources
+      .insert(id, path.as_ref().to_path_buf());
+
+    self
+  }
+
+  /// Sets a list of stores whose state should be split into layers.
+  ///
+  /// From the lowest to the highest precedence, the layers are the [defaults](Self::defaults_of),
+  /// the persisted state, the [environment](Self::env_prefix_of) and the
+  /// [overrides](crate::Store::set_override). Every reader, including the frontend, sees the merge
+  /// of all of them, with nested objects merged across layers, but only the persisted state is saved.
+  #[must_use]
+  pub fn layered<I, T>(mut self, stores: I) -> Self
+  where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+  {
+    let stores = stores
+      .into_iter()
+      .map(|it| StoreId::from(it.as_ref()));
+
+    self.layered.extend(stores);
+    self
+  }
+
+  /// Sets the prefix of the environment variables that override the keys of a store,
+  /// which is then [layered](Self::layered).
+  ///
+  /// With the `MYAPP_SETTINGS` prefix, `MYAPP_SETTINGS__THEME` sets the `theme` key,
+  /// while `MYAPP_SETTINGS__EDITOR__FONT_SIZE` sets the `font_size` field of the `editor` key.
+  /// Names are lowercased, and values are parsed as JSON, falling back to a string.
+  /// The variables are read when the store is loaded.
+  #[must_use]
+  pub fn env_prefix_of(mut self, id: impl AsRef<str>, prefix: impl Into<String>) -> Self {
+    let id = StoreId::from(id.as_ref());
+    self.layered.insert(id.clone());
+    self.env_prefixes.insert(id, prefix.into());
+    self
+  }
+
+  /// Makes a store fall back to another one for the keys it doesn't have.
+  ///
+  /// Reads on the child, including the state seen by the frontend, resolve through
+  /// the whole parent chain, while writes only ever change the child. Whenever a key of
+  /// the parent changes, the child notifies about it too, unless it has that key on its own.
+  #[must_use]
+  pub fn parent_of(mut self, child: impl AsRef<str>, parent: impl AsRef<str>) -> Self {
+    let child = StoreId::from(child.as_ref());
+    let parent = StoreId::from(parent.as_ref());
+    self.parents.insert(child, parent);
+    self
+  }
+
+  /// Sets the policy used to unload the stores that are no longer in use.
+  ///
+  /// By default, stores stay loaded until they're explicitly unloaded.
+  /// Use [`Store::evictable`] to exempt a store from the policy. Stores that are watched or
+  /// subscribed to, or that have undo or redo history or overrides, are always exempt.
+  #[must_use]
+  pub fn eviction_policy(mut self, policy: EvictionPolicy) -> Self {
+    self.eviction_policy = policy;
+    self
+  }
+
   /// Sets a list of stores that should not be saved to disk.
   #[must_use]
   pub fn save_denylist<I, T>(mut self, denylist: I) -> Self
@@ -117,6 +281,75 @@ where
     self
   }
 
+  /// Sets a list of stores that should record their changes to a write-ahead log.
+  ///
+  /// Instead of saving the whole state on every change, journaled stores append the change
+  /// to a `.wal` file next to the store file, which is replayed when the store is loaded.
+  /// The log is folded into the store file whenever the store is saved.
+  #[must_use]
+  pub fn journal<I, T>(mut self, stores: I) -> Self
+  where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+  {
+    let stores = stores
+      .into_iter()
+      .map(|it| StoreId::from(it.as_ref()));
+
+    self.journal.extend(stores);
+    self
+  }
+
+  /// Maximum amount of records in a write-ahead log before it is compacted.
+  ///
+  /// The default is 1000.
+  #[must_use]
+  pub fn journal_limit(mut self, limit: usize) -> Self {
+    self.journal_limit = limit;
+    self
+  }
+
+  /// Sets a list of stores whose keys should each be saved to their own file.
+  ///
+  /// The files are placed in a directory named after the store, and only the keys
+  /// that have changed since the last save are rewritten. All of them are still read
+  /// when the store is loaded. This is ignored when the layout is [`CollectionLayout::Container`].
+  ///
+  /// Each file is named after its key, percent-encoded so that keys differing only in case
+  /// don't collide. Keys too long for a file name are cut short and followed by their hash.
+  #[must_use]
+  pub fn shard<I, T>(mut self, stores: I) -> Self
+  where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+  {
+    let stores = stores
+      .into_iter()
+      .map(|it| StoreId::from(it.as_ref()));
+
+    self.sharded.extend(stores);
+    self
+  }
+
+  /// Sets a list of stores that should be backed by a CRDT.
+  ///
+  /// Their changes can be [exported](crate::Store::export_update) as updates and merged
+  /// into other replicas of the store, such as the same store on another device, without conflicts.
+  /// The CRDT is saved to its own file next to the store.
+  #[must_use]
+  pub fn crdt<I, T>(mut self, stores: I) -> Self
+  where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+  {
+    let stores = stores
+      .into_iter()
+      .map(|it| StoreId::from(it.as_ref()));
+
+    self.crdt.extend(stores);
+    self
+  }
+
   /// Defines how the stores should be serialized and deserialized.
   #[must_use]
   pub fn marshaler(mut self, marshaler: Box<dyn Marshaler>) -> Self {
@@ -132,6 +365,61 @@ where
     self
   }
 
+  /// Defines how the stores should be laid out on disk.
+  ///
+  /// To convert the stores that were previously saved with another layout,
+  /// use [`StoreCollection::convert_layout`](crate::StoreCollection::convert_layout).
+  #[must_use]
+  pub fn layout(mut self, layout: CollectionLayout) -> Self {
+    self.layout = layout;
+    self
+  }
+
+  /// Locks the collection directories, so that other processes can't use them at the same time.
+  ///
+  /// Besides the default directory, every directory set with [`path_of`](Self::path_of) is locked.
+  ///
+  /// The policy defines what to do when another process already holds the lock.
+  /// By default, the directory is not locked at all.
+  #[must_use]
+  pub fn lock_policy(mut self, policy: LockPolicy) -> Self {
+    self.lock_policy = Some(policy);
+    self
+  }
+
+  /// Shares the changes made to the stores with other processes using the same directory.
+  ///
+  /// Every change made by `set`, `patch` or to a blob is appended to a log owned by this process,
+  /// while the logs of the other processes are read at the given interval. Their changes are
+  /// then applied to the local stores as if they were made by the backend. When two processes
+  /// change the same key, the most recent change wins, and ties are broken the same way
+  /// everywhere, so all processes end up with the same state.
+  #[must_use]
+  pub fn process_sync(mut self, interval: Duration) -> Self {
+    self.process_sync = Some(interval);
+    self
+  }
+
+  /// Synchronizes the stores with a remote, such as a backend server.
+  ///
+  /// Every change made locally is added to a queue that is persisted to the disk,
+  /// so changes made while offline are pushed once the remote is reachable again.
+  /// If a sync fails, the next one is delayed exponentially.
+  #[must_use]
+  pub fn sync_adapter(mut self, adapter: Box<dyn SyncAdapter>) -> Self {
+    self.sync_adapter = Some(adapter);
+    self
+  }
+
+  /// Sets how often the stores are synchronized with the remote.
+  ///
+  /// The default is 30 seconds.
+  #[must_use]
+  pub fn sync_interval(mut self, interval: Duration) -> Self {
+    self.sync_interval = interval;
+    self
+  }
+
   /// Adds a `.dev` suffix to the store files when in development mode.
   ///
   /// This is enabled by default.
@@ -199,6 +487,24 @@ where
       None => handle.get_sandboxed_path()?.join(plugin_name),
     };
 
+    let lock = match self.lock_policy {
+      Some(policy) => {
+        let dirs = self.path_table.values().map(AsRef::as_ref);
+        CollectionLock::acquire(dirs.chain([default_path.as_path()]), policy)?
+      }
+      None => None,
+    };
+
+    let read_only = self.lock_policy.is_some() && lock.is_none();
+    let remote_sync = self
+      .sync_adapter
+      .map(|adapter| RemoteSync::new(&default_path, adapter, self.sync_interval, read_only))
+      .transpose()?;
+
+    let process_sync = self
+      .process_sync
+      .map(|interval| ProcessSync::new(&default_path, interval, read_only));
+
     let path_table = PathTable {
       default: default_path.into_boxed_path(),
       table: self.path_table,
@@ -213,28 +519,53 @@ where
       table: self.marshaler_table,
     };
 
+    let defaults = read_defaults(
+      &app,
+      &marshaler_table,
+      self.default_resources,
+      self.defaults,
+    )?;
+
     app.manage(StoreCollection::<R, C> {
       handle,
       name: Box::from(plugin_name),
       path_table,
       marshaler_table,
+      defaults,
+      layered: self.layered,
+      env_prefixes: self.env_prefixes,
+      parents: validate_parents(self.parents)?,
+      layout: RwLock::new(self.layout),
+      container: Mutex::new(Container::default()),
       stores: DashMap::new(),
       on_load: self.on_load,
       autosave: Mutex::new(Autosave::new(self.autosave)),
+      expiry_sweep: ExpirySweep::new(self.ttl_sweep_interval),
+      eviction: Eviction::new(self.eviction_policy),
+      writer: Writer::new(parallelism()),
       default_save_strategy: self.default_save_strategy,
+      default_patch_mode: self.default_patch_mode,
       save_denylist: self.save_denylist,
       sync_denylist: self.sync_denylist,
+      journal: self.journal,
+      journal_limit: self.journal_limit,
+      sharded: self.sharded,
+      crdt: self.crdt,
       migrator: Mutex::new(self.migrator),
+      lock: Mutex::new(lock),
+      process_sync,
+      remote_sync,
+      subscribers: DashMap::new(),
+      inherit_queue: InheritQueue::default(),
+      read_only,
       debug_stores: self.debug_stores,
       phantom: PhantomData,
     });
 
     let collection = app.store_collection_with_marker::<C>();
-    collection
-      .autosave
-      .lock()
-      .expect("autosave is poisoned")
-      .start::<R, C>(&app);
+    if self.layout.is_container() {
+      collection.read_container()?;
+    }
 
     collection
       .migrator
@@ -242,10 +573,64 @@ where
       .expect("migrator is poisoned")
       .read::<R, C>(&app)?;
 
-    Ok(())
+    collection.start_tasks()?;
+    collection.recover_transactions()?;
+
+    if self.preload_all {
+      collection.preload_all()
+    } else {
+      collection.preload(&self.preload)
+    }
   }
 }
 
+/// Ensures no store is its own ancestor.
+fn validate_parents(parents: HashMap<StoreId, StoreId>) -> Result<HashMap<StoreId, StoreId>> {
+  for child in parents.keys() {
+    let mut seen = HashSet::from([child]);
+    let mut current = child;
+    while let Some(parent) = parents.get(current) {
+      if !seen.insert(parent) {
+        return io_err!(InvalidInput, "store has a cyclic parent chain: {child}");
+      }
+
+      current = parent;
+    }
+  }
+
+  Ok(parents)
+}
+
+/// Reads the defaults bundled as resources, then applies the ones set in the code on top of them.
+fn read_defaults<R: Runtime>(
+  app: &AppHandle<R>,
+  marshaler_table: &MarshalerTable,
+  resources: HashMap<StoreId, PathBuf>,
+  states: HashMap<StoreId, Result<StoreState>>,
+) -> Result<HashMap<StoreId, StoreState>> {
+  let mut defaults = HashMap::new();
+  for (id, path) in resources {
+    let path = app
+      .path()
+      .resolve(path, BaseDirectory::Resource)?;
+    let state = marshaler_table
+      .get(&id)
+      .deserialize(&fs::read(path)?)
+      .map_err(Error::FailedToDeserialize)?;
+
+    defaults.insert(id, state);
+  }
+
+  for (id, state) in states {
+    defaults
+      .entry(id)
+      .or_insert_with(StoreState::default)
+      .patch(state?);
+  }
+
+  Ok(defaults)
+}
+
 impl<R> StoreCollectionBuilder<R, DefaultMarker>
 where
   R: Runtime,
@@ -272,12 +657,31 @@ where
       path_table: HashMap::new(),
       default_marshaler: None,
       marshaler_table: HashMap::new(),
+      defaults: HashMap::new(),
+      default_resources: HashMap::new(),
+      layered: DashSet::new(),
+      env_prefixes: HashMap::new(),
+      parents: HashMap::new(),
+      preload: Vec::new(),
+      preload_all: false,
+      layout: CollectionLayout::default(),
       default_save_strategy: SaveStrategy::Immediate,
+      default_patch_mode: PatchMode::default(),
       autosave: None,
+      ttl_sweep_interval: Duration::from_secs(60),
+      eviction_policy: EvictionPolicy::default(),
       on_load: None,
       save_denylist: DashSet::new(),
       sync_denylist: DashSet::new(),
+      journal: DashSet::new(),
+      journal_limit: 1000,
+      sharded: DashSet::new(),
+      crdt: DashSet::new(),
       migrator: Migrator::default(),
+      lock_policy: None,
+      process_sync: None,
+      sync_adapter: None,
+      sync_interval: Duration::from_secs(30),
       debug_stores: true,
     }
   }
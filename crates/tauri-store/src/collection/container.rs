@@ -0,0 +1,167 @@
+use super::{CollectionMarker, StoreCollection};
+use crate::error::{Error, Result};
+use crate::io::{remove_file, write_file_atomic};
+use crate::io_err;
+use crate::migration::{self, MigrationHistory, Migrator};
+use crate::store::{Marshaler, StoreId, StoreState};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use tauri::Runtime;
+
+/// How the stores of a collection are laid out on disk.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CollectionLayout {
+  /// Each store is saved to its own file, alongside the migration history.
+  #[default]
+  File,
+  /// The state of every store, along with the migration history, is saved to a single file.
+  ///
+  /// The container is always serialized with the default marshaler and saved
+  /// to the default directory, ignoring any store-specific path or marshaler.
+  ///
+  /// Everything else still has its own file next to the container: journals, CRDT entries,
+  /// key deadlines and blobs of each store, as well as the collection's lock, the logs of
+  /// process sync, the queue of the sync adapter and the intent logs of transactions.
+  Container,
+}
+
+impl CollectionLayout {
+  /// Whether the layout is [`File`](CollectionLayout::File).
+  #[inline]
+  pub const fn is_file(&self) -> bool {
+    matches!(self, Self::File)
+  }
+
+  /// Whether the layout is [`Container`](CollectionLayout::Container).
+  #[inline]
+  pub const fn is_container(&self) -> bool {
+    matches!(self, Self::Container)
+  }
+}
+
+/// In-memory copy of the container file.
+///
+/// When using the container layout, this is the source of truth for every store
+/// that is not currently loaded, so it must be kept in sync with what is on disk.
+#[derive(Debug, Default)]
+pub(crate) struct Container {
+  pub(crate) stores: HashMap<StoreId, StoreState>,
+  pub(crate) migrations: MigrationHistory,
+}
+
+impl Container {
+  const VERSION: u64 = 1;
+
+  const KEY_VERSION: &'static str = "version";
+  const KEY_STORES: &'static str = "stores";
+  const KEY_MIGRATIONS: &'static str = "migrations";
+
+  pub(crate) fn read(path: &Path, marshaler: &dyn Marshaler) -> Result<Self> {
+    let mut state = match fs::read(path) {
+      Ok(bytes) => marshaler
+        .deserialize(&bytes)
+        .map_err(Error::FailedToDeserialize)?,
+      Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+      Err(err) => return Err(Error::Io(err)),
+    };
+
+    match take::<Option<u64>>(&mut state, Self::KEY_VERSION)? {
+      Some(Self::VERSION) => {}
+      Some(version) => return io_err!(InvalidData, "unsupported container version: {version}"),
+      None => return io_err!(InvalidData, "container has no version"),
+    }
+
+    Ok(Self {
+      stores: take(&mut state, Self::KEY_STORES)?,
+      migrations: take(&mut state, Self::KEY_MIGRATIONS)?,
+    })
+  }
+
+  pub(crate) fn write(&self, path: &Path, marshaler: &dyn Marshaler) -> Result<()> {
+    let state = StoreState::from([
+      (Self::KEY_VERSION, Value::from(Self::VERSION)),
+      (Self::KEY_STORES, serde_json::to_value(&self.stores)?),
+      (
+        Self::KEY_MIGRATIONS,
+        serde_json::to_value(&self.migrations)?,
+      ),
+    ]);
+
+    let bytes = marshaler
+      .serialize(&state)
+      .map_err(Error::FailedToSerialize)?;
+
+    write_file_atomic(path, &bytes)
+  }
+}
+
+/// Moves the stores from their own files into the container.
+pub(super) fn pack<R, C>(collection: &StoreCollection<R, C>, migrator: &Migrator) -> Result<()>
+where
+  R: Runtime,
+  C: CollectionMarker,
+{
+  let ids = collection.scan_store_files()?;
+  let mut container = collection
+    .container
+    .lock()
+    .expect("container is poisoned");
+
+  container.stores.clear();
+  for id in &ids {
+    let state = collection.read_store_file(id)?;
+    container.stores.insert(id.clone(), state);
+  }
+
+  container.migrations = migrator.history().clone();
+
+  let marshaler = collection.marshaler_table.default.as_ref();
+  container.write(&collection.container_path(), marshaler)?;
+
+  // The old files should only be removed after the container is safely written.
+  for id in &ids {
+    collection.remove_store_file(id)?;
+  }
+
+  remove_file(&migration::path::<R, C>(collection.app_handle()))
+}
+
+/// Moves the stores from the container into their own files.
+pub(super) fn unpack<R, C>(collection: &StoreCollection<R, C>, migrator: &Migrator) -> Result<()>
+where
+  R: Runtime,
+  C: CollectionMarker,
+{
+  let mut container = collection
+    .container
+    .lock()
+    .expect("container is poisoned");
+
+  for (id, state) in &container.stores {
+    collection.write_store_file(id, state)?;
+  }
+
+  migrator.write_file::<R, C>(collection.app_handle())?;
+  remove_file(&collection.container_path())?;
+
+  *container = Container::default();
+
+  Ok(())
+}
+
+fn take<T>(state: &mut StoreState, key: &str) -> Result<T>
+where
+  T: DeserializeOwned + Default,
+{
+  match state.remove(key) {
+    Some(value) => Ok(serde_json::from_value(value)?),
+    None => Ok(T::default()),
+  }
+}
@@ -0,0 +1,163 @@
+use super::CollectionMarker;
+use crate::manager::ManagerExt;
+use crate::store::StoreState;
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::async_runtime::spawn_blocking;
+use tauri::{AppHandle, Runtime};
+use tauri_store_utils::set_interval;
+use tokio::task::AbortHandle;
+
+/// How often the stores are checked against the policy, unless the idle timeout is shorter.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Limits on which stores may stay loaded.
+///
+/// Stores that break any of them are saved and then unloaded, starting with the least recently used.
+/// They are loaded again the next time they're accessed.
+///
+/// Stores that are still in use, as they are watched or subscribed to, are never evicted.
+/// Neither are those with undo or redo history or overrides, which only live in memory.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EvictionPolicy {
+  /// Unloads the stores that haven't been accessed for this long.
+  pub idle_timeout: Option<Duration>,
+  /// Maximum amount of stores that can be loaded at once.
+  pub max_stores: Option<usize>,
+  /// Maximum size of the loaded stores, estimated from their state.
+  ///
+  /// It is checked whenever a store is loaded and on every periodic check.
+  pub max_bytes: Option<usize>,
+}
+
+impl EvictionPolicy {
+  /// Returns a policy that never evicts anything.
+  pub const fn new() -> Self {
+    Self {
+      idle_timeout: None,
+      max_stores: None,
+      max_bytes: None,
+    }
+  }
+
+  /// Unloads the stores that haven't been accessed for the given duration.
+  #[must_use]
+  pub const fn idle_timeout(mut self, timeout: Duration) -> Self {
+    self.idle_timeout = Some(timeout);
+    self
+  }
+
+  /// Keeps at most this many stores loaded.
+  #[must_use]
+  pub const fn max_stores(mut self, max: usize) -> Self {
+    self.max_stores = Some(max);
+    self
+  }
+
+  /// Keeps the estimated size of the loaded stores under this many bytes.
+  #[must_use]
+  pub const fn max_bytes(mut self, max: usize) -> Self {
+    self.max_bytes = Some(max);
+    self
+  }
+
+  /// Whether the policy may ever evict a store.
+  pub const fn is_enabled(&self) -> bool {
+    self.idle_timeout.is_some() || self.is_bounded()
+  }
+
+  /// Whether the policy limits how many stores, or how much of them, can be loaded.
+  pub const fn is_bounded(&self) -> bool {
+    self.max_stores.is_some() || self.max_bytes.is_some()
+  }
+}
+
+/// Periodically unloads the stores that the [`EvictionPolicy`] no longer allows to stay loaded.
+pub(crate) struct Eviction {
+  pub(crate) policy: EvictionPolicy,
+  abort_handle: Mutex<Option<AbortHandle>>,
+}
+
+impl Eviction {
+  pub(crate) fn new(policy: EvictionPolicy) -> Self {
+    Self {
+      policy,
+      abort_handle: Mutex::new(None),
+    }
+  }
+
+  pub(crate) fn start<R, C>(&self, app: &AppHandle<R>)
+  where
+    R: Runtime,
+    C: CollectionMarker,
+  {
+    self.stop();
+    if !self.policy.is_enabled() {
+      return;
+    }
+
+    let interval = self
+      .policy
+      .idle_timeout
+      .map_or(CHECK_INTERVAL, |timeout| timeout.min(CHECK_INTERVAL));
+
+    let abort_handle = set_interval(app, interval, |app| async move {
+      let _ = spawn_blocking(move || app.store_collection_with_marker::<C>().evict()).await;
+    });
+
+    *self
+      .abort_handle
+      .lock()
+      .expect("abort handle is poisoned") = Some(abort_handle);
+  }
+
+  pub(crate) fn stop(&self) {
+    if let Some(handle) = self
+      .abort_handle
+      .lock()
+      .expect("abort handle is poisoned")
+      .take()
+    {
+      handle.abort();
+    }
+  }
+}
+
+impl Drop for Eviction {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+/// Estimates how many bytes the state would take once serialized as JSON.
+pub(crate) fn estimate_size(state: &StoreState) -> usize {
+  state
+    .entries()
+    .map(|(key, value)| key.len() + 3 + estimate_value_size(value))
+    .sum()
+}
+
+fn estimate_value_size(value: &Value) -> usize {
+  match value {
+    Value::Null => 4,
+    Value::Bool(_) => 5,
+    Value::Number(_) => 8,
+    Value::String(string) => string.len() + 2,
+    Value::Array(array) => {
+      array
+        .iter()
+        .map(|value| estimate_value_size(value) + 1)
+        .sum::<usize>()
+        + 2
+    }
+    Value::Object(object) => {
+      object
+        .iter()
+        .map(|(key, value)| key.len() + 4 + estimate_value_size(value))
+        .sum::<usize>()
+        + 2
+    }
+  }
+}
@@ -0,0 +1,81 @@
+use super::CollectionMarker;
+use crate::store::{is_holding_store, KeyChange, StoreResource, StoreState};
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard, TryLockError};
+use tauri::{AppHandle, ResourceId, Runtime};
+
+/// New state of a parent store, waiting to be passed down to one of its children.
+struct Inherit {
+  child: ResourceId,
+  state: StoreState,
+  keys: Vec<KeyChange>,
+}
+
+/// Children waiting to inherit the new state of their parents.
+///
+/// A child can't be locked while its parent is, as the same thread might already hold it,
+/// which is the case for a transaction that includes both. The states are queued instead,
+/// and only passed down by a thread that holds no store at all.
+#[derive(Default)]
+pub(crate) struct InheritQueue {
+  queue: Mutex<VecDeque<Inherit>>,
+  /// Held while the queue is flushed, so each child receives the states in order.
+  flushing: Mutex<()>,
+}
+
+impl InheritQueue {
+  fn lock(&self) -> MutexGuard<'_, VecDeque<Inherit>> {
+    self
+      .queue
+      .lock()
+      .expect("inherit queue is poisoned")
+  }
+
+  pub(crate) fn push(&self, child: ResourceId, state: StoreState, keys: Vec<KeyChange>) {
+    self
+      .lock()
+      .push_back(Inherit { child, state, keys });
+  }
+
+  pub(crate) fn is_empty(&self) -> bool {
+    self.lock().is_empty()
+  }
+
+  /// Passes the queued states down to the children that are still loaded.
+  ///
+  /// This does nothing if the current thread holds any store, as it could be one of the children.
+  /// The queue is flushed again once it releases them.
+  pub(crate) fn flush<R, C>(&self, app: &AppHandle<R>)
+  where
+    R: Runtime,
+    C: CollectionMarker,
+  {
+    while !is_holding_store() {
+      let flushing = match self.flushing.try_lock() {
+        Ok(guard) => guard,
+        Err(TryLockError::Poisoned(err)) => err.into_inner(),
+        // Whoever is flushing will also pass down what has just been queued.
+        Err(TryLockError::WouldBlock) => return,
+      };
+
+      loop {
+        let next = self.lock().pop_front();
+        let Some(inherit) = next else {
+          break;
+        };
+
+        // A child that was unloaded in the meantime has already read the latest state when reloaded.
+        if let Ok(resource) = StoreResource::<R, C>::get(app, inherit.child) {
+          let _ = resource.locked(|store| store.inherit(inherit.state, &inherit.keys));
+        }
+      }
+
+      drop(flushing);
+
+      // Something might have been queued after the last pop, but before the flush lock was released.
+      if self.is_empty() {
+        return;
+      }
+    }
+  }
+}
@@ -0,0 +1,90 @@
+use crate::error::{Error, Result};
+use fs4::fs_std::FileExt;
+use std::collections::BTreeSet;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// What to do when the collection is already locked by another process.
+///
+/// Every directory the stores are saved to is locked, including those set by
+/// [`StoreCollectionBuilder::path_of`](crate::StoreCollectionBuilder::path_of).
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockPolicy {
+  /// Fails to build the collection with [`Error::Locked`].
+  Fail,
+  /// Builds the collection, but never writes anything to the disk.
+  ReadOnly,
+  /// Waits for the lock to be released, failing with [`Error::Locked`] after the timeout.
+  Wait(Duration),
+}
+
+/// Advisory lock on every directory of the collection, held for as long as the app is running.
+///
+/// The locks are released when the files are closed, which also happens if the process crashes.
+pub(crate) struct CollectionLock {
+  _files: Vec<File>,
+}
+
+impl CollectionLock {
+  const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+  /// Tries to lock each of the directories according to the policy.
+  ///
+  /// They are locked in order, so two processes sharing some of them can't deadlock.
+  /// Returns `None` if the collection should be opened as read-only.
+  pub(crate) fn acquire<'a, I>(dirs: I, policy: LockPolicy) -> Result<Option<Self>>
+  where
+    I: IntoIterator<Item = &'a Path>,
+  {
+    let dirs = dirs.into_iter().collect::<BTreeSet<_>>();
+    let start = Instant::now();
+    let mut files = Vec::with_capacity(dirs.len());
+
+    for dir in dirs {
+      match Self::acquire_dir(dir, policy, start)? {
+        Some(file) => files.push(file),
+        None => return Ok(None),
+      }
+    }
+
+    Ok(Some(Self { _files: files }))
+  }
+
+  fn acquire_dir(dir: &Path, policy: LockPolicy, start: Instant) -> Result<Option<File>> {
+    let path = path(dir);
+    fs::create_dir_all(dir)?;
+
+    let file = OpenOptions::new()
+      .read(true)
+      .write(true)
+      .create(true)
+      .truncate(false)
+      .open(&path)?;
+
+    loop {
+      if FileExt::try_lock_exclusive(&file)? {
+        return Ok(Some(file));
+      }
+
+      match policy {
+        LockPolicy::Fail => return Err(Error::Locked(path)),
+        LockPolicy::ReadOnly => return Ok(None),
+        LockPolicy::Wait(timeout) => {
+          if start.elapsed() >= timeout {
+            return Err(Error::Locked(path));
+          }
+
+          thread::sleep(Self::POLL_INTERVAL);
+        }
+      }
+    }
+  }
+}
+
+/// Path to the lock file of the collection.
+pub(crate) fn path(dir: &Path) -> PathBuf {
+  dir.join("lock.tauristore")
+}
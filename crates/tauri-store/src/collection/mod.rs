@@ -1,28 +1,62 @@
 mod autosave;
 mod builder;
+mod container;
+mod eviction;
 mod handle;
+mod inherit;
+mod lock;
 mod marker;
+mod parallel;
+mod process_sync;
+mod sweep;
 mod table;
+mod transaction;
+pub(crate) mod writer;
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::event::{emit, STORE_UNLOAD_EVENT};
+use crate::io::{remove_dir_all, remove_file, write_file};
 use crate::migration::Migrator;
-use crate::store::{SaveStrategy, Store, StoreId, StoreResource, StoreState, WatcherId};
+use crate::remote::{RemoteSync, SyncStatus};
+use crate::store::{
+  make_path, shard, AtomicOp, Batch, ChangeReceiver, KeyChange, PatchMode, SaveStrategy, Store,
+  StoreChange, StoreId, StoreResource, StoreState, WatcherId,
+};
 use autosave::Autosave;
+use container::Container;
 use dashmap::{DashMap, DashSet};
+use eviction::{estimate_size, Eviction};
+use inherit::InheritQueue;
+use lock::CollectionLock;
+use process_sync::ProcessSync;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
-use std::fmt;
+use std::collections::{HashMap, HashSet};
+use std::io::ErrorKind;
 use std::marker::PhantomData;
-use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
+use std::{fmt, fs};
+use sweep::ExpirySweep;
 use table::{MarshalerTable, PathTable};
-use tauri::{AppHandle, Resource, ResourceId, Runtime};
+use tauri::{AppHandle, Manager, Resource, ResourceId, Runtime};
+use tokio::sync::broadcast;
+use writer::Writer;
 
 pub use builder::StoreCollectionBuilder;
+pub use container::CollectionLayout;
+pub use eviction::EvictionPolicy;
 pub use handle::Handle;
+pub use lock::LockPolicy;
 pub use marker::{CollectionMarker, DefaultMarker};
+pub use transaction::Transaction;
+
+/// Amount of changes a subscriber may fall behind before the oldest ones are dropped.
+const SUBSCRIPTION_CAPACITY: usize = 256;
+
+/// A store state waiting to be written to the container, along with the length of its log.
+type ContainerEntry<R, C> = (Arc<StoreResource<R, C>>, StoreId, StoreState, usize, u64);
 
 /// Closure to be called when a store is loaded.
 pub type OnLoadFn<R, C> = dyn Fn(&Store<R, C>) -> Result<()> + Send + Sync;
@@ -39,12 +73,32 @@ where
   pub(crate) stores: DashMap<StoreId, ResourceId>,
   pub(crate) path_table: PathTable,
   pub(crate) marshaler_table: MarshalerTable,
+  pub(crate) defaults: HashMap<StoreId, StoreState>,
+  pub(crate) layered: DashSet<StoreId>,
+  pub(crate) env_prefixes: HashMap<StoreId, String>,
+  pub(crate) parents: HashMap<StoreId, StoreId>,
+  pub(crate) layout: RwLock<CollectionLayout>,
+  pub(crate) container: Mutex<Container>,
   pub(crate) on_load: Option<Box<OnLoadFn<R, C>>>,
   pub(crate) autosave: Mutex<Autosave>,
+  pub(crate) expiry_sweep: ExpirySweep,
+  pub(crate) eviction: Eviction,
+  pub(crate) writer: Writer,
   pub(crate) default_save_strategy: SaveStrategy,
+  pub(crate) default_patch_mode: PatchMode,
   pub(crate) save_denylist: DashSet<StoreId>,
   pub(crate) sync_denylist: DashSet<StoreId>,
+  pub(crate) journal: DashSet<StoreId>,
+  pub(crate) journal_limit: usize,
+  pub(crate) sharded: DashSet<StoreId>,
+  pub(crate) crdt: DashSet<StoreId>,
   pub(crate) migrator: Mutex<Migrator>,
+  pub(crate) lock: Mutex<Option<CollectionLock>>,
+  pub(crate) process_sync: Option<ProcessSync>,
+  pub(crate) remote_sync: Option<RemoteSync>,
+  pub(crate) subscribers: DashMap<StoreId, broadcast::Sender<Arc<StoreChange>>>,
+  pub(crate) inherit_queue: InheritQueue,
+  pub(crate) read_only: bool,
   pub(crate) debug_stores: bool,
   phantom: PhantomData<C>,
 }
@@ -61,21 +115,35 @@ where
 
   pub(crate) fn get_resource(&self, id: impl AsRef<str>) -> Result<Arc<StoreResource<R, C>>> {
     let id = StoreId::from(id.as_ref());
-    let rid = match self.rid(&id) {
-      Some(rid) => rid,
-      None => self.load_store(id)?,
+    let app = self.handle.app();
+
+    // If the store was evicted in the meantime, it's loaded again.
+    // This is fine, as eviction only unloads stores whose state is saved.
+    let resource = match self
+      .rid(&id)
+      .and_then(|rid| StoreResource::get(app, rid).ok())
+    {
+      Some(resource) => resource,
+      None => StoreResource::get(app, self.load_store(&id)?)?,
     };
 
-    StoreResource::get(self.handle.app(), rid)
+    resource.touch();
+    Ok(resource)
   }
 
-  fn load_store(&self, id: StoreId) -> Result<ResourceId> {
-    let (rid, resource) = Store::load(self.handle.app(), &id)?;
+  fn load_store(&self, id: &StoreId) -> Result<ResourceId> {
+    let (rid, resource) = Store::load(self.handle.app(), id)?;
     if let Some(on_load) = &self.on_load {
       resource.locked(|store| on_load(store))?;
     }
 
-    self.stores.insert(id, rid);
+    self.stores.insert(id.clone(), rid);
+
+    // Failing to evict another store shouldn't prevent this one from being used.
+    if self.eviction.policy.is_bounded() {
+      let _ = self.evict_except(Some(id));
+    }
+
     Ok(rid)
   }
 
@@ -89,12 +157,24 @@ where
     self.stores.iter().map(|it| *it.value()).collect()
   }
 
+  /// Gets the resource ids for all the stores, along with their store ids.
+  fn rids_by_id(&self) -> Vec<(StoreId, ResourceId)> {
+    self
+      .stores
+      .iter()
+      .map(|it| (it.key().clone(), *it.value()))
+      .collect()
+  }
+
   /// Gets a handle to the application instance.
   pub fn app_handle(&self) -> &AppHandle<R> {
     self.handle.app()
   }
 
   /// Lists all the store ids.
+  ///
+  /// This only includes the stores that are loaded.
+  /// Use [`StoreCollection::persisted_ids`] to list those saved on the disk.
   pub fn ids(&self) -> Vec<StoreId> {
     self
       .stores
@@ -103,6 +183,49 @@ where
       .collect()
   }
 
+  /// Lists the ids of the stores saved on the disk, whether they are loaded or not.
+  pub fn persisted_ids(&self) -> Result<Vec<StoreId>> {
+    let layout = self.layout.read().expect("layout is poisoned");
+    let mut ids = if layout.is_container() {
+      self
+        .container
+        .lock()
+        .expect("container is poisoned")
+        .stores
+        .keys()
+        .cloned()
+        .collect()
+    } else {
+      self.scan_store_files()?
+    };
+
+    ids.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+    Ok(ids)
+  }
+
+  /// Loads the stores in parallel, so that they're ready by the time they're first accessed.
+  ///
+  /// Stores that are already loaded are skipped.
+  pub fn preload(&self, ids: &[impl AsRef<str>]) -> Result<()> {
+    let ids = ids
+      .iter()
+      .map(|id| StoreId::from(id.as_ref()))
+      .filter(|id| !self.stores.contains_key(id))
+      .collect::<HashSet<_>>()
+      .into_iter()
+      .map(|id| (id, ()))
+      .collect();
+
+    parallel::try_for_each(self, ids, |collection, id, ()| {
+      collection.get_resource(id).map(drop)
+    })
+  }
+
+  /// Loads every store saved on the disk in parallel.
+  pub fn preload_all(&self) -> Result<()> {
+    self.preload(&self.persisted_ids()?)
+  }
+
   /// Store collection name.
   #[inline]
   pub fn name(&self) -> &str {
@@ -121,6 +244,268 @@ where
     self.path_table.get(&store_id)
   }
 
+  /// Layout used to save the stores.
+  pub fn layout(&self) -> CollectionLayout {
+    *self.layout.read().expect("layout is poisoned")
+  }
+
+  /// Converts the stores saved on disk to another layout, which the collection will use from now on.
+  ///
+  /// Loaded stores are saved immediately before the conversion takes place.
+  pub fn convert_layout(&self, layout: CollectionLayout) -> Result<()> {
+    if self.read_only {
+      return Err(self.locked_error());
+    }
+
+    self.save_all_now()?;
+
+    // The migrator must be locked before the layout, as that's also the order
+    // in which they are locked when a store is loaded and migrated.
+    let migrator = self
+      .migrator
+      .lock()
+      .expect("migrator is poisoned");
+    let mut current = self.layout.write().expect("layout is poisoned");
+    if *current == layout {
+      return Ok(());
+    }
+
+    // Nothing else can be queued while we hold the layout.
+    self.writer.flush();
+
+    match layout {
+      CollectionLayout::File => container::unpack(self, &migrator)?,
+      CollectionLayout::Container => container::pack(self, &migrator)?,
+    }
+
+    *current = layout;
+
+    Ok(())
+  }
+
+  /// Whether the collection was opened as read-only because another process holds its lock.
+  ///
+  /// Read-only collections never write anything to the disk.
+  #[inline]
+  pub fn is_read_only(&self) -> bool {
+    self.read_only
+  }
+
+  pub(crate) fn locked_error(&self) -> Error {
+    Error::Locked(lock::path(self.path()))
+  }
+
+  /// Whether changes to the stores are shared with other processes using the same directory.
+  #[inline]
+  pub fn is_process_synced(&self) -> bool {
+    self.process_sync.is_some()
+  }
+
+  /// Path to the container file, used when the layout is [`CollectionLayout::Container`].
+  pub(crate) fn container_path(&self) -> PathBuf {
+    let filename = if cfg!(debug_assertions) && self.debug_stores {
+      "container.dev.tauristore"
+    } else {
+      "container.tauristore"
+    };
+
+    self.path().join(filename)
+  }
+
+  /// Reads the container from the disk, replacing the one in memory.
+  pub(crate) fn read_container(&self) -> Result<()> {
+    let marshaler = self.marshaler_table.default.as_ref();
+    let container = Container::read(&self.container_path(), marshaler)?;
+    *self
+      .container
+      .lock()
+      .expect("container is poisoned") = container;
+
+    Ok(())
+  }
+
+  /// Calls a closure with a mutable reference to the container, then saves it to the disk.
+  ///
+  /// The caller is expected to hold a read lock on the layout.
+  pub(crate) fn update_container<F>(&self, f: F) -> Result<()>
+  where
+    F: FnOnce(&mut Container),
+  {
+    let mut container = self
+      .container
+      .lock()
+      .expect("container is poisoned");
+
+    f(&mut container);
+
+    let marshaler = self.marshaler_table.default.as_ref();
+    container.write(&self.container_path(), marshaler)
+  }
+
+  /// Path to the file of a store, used when the layout is [`CollectionLayout::File`].
+  pub(crate) fn store_file_path(&self, id: &StoreId) -> PathBuf {
+    let marshaler = self.marshaler_table.get(id);
+    make_path(self, id, marshaler.extension())
+  }
+
+  /// Whether each key of the store is saved to its own file.
+  ///
+  /// This has no effect when the layout is [`CollectionLayout::Container`].
+  pub fn is_sharded(&self, id: impl AsRef<str>) -> bool {
+    let id = StoreId::from(id.as_ref());
+    self.sharded.contains(&id)
+  }
+
+  /// Directory where the keys of a sharded store are saved.
+  pub(crate) fn shard_dir(&self, id: &StoreId) -> PathBuf {
+    let dirname = if cfg!(debug_assertions) && self.debug_stores {
+      format!("{id}.dev")
+    } else {
+      id.to_string()
+    };
+
+    self.path_of(id).join(dirname)
+  }
+
+  /// Directory where the blobs of a store are saved.
+  pub(crate) fn blob_dir(&self, id: &StoreId) -> PathBuf {
+    let dirname = if cfg!(debug_assertions) && self.debug_stores {
+      format!("{id}.dev.blobs")
+    } else {
+      format!("{id}.blobs")
+    };
+
+    self.path_of(id).join(dirname)
+  }
+
+  /// Path to the write-ahead log of a store.
+  pub(crate) fn journal_path(&self, id: &StoreId) -> PathBuf {
+    make_path(self, id, "wal")
+  }
+
+  /// Path to the CRDT of a store.
+  pub(crate) fn crdt_path(&self, id: &StoreId) -> PathBuf {
+    make_path(self, id, "crdt")
+  }
+
+  /// Path to the expiry deadlines of a store.
+  pub(crate) fn expiry_path(&self, id: &StoreId) -> PathBuf {
+    make_path(self, id, "ttl")
+  }
+
+  /// Reads the state of a store from its file.
+  pub(crate) fn read_store_file(&self, id: &StoreId) -> Result<StoreState> {
+    if self.sharded.contains(id) {
+      return shard::read(&self.shard_dir(id), self.marshaler_table.get(id));
+    }
+
+    match fs::read(self.store_file_path(id)) {
+      Ok(bytes) => self
+        .marshaler_table
+        .get(id)
+        .deserialize(&bytes)
+        .map_err(Error::FailedToDeserialize),
+      Err(err) if err.kind() == ErrorKind::NotFound => Ok(StoreState::default()),
+      Err(err) => Err(Error::Io(err)),
+    }
+  }
+
+  /// Writes the state of a store to its file.
+  pub(crate) fn write_store_file(&self, id: &StoreId, state: &StoreState) -> Result<()> {
+    if self.sharded.contains(id) {
+      return shard::write_all(&self.shard_dir(id), self.marshaler_table.get(id), state);
+    }
+
+    let bytes = self
+      .marshaler_table
+      .get(id)
+      .serialize(state)
+      .map_err(Error::FailedToSerialize)?;
+
+    write_file(&self.store_file_path(id), &bytes)
+  }
+
+  /// Deletes the file of a store.
+  pub(crate) fn remove_store_file(&self, id: &StoreId) -> Result<()> {
+    if self.sharded.contains(id) {
+      remove_dir_all(&self.shard_dir(id))
+    } else {
+      remove_file(&self.store_file_path(id))
+    }
+  }
+
+  /// Writes the stores to the disk according to the given layout.
+  ///
+  /// The caller is expected to hold a read lock on the layout.
+  pub(crate) fn write_stores<'a, I>(&self, layout: CollectionLayout, stores: I) -> Result<()>
+  where
+    I: IntoIterator<Item = (&'a StoreId, &'a StoreState)>,
+  {
+    match layout {
+      CollectionLayout::File => stores
+        .into_iter()
+        .try_for_each(|(id, state)| self.write_store_file(id, state)),
+      CollectionLayout::Container => self.update_container(|container| {
+        for (id, state) in stores {
+          container.stores.insert(id.clone(), state.clone());
+        }
+      }),
+    }
+  }
+
+  /// Lists the ids of the stores whose files can be found on the disk.
+  pub(crate) fn scan_store_files(&self) -> Result<Vec<StoreId>> {
+    let mut ids = HashSet::new();
+    let extension = self.marshaler_table.default.extension();
+    let suffix = if cfg!(debug_assertions) && self.debug_stores {
+      format!(".dev.{extension}")
+    } else {
+      format!(".{extension}")
+    };
+
+    match fs::read_dir(self.path()) {
+      Ok(entries) => {
+        for entry in entries {
+          let path = entry?.path();
+          let id = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(&suffix))
+            .map(StoreId::from);
+
+          // A store with a custom path or marshaler might not actually be saved here.
+          if let Some(id) = id {
+            if path.is_file() && self.store_file_path(&id) == path {
+              ids.insert(id);
+            }
+          }
+        }
+      }
+      Err(err) if err.kind() == ErrorKind::NotFound => {}
+      Err(err) => return Err(Error::Io(err)),
+    }
+
+    let custom = self
+      .path_table
+      .table
+      .keys()
+      .chain(self.marshaler_table.table.keys());
+
+    for id in custom {
+      if !ids.contains(id) && self.store_file_path(id).is_file() {
+        ids.insert(id.clone());
+      }
+    }
+
+    for id in self.sharded.iter() {
+      if self.shard_dir(&id).is_dir() {
+        ids.insert(id.clone());
+      }
+    }
+
+    Ok(ids.into_iter().collect())
+  }
+
   /// Calls a closure with a mutable reference to the store with the given id.
   pub fn with_store<F, T>(&self, store_id: impl AsRef<str>, f: F) -> Result<T>
   where
@@ -209,6 +594,25 @@ where
       .locked(|store| store.get(key))
   }
 
+  /// Gets a raw value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) from a store.
+  pub fn get_raw_path(&self, store_id: impl AsRef<str>, pointer: impl AsRef<str>) -> Option<Value> {
+    self
+      .get_resource(store_id)
+      .ok()?
+      .locked(|store| store.get_raw_path(pointer).cloned())
+  }
+
+  /// Gets a value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) from a store
+  /// and tries to parse it as an instance of type `T`.
+  pub fn get_path<T>(&self, store_id: impl AsRef<str>, pointer: impl AsRef<str>) -> Result<T>
+  where
+    T: DeserializeOwned,
+  {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.get_path(pointer))
+  }
+
   /// Gets a value from a store and tries to parse it as an instance of type `T`.
   ///
   /// If the key does not exist, returns the provided default value.
@@ -268,6 +672,105 @@ where
       .locked(|store| store.set(key, value))
   }
 
+  /// Restores a key of a store to its default value, removing it if it has none.
+  pub fn reset_key(&self, store_id: impl AsRef<str>, key: impl AsRef<str>) -> Result<()> {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.reset_key(key))
+  }
+
+  /// Restores a store to its default values, removing every key that has none.
+  pub fn reset(&self, store_id: impl AsRef<str>) -> Result<()> {
+    self.get_resource(store_id)?.locked(Store::reset)
+  }
+
+  /// Sets a value that takes precedence over every other layer of a layered store.
+  pub fn set_override<K, V>(&self, store_id: impl AsRef<str>, key: K, value: V) -> Result<()>
+  where
+    K: AsRef<str>,
+    V: Into<Value>,
+  {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.set_override(key, value))
+  }
+
+  /// Removes an override from a layered store, returning its value, if any.
+  pub fn remove_override(
+    &self,
+    store_id: impl AsRef<str>,
+    key: impl AsRef<str>,
+  ) -> Result<Option<Value>> {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.remove_override(key))
+  }
+
+  /// Removes every override from a layered store.
+  pub fn clear_overrides(&self, store_id: impl AsRef<str>) -> Result<()> {
+    self
+      .get_resource(store_id)?
+      .locked(Store::clear_overrides)
+  }
+
+  /// Sets a key-value pair in a store that is removed once the given duration elapses.
+  pub fn set_with_ttl<K, V>(
+    &self,
+    store_id: impl AsRef<str>,
+    key: K,
+    value: V,
+    ttl: Duration,
+  ) -> Result<()>
+  where
+    K: AsRef<str>,
+    V: Into<Value>,
+  {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.set_with_ttl(key, value, ttl))
+  }
+
+  /// Time left until a key of a store expires, if it was set with a time-to-live.
+  pub fn ttl(&self, store_id: impl AsRef<str>, key: impl AsRef<str>) -> Result<Option<Duration>> {
+    self
+      .get_resource(store_id)?
+      .locked(|store| Ok(store.ttl(key)))
+  }
+
+  /// Removes the expired keys of every loaded store, returning how many were removed.
+  pub fn purge_expired(&self) -> Result<usize> {
+    self
+      .resources()?
+      .into_iter()
+      .try_fold(0, |total, resource| {
+        Ok(total + resource.locked(Store::purge_expired)?)
+      })
+  }
+
+  /// Sets a value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) in a store,
+  /// creating the intermediate objects that don't exist.
+  pub fn set_path<P, V>(&self, store_id: impl AsRef<str>, pointer: P, value: V) -> Result<()>
+  where
+    P: AsRef<str>,
+    V: Into<Value>,
+  {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.set_path(pointer, value))
+  }
+
+  /// Removes the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901)
+  /// from a store, returning it, if any.
+  pub fn remove_path(
+    &self,
+    store_id: impl AsRef<str>,
+    pointer: impl AsRef<str>,
+  ) -> Result<Option<Value>> {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.remove_path(pointer))
+  }
+
   /// Patches a store state.
   pub fn patch<S>(&self, store_id: impl AsRef<str>, state: S) -> Result<()>
   where
@@ -278,50 +781,294 @@ where
       .locked(|store| store.patch(state))
   }
 
-  /// Saves a store to the disk.
-  pub fn save(&self, store_id: impl AsRef<str>) -> Result<()> {
+  /// Applies a [JSON Merge Patch](https://datatracker.ietf.org/doc/html/rfc7396) to a store state.
+  pub fn merge_patch<S>(&self, store_id: impl AsRef<str>, patch: S) -> Result<()>
+  where
+    S: Into<StoreState>,
+  {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.merge_patch(patch))
+  }
+
+  /// Replaces the value of a key with the one returned by the closure, returning the new value.
+  pub fn update<K, F>(&self, store_id: impl AsRef<str>, key: K, f: F) -> Result<Value>
+  where
+    K: AsRef<str>,
+    F: FnOnce(Option<&Value>) -> Result<Value>,
+  {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.update(key, f))
+  }
+
+  /// Applies an atomic operation to the value of a key, returning the new value.
+  pub fn apply_op<K>(&self, store_id: impl AsRef<str>, key: K, op: AtomicOp) -> Result<Value>
+  where
+    K: AsRef<str>,
+  {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.apply_op(key, op))
+  }
+
+  /// Reverts the last change to a store, returning whether there was anything to undo.
+  pub fn undo(&self, store_id: impl AsRef<str>) -> Result<bool> {
+    self.get_resource(store_id)?.locked(Store::undo)
+  }
+
+  /// Reapplies the last change to a store that was undone, returning whether there was anything to redo.
+  pub fn redo(&self, store_id: impl AsRef<str>) -> Result<bool> {
+    self.get_resource(store_id)?.locked(Store::redo)
+  }
+
+  /// Whether there is a change to undo in a store.
+  pub fn can_undo(&self, store_id: impl AsRef<str>) -> Result<bool> {
+    self
+      .get_resource(store_id)?
+      .locked(|store| Ok(store.can_undo()))
+  }
+
+  /// Whether there is a change to redo in a store.
+  pub fn can_redo(&self, store_id: impl AsRef<str>) -> Result<bool> {
+    self
+      .get_resource(store_id)?
+      .locked(|store| Ok(store.can_redo()))
+  }
+
+  /// Discards every change that could be undone or redone in a store.
+  pub fn clear_history(&self, store_id: impl AsRef<str>) -> Result<()> {
+    self
+      .get_resource(store_id)?
+      .locked(Store::clear_history)
+  }
+
+  /// Applies every change made in the closure to a store at once.
+  pub fn batch<F, T>(&self, store_id: impl AsRef<str>, f: F) -> Result<T>
+  where
+    F: FnOnce(&mut Batch<'_>) -> T,
+  {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.batch(f))
+  }
+
+  /// Saves a blob to a store and sets a reference to it under the given key.
+  pub fn put_blob<K, B>(&self, store_id: impl AsRef<str>, key: K, bytes: B) -> Result<()>
+  where
+    K: AsRef<str>,
+    B: AsRef<[u8]>,
+  {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.put_blob(key, bytes))
+  }
+
+  /// Reads a blob referenced by a key in a store.
+  pub fn get_blob(
+    &self,
+    store_id: impl AsRef<str>,
+    key: impl AsRef<str>,
+  ) -> Result<Option<Vec<u8>>> {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.get_blob(key))
+  }
+
+  /// Removes the reference to a blob from a store, returning whether it existed.
+  pub fn remove_blob(&self, store_id: impl AsRef<str>, key: impl AsRef<str>) -> Result<bool> {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.remove_blob(key))
+  }
+
+  /// Saves a store, then deletes the blobs that it no longer references,
+  /// returning how many of them were deleted.
+  ///
+  /// Blobs referenced anywhere in the state are kept, as are those that undoing or redoing would restore.
+  pub fn collect_blobs(&self, store_id: impl AsRef<str>) -> Result<usize> {
+    self.get_resource(store_id)?.collect_blobs()
+  }
+
+  /// Whether the store is backed by a CRDT.
+  pub fn is_crdt(&self, id: impl AsRef<str>) -> bool {
+    let id = StoreId::from(id.as_ref());
+    self.crdt.contains(&id)
+  }
+
+  /// Clock of the CRDT of a store.
+  pub fn crdt_clock(&self, store_id: impl AsRef<str>) -> Result<u64> {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.crdt_clock())
+  }
+
+  /// Encodes the changes to a store as an update that can be merged into other replicas.
+  pub fn export_update(&self, store_id: impl AsRef<str>, since: Option<u64>) -> Result<Vec<u8>> {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.export_update(since))
+  }
+
+  /// Merges an update exported by another replica of a store.
+  pub fn import_update<U>(&self, store_id: impl AsRef<str>, update: U) -> Result<()>
+  where
+    U: AsRef<[u8]>,
+  {
     self
       .get_resource(store_id)?
-      .locked(|store| store.save())
+      .locked(|store| store.import_update(update))
+  }
+
+  /// Saves a store to the disk.
+  pub fn save(&self, store_id: impl AsRef<str>) -> Result<()> {
+    self.get_resource(store_id)?.save()
   }
 
   /// Saves a store to the disk immediately, ignoring the save strategy.
+  ///
+  /// The store is only locked while its state is serialized, not while the file is written,
+  /// unless it is in a [container](crate::CollectionLayout::Container) or [sharded](crate::StoreCollectionBuilder::shard).
   pub fn save_now(&self, store_id: impl AsRef<str>) -> Result<()> {
-    self.get_resource(store_id)?.locked(|store| {
-      store.abort_pending_save();
-      store.save_now()
-    })
+    self.get_resource(store_id)?.save_now(false)
+  }
+
+  /// Saves a store to the disk immediately, even if it has not changed since it was last saved.
+  pub fn force_save_now(&self, store_id: impl AsRef<str>) -> Result<()> {
+    self.get_resource(store_id)?.save_now(true)
+  }
+
+  /// Whether a store has changed since it was last saved.
+  pub fn is_dirty(&self, store_id: impl AsRef<str>) -> Result<bool> {
+    self
+      .get_resource(store_id)?
+      .locked(|store| Ok(store.is_dirty()))
   }
 
   /// Saves some stores to the disk.
   pub fn save_some(&self, ids: &[impl AsRef<str>]) -> Result<()> {
-    ids.iter().try_for_each(|id| self.save(id))
+    if self.layout().is_container() {
+      let resources = ids
+        .iter()
+        .map(|id| self.get_resource(id))
+        .collect::<Result<Vec<_>>>()?;
+
+      return self.save_container(resources, false);
+    }
+
+    parallel::try_for_each(self, with_ids(ids), |collection, id, ()| {
+      collection.save(id)
+    })
   }
 
   /// Saves some stores to the disk immediately, ignoring the save strategy.
   pub fn save_some_now(&self, ids: &[impl AsRef<str>]) -> Result<()> {
-    ids.iter().try_for_each(|id| self.save_now(id))
+    if self.layout().is_container() {
+      let resources = ids
+        .iter()
+        .map(|id| self.get_resource(id))
+        .collect::<Result<Vec<_>>>()?;
+
+      return self.save_container(resources, true);
+    }
+
+    parallel::try_for_each(self, with_ids(ids), |collection, id, ()| {
+      collection.save_now(id)
+    })
   }
 
   /// Saves all the stores to the disk.
+  ///
+  /// Stores that have not changed since they were last saved are skipped.
   pub fn save_all(&self) -> Result<()> {
-    // I suppose going through the rids is better than through the store ids.
-    // This way, we don't need to hold references into the dashmap nor clone its keys.
-    // The downside (?) is that we need to use the StoreResource directly.
-    let app = self.handle.app();
-    self
-      .rids()
-      .into_iter()
-      .try_for_each(|rid| StoreResource::<R, C>::save(app, rid))
+    if self.layout().is_container() {
+      return self.save_container(self.resources()?, false);
+    }
+
+    parallel::try_for_each(self, self.rids_by_id(), |collection, _, rid| {
+      StoreResource::<R, C>::get(collection.app_handle(), rid)?.save()
+    })
   }
 
   /// Saves all the stores to the disk immediately, ignoring the save strategy.
   pub fn save_all_now(&self) -> Result<()> {
+    if self.layout().is_container() {
+      return self.save_container(self.resources()?, true);
+    }
+
+    parallel::try_for_each(self, self.rids_by_id(), |collection, _, rid| {
+      StoreResource::<R, C>::get(collection.app_handle(), rid)?.save_now(false)
+    })
+  }
+
+  /// Gets the resources for all the stores.
+  fn resources(&self) -> Result<Vec<Arc<StoreResource<R, C>>>> {
     let app = self.handle.app();
     self
       .rids()
       .into_iter()
-      .try_for_each(|rid| StoreResource::<R, C>::save_now(app, rid))
+      .map(|rid| StoreResource::get(app, rid))
+      .collect()
+  }
+
+  /// Saves the stores to the container at once.
+  ///
+  /// Unless `now` is `true`, stores whose save strategy is not immediate will schedule
+  /// their saves as usual, writing the container on their own when the time comes.
+  fn save_container<I>(&self, resources: I, now: bool) -> Result<()>
+  where
+    I: IntoIterator<Item = Arc<StoreResource<R, C>>>,
+  {
+    let mut stores = Vec::new();
+    for resource in resources {
+      resource.locked(|store| {
+        if now {
+          store.abort_pending_save();
+        } else if !store.save_strategy().is_immediate() {
+          return store.save();
+        }
+
+        if store.is_dirty() && !self.save_denylist.contains(&store.id) {
+          store.save_sidecars()?;
+          let state = store.persisted_state().clone();
+          let len = store.journal.len();
+          let changes = store.changes();
+          stores.push((Arc::clone(&resource), store.id.clone(), state, len, changes));
+        }
+
+        Ok(())
+      })?;
+    }
+
+    self.write_container_stores(&stores)
+  }
+
+  /// Writes the collected states to the container, then compacts the logs of their stores.
+  fn write_container_stores(&self, stores: &[ContainerEntry<R, C>]) -> Result<()> {
+    if stores.is_empty() || self.read_only {
+      return Ok(());
+    }
+
+    // If the layout was converted while we were collecting the states,
+    // the stores will be written to wherever the new layout dictates.
+    {
+      let layout = self.layout.read().expect("layout is poisoned");
+      let iter = stores
+        .iter()
+        .map(|(_, id, state, _, _)| (id, state));
+
+      self.write_stores(*layout, iter)?;
+    }
+
+    stores
+      .iter()
+      .try_for_each(|(resource, _, _, len, changes)| {
+        resource.locked(|store| {
+          store.journal.compact(*len)?;
+          store.mark_saved(*changes);
+          Ok(())
+        })
+      })
   }
 
   /// Default save strategy for the stores.
@@ -331,6 +1078,35 @@ where
     self.default_save_strategy
   }
 
+  /// How the stores apply a partial state when they are patched.
+  #[inline]
+  pub fn default_patch_mode(&self) -> PatchMode {
+    self.default_patch_mode
+  }
+
+  /// Pushes the changes waiting to be synchronized with the remote, then pulls the remote ones.
+  ///
+  /// This does nothing if no [sync adapter](crate::StoreCollectionBuilder::sync_adapter) was set.
+  pub async fn sync_now(&self) -> Result<()> {
+    match &self.remote_sync {
+      Some(remote) => remote.sync::<R, C>(self.handle.app()).await,
+      None => Ok(()),
+    }
+  }
+
+  /// Status of the synchronization with the remote, if a sync adapter was set.
+  pub fn sync_status(&self) -> Option<SyncStatus> {
+    self.remote_sync.as_ref().map(RemoteSync::status)
+  }
+
+  /// Amount of changes waiting to be pushed to the remote.
+  pub fn pending_changes(&self) -> usize {
+    self
+      .remote_sync
+      .as_ref()
+      .map_or(0, RemoteSync::pending)
+  }
+
   /// Saves the stores periodically.
   pub fn set_autosave(&self, duration: Duration) {
     if let Ok(mut autosave) = self.autosave.lock() {
@@ -356,6 +1132,119 @@ where
       .locked(|store| Ok(store.watch(f)))
   }
 
+  /// Watches a store for changes, receiving what has changed.
+  pub fn watch_changes<F>(&self, store_id: impl AsRef<str>, f: F) -> Result<WatcherId>
+  where
+    F: Fn(AppHandle<R>, &StoreChange) -> Result<()> + Send + Sync + 'static,
+  {
+    self
+      .get_resource(store_id)?
+      .locked(|store| Ok(store.watch_changes(f)))
+  }
+
+  /// Watches a key of a store for changes.
+  pub fn watch_key<F>(
+    &self,
+    store_id: impl AsRef<str>,
+    key: impl AsRef<str>,
+    f: F,
+  ) -> Result<WatcherId>
+  where
+    F: Fn(AppHandle<R>, &StoreChange) -> Result<()> + Send + Sync + 'static,
+  {
+    self
+      .get_resource(store_id)?
+      .locked(|store| Ok(store.watch_key(key, f)))
+  }
+
+  /// Watches the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901)
+  /// of a store for changes.
+  pub fn watch_path<F>(
+    &self,
+    store_id: impl AsRef<str>,
+    pointer: impl AsRef<str>,
+    f: F,
+  ) -> Result<WatcherId>
+  where
+    F: Fn(AppHandle<R>, &StoreChange) -> Result<()> + Send + Sync + 'static,
+  {
+    self
+      .get_resource(store_id)?
+      .locked(|store| store.watch_path(pointer, f))
+  }
+
+  /// Watches some keys of a store for changes.
+  pub fn watch_keys<I, T, F>(&self, store_id: impl AsRef<str>, keys: I, f: F) -> Result<WatcherId>
+  where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+    F: Fn(AppHandle<R>, &StoreChange) -> Result<()> + Send + Sync + 'static,
+  {
+    self
+      .get_resource(store_id)?
+      .locked(|store| Ok(store.watch_keys(keys, f)))
+  }
+
+  /// Subscribes to the changes of a store.
+  ///
+  /// The changes are received in the order they were made. Unlike watchers, the store doesn't need
+  /// to be loaded to subscribe to it. If the receiver falls behind by more than a few hundred changes,
+  /// the oldest ones are dropped and the next call to [`recv`](broadcast::Receiver::recv) returns
+  /// [`RecvError::Lagged`](broadcast::error::RecvError::Lagged) with the amount that was skipped.
+  pub fn subscribe(&self, store_id: impl AsRef<str>) -> ChangeReceiver {
+    let id = StoreId::from(store_id.as_ref());
+    self
+      .subscribers
+      .entry(id)
+      .or_insert_with(|| broadcast::channel(SUBSCRIPTION_CAPACITY).0)
+      .subscribe()
+  }
+
+  /// Whether anyone is still subscribed to the changes of a store.
+  fn has_subscribers(&self, id: &StoreId) -> bool {
+    self
+      .subscribers
+      .get(id)
+      .is_some_and(|sender| sender.receiver_count() > 0)
+  }
+
+  /// Queues the new state of a store to be passed down to its children that are loaded.
+  ///
+  /// The caller is expected to hold the lock on the store, so the states are queued in order.
+  /// They're passed down by [`StoreCollection::flush_children`].
+  pub(crate) fn queue_children(&self, parent: &StoreId, state: &StoreState, keys: &[KeyChange]) {
+    for (child, _) in self
+      .parents
+      .iter()
+      .filter(|(_, it)| *it == parent)
+    {
+      if let Some(rid) = self.rid(child) {
+        self
+          .inherit_queue
+          .push(rid, state.clone(), keys.to_vec());
+      }
+    }
+  }
+
+  /// Passes the queued states down to the children, unless the current thread holds any store.
+  pub(crate) fn flush_children(&self) {
+    self
+      .inherit_queue
+      .flush::<R, C>(self.handle.app());
+  }
+
+  /// Sends the change to the subscribers of the store.
+  ///
+  /// The caller is expected to hold the lock on the store, so the changes are sent in order.
+  pub(crate) fn publish_change(&self, change: &Arc<StoreChange>) {
+    // Nobody is listening anymore.
+    self
+      .subscribers
+      .remove_if(&change.id, |_, sender| {
+        sender.send(Arc::clone(change)).is_err()
+      });
+  }
+
   /// Removes a watcher from a store.
   pub fn unwatch(
     &self,
@@ -395,7 +1284,7 @@ where
   #[doc(hidden)]
   pub fn destroy(&self, id: impl AsRef<str>) -> Result<()> {
     let id = StoreId::from(id.as_ref());
-    self.unload_and(&id, Store::destroy)
+    self.unload_and(&id, |resource| resource.locked(Store::destroy))
   }
 
   /// Removes the store from the collection.
@@ -404,40 +1293,203 @@ where
     // The store needs to be saved immediately here.
     // Otherwise, the plugin might try to load it again if `StoreCollection::get_resource` is called.
     // This scenario will happen whenever the save strategy is not `Immediate`.
-    self.unload_and(id, |store| store.save_now())
+    self.unload_and(id, |resource| resource.save_now(false))
   }
 
   fn unload_and<F>(&self, id: &StoreId, f: F) -> Result<()>
   where
-    F: FnOnce(&mut Store<R, C>) -> Result<()>,
+    F: FnOnce(&StoreResource<R, C>) -> Result<()>,
   {
     let app = self.handle.app();
     if let Some((_, rid)) = self.stores.remove(id) {
       let resource = StoreResource::<R, C>::take(app, rid)?;
-      resource.locked(f)?;
+      f(&resource)?;
       emit(app, STORE_UNLOAD_EVENT, id, None::<&str>)?;
     }
 
     Ok(())
   }
 
+  /// Saves and unloads the stores that the eviction policy no longer allows to stay loaded.
+  ///
+  /// This already happens periodically, as well as whenever a store is loaded,
+  /// if the policy limits how many stores can be loaded at once.
+  pub fn evict(&self) -> Result<()> {
+    self.evict_except(None)
+  }
+
+  fn evict_except(&self, keep: Option<&StoreId>) -> Result<()> {
+    let policy = self.eviction.policy;
+    if !policy.is_enabled() {
+      return Ok(());
+    }
+
+    let app = self.handle.app();
+    let mut loaded = 0;
+    let mut bytes = 0;
+    let mut candidates = Vec::new();
+
+    for (id, rid) in self.rids_by_id() {
+      let Ok(resource) = StoreResource::<R, C>::get(app, rid) else {
+        continue;
+      };
+
+      loaded += 1;
+
+      // A store that is locked or held elsewhere is in use, so it isn't a candidate.
+      // Besides, locking it here could deadlock if this thread is the one holding it.
+      let Ok(store) = resource.inner.try_lock() else {
+        continue;
+      };
+
+      let size = estimate_size(store.persisted_state());
+      bytes += size;
+
+      if keep != Some(&id) && self.is_evictable(&id, &resource, &store) {
+        candidates.push((id, rid, resource.last_access(), size));
+      }
+    }
+
+    candidates.sort_by_key(|(_, _, last_access, _)| *last_access);
+
+    let mut evicted = Vec::new();
+    for (id, rid, last_access, size) in candidates {
+      let idle = policy
+        .idle_timeout
+        .is_some_and(|timeout| last_access.elapsed() >= timeout);
+
+      let over_limit = policy.max_stores.is_some_and(|max| loaded > max)
+        || policy.max_bytes.is_some_and(|max| bytes > max);
+
+      if idle || over_limit {
+        loaded -= 1;
+        bytes -= size;
+        evicted.push((id, rid));
+      }
+    }
+
+    parallel::try_for_each(self, evicted, |collection, id, rid| {
+      collection.evict_store(id, rid)
+    })
+  }
+
+  /// Saves a store, then unloads it unless it has been used in the meantime.
+  fn evict_store(&self, id: &StoreId, rid: ResourceId) -> Result<()> {
+    let app = self.handle.app();
+    StoreResource::<R, C>::get(app, rid)?.save_now(false)?;
+
+    // Holding the resources table keeps anyone else from getting the store
+    // between the moment it's checked and the moment it's removed.
+    let mut table = app.resources_table();
+    let Ok(resource) = table.get::<StoreResource<R, C>>(rid) else {
+      return Ok(());
+    };
+
+    let Ok(store) = resource.inner.try_lock() else {
+      return Ok(());
+    };
+
+    // A store changed since it was saved would otherwise lose those changes.
+    if store.is_dirty() || !self.is_evictable(id, &resource, &store) {
+      return Ok(());
+    }
+
+    drop(store);
+    drop(resource);
+
+    self.stores.remove_if(id, |_, it| *it == rid);
+    table.take::<StoreResource<R, C>>(rid)?;
+    drop(table);
+
+    emit(app, STORE_UNLOAD_EVENT, id, None::<&str>)
+  }
+
+  /// Whether a store, which the caller has locked, isn't being used by anyone else.
+  fn is_evictable(
+    &self,
+    id: &StoreId,
+    resource: &Arc<StoreResource<R, C>>,
+    store: &Store<R, C>,
+  ) -> bool {
+    // Besides the one being checked, the only reference should be the one in the resources table.
+    store.can_evict() && !self.has_subscribers(id) && Arc::strong_count(resource) <= 2
+  }
+
+  /// Starts the tasks that run in the background while the collection is in use.
+  pub(crate) fn start_tasks(&self) -> Result<()> {
+    let app = self.handle.app();
+    self
+      .autosave
+      .lock()
+      .expect("autosave is poisoned")
+      .start::<R, C>(app);
+
+    self.expiry_sweep.start::<R, C>(app);
+    self.eviction.start::<R, C>(app);
+
+    if let Some(sync) = &self.process_sync {
+      sync.start::<R, C>(app)?;
+    }
+
+    if let Some(remote) = &self.remote_sync {
+      remote.start::<R, C>(app);
+    }
+
+    Ok(())
+  }
+
   /// Runs any necessary tasks before the application exits.
   #[doc(hidden)]
   pub fn on_exit(&self) -> Result<()> {
     self.clear_autosave();
+    self.expiry_sweep.stop();
+    self.eviction.stop();
 
     let app = self.handle.app();
+    let layout = self.layout();
+    let mut stores = Vec::new();
+    let mut pending = Vec::new();
+
     for rid in self.rids() {
       if let Ok(resource) = StoreResource::<R, C>::take(app, rid) {
         resource.locked(|store| {
           store.abort_pending_save();
           if store.save_on_exit {
-            let _ = store.save_now();
+            if layout.is_container() {
+              if store.is_dirty() && !self.save_denylist.contains(&store.id) {
+                let _ = store.save_sidecars();
+                let state = store.persisted_state().clone();
+                let len = store.journal.len();
+                let changes = store.changes();
+                stores.push((Arc::clone(&resource), store.id.clone(), state, len, changes));
+              }
+            } else if let Ok(save) = store.begin_save(false) {
+              pending.push((Arc::clone(&resource), save));
+            }
           }
         });
       }
     }
 
+    // The files are written while the other stores are still being serialized.
+    for (resource, save) in pending {
+      let _ = resource.finish_save(save);
+    }
+
+    self.write_container_stores(&stores)?;
+
+    if let Some(sync) = &self.process_sync {
+      sync.stop();
+    }
+
+    if let Some(remote) = &self.remote_sync {
+      remote.stop();
+    }
+
+    // Every file is written before other processes may acquire the lock.
+    self.writer.close();
+    self.lock.lock().expect("lock is poisoned").take();
+
     Ok(())
   }
 }
@@ -460,3 +1512,11 @@ where
       .finish_non_exhaustive()
   }
 }
+
+/// Pairs each id with a unit value, as expected by [`parallel::try_for_each`].
+fn with_ids(ids: &[impl AsRef<str>]) -> Vec<(StoreId, ())> {
+  ids
+    .iter()
+    .map(|id| (StoreId::from(id.as_ref()), ()))
+    .collect()
+}
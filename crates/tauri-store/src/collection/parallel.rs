@@ -0,0 +1,109 @@
+use super::{CollectionMarker, StoreCollection};
+use crate::error::{Error, Result};
+use crate::io_err;
+use crate::manager::ManagerExt;
+use crate::store::StoreId;
+use std::num::NonZeroUsize;
+use std::sync::{mpsc, Arc, Mutex};
+use std::{mem, thread, vec};
+use tauri::async_runtime::spawn_blocking;
+use tauri::Runtime;
+
+/// Upper bound on the threads used to save or load stores at the same time.
+const MAX_THREADS: usize = 4;
+
+/// Amount of threads to use when working on several stores at once.
+pub(crate) fn parallelism() -> usize {
+  thread::available_parallelism()
+    .map_or(1, NonZeroUsize::get)
+    .min(MAX_THREADS)
+}
+
+/// Calls the closure for every store, spreading them across a bounded number of blocking tasks
+/// of the async runtime, then waits for all of them to finish.
+///
+/// Unlike [`Iterator::try_for_each`], a failure doesn't stop the remaining stores.
+/// Every error is collected instead and returned as [`Error::Multiple`].
+pub(crate) fn try_for_each<R, C, T, F>(
+  collection: &StoreCollection<R, C>,
+  items: Vec<(StoreId, T)>,
+  f: F,
+) -> Result<()>
+where
+  R: Runtime,
+  C: CollectionMarker,
+  T: Send + 'static,
+  F: Fn(&StoreCollection<R, C>, &StoreId, T) -> Result<()> + Send + Sync + 'static,
+{
+  let tasks = parallelism().min(items.len());
+  let work = Arc::new(Work {
+    queue: Mutex::new(items.into_iter()),
+    errors: Mutex::new(Vec::new()),
+    f,
+  });
+
+  if tasks > 1 {
+    let (sender, receiver) = mpsc::channel();
+    for _ in 0..tasks {
+      let app = collection.app_handle().clone();
+      let work = Arc::clone(&work);
+      let sender = sender.clone();
+      spawn_blocking(move || {
+        work.run(&app.store_collection_with_marker::<C>());
+        let _ = sender.send(());
+      });
+    }
+
+    drop(sender);
+
+    // The sender of a task that panicked is dropped without sending anything.
+    for _ in 0..tasks {
+      if receiver.recv().is_err() {
+        return io_err!(Other, "a task working on the stores has panicked");
+      }
+    }
+  } else {
+    work.run(collection);
+  }
+
+  let errors = mem::take(&mut *work.errors.lock().expect("errors are poisoned"));
+  if errors.is_empty() {
+    Ok(())
+  } else {
+    Err(Error::Multiple(errors))
+  }
+}
+
+struct Work<T, F> {
+  queue: Mutex<vec::IntoIter<(StoreId, T)>>,
+  errors: Mutex<Vec<(StoreId, Error)>>,
+  f: F,
+}
+
+impl<T, F> Work<T, F> {
+  fn run<R, C>(&self, collection: &StoreCollection<R, C>)
+  where
+    R: Runtime,
+    C: CollectionMarker,
+    F: Fn(&StoreCollection<R, C>, &StoreId, T) -> Result<()>,
+  {
+    loop {
+      let next = self
+        .queue
+        .lock()
+        .expect("queue is poisoned")
+        .next();
+      let Some((id, item)) = next else {
+        break;
+      };
+
+      if let Err(err) = (self.f)(collection, &id, item) {
+        self
+          .errors
+          .lock()
+          .expect("errors are poisoned")
+          .push((id, err));
+      }
+    }
+  }
+}
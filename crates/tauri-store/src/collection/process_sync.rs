@@ -0,0 +1,432 @@
+use super::CollectionMarker;
+use crate::error::Result;
+use crate::io::remove_file;
+use crate::manager::ManagerExt;
+use crate::store::{Delta, StoreId};
+use fs4::fs_std::FileExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::async_runtime::spawn_blocking;
+use tauri::{AppHandle, Runtime};
+use tauri_store_utils::set_interval;
+use tokio::task::AbortHandle;
+
+/// Size after which the log of this process is rotated.
+const MAX_LOG_LEN: u64 = 1024 * 1024;
+
+/// A set of changes to a store, as written to the log of the process that made them.
+#[derive(Debug, Deserialize, Serialize)]
+struct Record {
+  origin: String,
+  ts: u64,
+  store: StoreId,
+  deltas: Vec<Delta>,
+}
+
+/// When and where a key was last changed.
+///
+/// Versions are compared by their timestamp, then by their origin,
+/// so every process picks the same winner when two changes conflict.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Version {
+  ts: u64,
+  origin: String,
+}
+
+/// Shares the changes made to the stores with other processes using the same collection directory.
+///
+/// Each process appends its changes to its own log, and periodically reads the logs of the others.
+/// Conflicts are resolved per key by the last-writer-wins rule, using a hybrid logical clock
+/// so that a change is always considered newer than every change its process had already seen.
+pub(crate) struct ProcessSync {
+  dir: PathBuf,
+  origin: String,
+  interval: Duration,
+  read_only: bool,
+  state: Mutex<SyncState>,
+  abort_handle: Mutex<Option<AbortHandle>>,
+}
+
+#[derive(Default)]
+struct SyncState {
+  clock: u64,
+  generation: u32,
+  /// Logs of this process, the current one being the last.
+  /// The previous one is kept around so that other processes can finish reading it.
+  logs: VecDeque<OwnLog>,
+  /// Keeps the logs of this process from being seen as stale.
+  lock: Option<File>,
+  offsets: HashMap<PathBuf, u64>,
+  versions: HashMap<(StoreId, String), Version>,
+}
+
+struct OwnLog {
+  path: PathBuf,
+  file: File,
+  len: u64,
+}
+
+impl ProcessSync {
+  pub(crate) fn new(dir: &Path, interval: Duration, read_only: bool) -> Self {
+    let nanos = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .unwrap_or_default()
+      .as_nanos();
+
+    Self {
+      dir: dir.join("sync.tauristore"),
+      origin: format!("{}-{nanos:x}", process::id()),
+      interval,
+      read_only,
+      state: Mutex::new(SyncState::default()),
+      abort_handle: Mutex::new(None),
+    }
+  }
+
+  /// Removes the logs left behind by processes that are no longer running,
+  /// then starts reading the logs of the others.
+  ///
+  /// Changes made before this process started are not applied,
+  /// as they are expected to have been saved already.
+  pub(crate) fn start<R, C>(&self, app: &AppHandle<R>) -> Result<()>
+  where
+    R: Runtime,
+    C: CollectionMarker,
+  {
+    fs::create_dir_all(&self.dir)?;
+
+    let mut state = self.state();
+    if !self.read_only {
+      self.remove_stale_logs()?;
+
+      let lock = File::create(self.dir.join(format!("{}.lock", self.origin)))?;
+      FileExt::lock_exclusive(&lock)?;
+      state.lock = Some(lock);
+    }
+
+    for path in self.remote_logs()? {
+      let len = fs::metadata(&path)?.len();
+      state.offsets.insert(path, len);
+    }
+
+    drop(state);
+
+    let abort_handle = set_interval(app, self.interval, |app| async move {
+      let _ = spawn_blocking(move || poll::<R, C>(&app)).await;
+    });
+
+    self
+      .abort_handle
+      .lock()
+      .expect("abort handle is poisoned")
+      .replace(abort_handle);
+
+    Ok(())
+  }
+
+  /// Stops reading the logs of other processes and releases the lock on the logs of this one.
+  ///
+  /// The logs are left in place, as the other processes may not have read the last records yet.
+  /// They are removed as stale by the next process to start.
+  pub(crate) fn stop(&self) {
+    if let Some(handle) = self
+      .abort_handle
+      .lock()
+      .expect("abort handle is poisoned")
+      .take()
+    {
+      handle.abort();
+    }
+
+    let mut state = self.state();
+    state.logs.clear();
+    state.lock = None;
+  }
+
+  /// Appends changes made by this process to its log.
+  ///
+  /// The caller is expected to hold the lock on the store.
+  pub(crate) fn publish(&self, id: &StoreId, deltas: &[Delta]) -> Result<()> {
+    if self.read_only || deltas.is_empty() {
+      return Ok(());
+    }
+
+    let mut state = self.state();
+    let ts = state.tick(now());
+    let version = Version { ts, origin: self.origin.clone() };
+    for delta in deltas {
+      let key = (id.clone(), delta.key().to_owned());
+      state.versions.insert(key, version.clone());
+    }
+
+    let record = Record {
+      origin: self.origin.clone(),
+      ts,
+      store: id.clone(),
+      deltas: deltas.to_vec(),
+    };
+
+    let mut bytes = serde_json::to_vec(&record)?;
+    bytes.push(b'\n');
+
+    let log = self.current_log(&mut state)?;
+    log.file.write_all(&bytes)?;
+    log.file.flush()?;
+    log.len += bytes.len() as u64;
+
+    Ok(())
+  }
+
+  /// Reads the records that other processes have appended to their logs since the last call.
+  fn read(&self) -> Result<Vec<Record>> {
+    let mut state = self.state();
+    let paths = self.remote_logs()?;
+    state
+      .offsets
+      .retain(|path, _| paths.contains(path));
+
+    // No more records will come from a process whose logs are all gone,
+    // so there is nothing left to compare its versions against.
+    let origins = paths
+      .iter()
+      .map(|path| origin_of(path))
+      .collect::<HashSet<_>>();
+
+    state.versions.retain(|_, version| {
+      version.origin == self.origin || origins.contains(version.origin.as_str())
+    });
+
+    let mut records = Vec::new();
+    for path in paths {
+      let offset = state.offsets.entry(path.clone()).or_default();
+      let mut file = match File::open(&path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => continue,
+        Err(err) => return Err(err.into()),
+      };
+
+      // The log was replaced by a new one with the same name.
+      if file.metadata()?.len() < *offset {
+        *offset = 0;
+      }
+
+      let mut bytes = Vec::new();
+      file.seek(SeekFrom::Start(*offset))?;
+      file.read_to_end(&mut bytes)?;
+
+      // An incomplete line is still being written, so it will be read next time.
+      for line in bytes.split_inclusive(|byte| *byte == b'\n') {
+        if !line.ends_with(b"\n") {
+          break;
+        }
+
+        *offset += line.len() as u64;
+        if let Ok(record) = serde_json::from_slice::<Record>(line) {
+          records.push(record);
+        }
+      }
+    }
+
+    for record in &records {
+      state.tick(record.ts);
+    }
+
+    Ok(records)
+  }
+
+  /// Keeps only the remote changes that are newer than the current version of their key.
+  ///
+  /// The caller is expected to hold the lock on the store.
+  fn merge(&self, id: &StoreId, records: Vec<Record>) -> Vec<Delta> {
+    let mut state = self.state();
+    let mut deltas = Vec::new();
+    for record in records {
+      let version = Version { ts: record.ts, origin: record.origin };
+      for delta in record.deltas {
+        let key = (id.clone(), delta.key().to_owned());
+        if state
+          .versions
+          .get(&key)
+          .map_or(true, |current| *current < version)
+        {
+          state.versions.insert(key, version.clone());
+          deltas.push(delta);
+        }
+      }
+    }
+
+    deltas
+  }
+
+  fn current_log<'a>(&self, state: &'a mut SyncState) -> Result<&'a mut OwnLog> {
+    let is_full = state
+      .logs
+      .back()
+      .map_or(true, |log| log.len >= MAX_LOG_LEN);
+
+    if is_full {
+      state.generation += 1;
+      let path = self
+        .dir
+        .join(format!("{}.{}.log", self.origin, state.generation));
+
+      let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+
+      state
+        .logs
+        .push_back(OwnLog { path, file, len: 0 });
+
+      if state.logs.len() > 2 {
+        if let Some(log) = state.logs.pop_front() {
+          drop(log.file);
+          remove_file(&log.path)?;
+        }
+      }
+    }
+
+    Ok(
+      state
+        .logs
+        .back_mut()
+        .expect("there is always a current log"),
+    )
+  }
+
+  /// Lists the logs of the other processes.
+  fn remote_logs(&self) -> Result<Vec<PathBuf>> {
+    let prefix = format!("{}.", self.origin);
+    let paths = read_dir(&self.dir)?
+      .into_iter()
+      .filter(|path| has_extension(path, "log"))
+      .filter(|path| !file_name(path).starts_with(&prefix))
+      .collect();
+
+    Ok(paths)
+  }
+
+  /// Removes the files of every process whose lock is no longer held.
+  fn remove_stale_logs(&self) -> Result<()> {
+    let paths = read_dir(&self.dir)?;
+    for lock_path in paths
+      .iter()
+      .filter(|path| has_extension(path, "lock"))
+    {
+      let Ok(file) = OpenOptions::new().write(true).open(lock_path) else {
+        continue;
+      };
+
+      if !FileExt::try_lock_exclusive(&file)? {
+        continue;
+      }
+
+      drop(file);
+
+      let origin = file_name(lock_path).trim_end_matches(".lock");
+      let prefix = format!("{origin}.");
+      for path in &paths {
+        if file_name(path).starts_with(&prefix) {
+          remove_file(path)?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  fn state(&self) -> MutexGuard<'_, SyncState> {
+    self.state.lock().expect("sync state is poisoned")
+  }
+}
+
+impl SyncState {
+  /// Advances the clock past both its current value and the given timestamp.
+  fn tick(&mut self, ts: u64) -> u64 {
+    self.clock = self.clock.saturating_add(1).max(ts);
+    self.clock
+  }
+}
+
+/// Applies the changes made by other processes to the local stores.
+fn poll<R, C>(app: &AppHandle<R>)
+where
+  R: Runtime,
+  C: CollectionMarker,
+{
+  let collection = app.store_collection_with_marker::<C>();
+  let Some(sync) = &collection.process_sync else {
+    return;
+  };
+
+  let Ok(records) = sync.read() else {
+    return;
+  };
+
+  let mut stores: Vec<(StoreId, Vec<Record>)> = Vec::new();
+  for record in records {
+    match stores
+      .iter_mut()
+      .find(|(id, _)| *id == record.store)
+    {
+      Some((_, records)) => records.push(record),
+      None => stores.push((record.store.clone(), vec![record])),
+    }
+  }
+
+  for (id, records) in stores {
+    if let Ok(resource) = collection.get_resource(&id) {
+      let _ = resource.locked(|store| {
+        let deltas = sync.merge(&id, records);
+        if deltas.is_empty() {
+          Ok(())
+        } else {
+          store.apply_remote_deltas(deltas)
+        }
+      });
+    }
+  }
+}
+
+fn now() -> u64 {
+  let millis = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_millis();
+
+  u64::try_from(millis).unwrap_or(u64::MAX)
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+  match fs::read_dir(dir) {
+    Ok(entries) => entries.map(|entry| Ok(entry?.path())).collect(),
+    Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+    Err(err) => Err(err.into()),
+  }
+}
+
+fn file_name(path: &Path) -> &str {
+  path
+    .file_name()
+    .and_then(|name| name.to_str())
+    .unwrap_or_default()
+}
+
+/// Returns the origin of the process that owns a log.
+fn origin_of(path: &Path) -> &str {
+  let name = file_name(path);
+  name
+    .split_once('.')
+    .map_or(name, |(origin, _)| origin)
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+  path.extension().is_some_and(|it| it == extension)
+}
@@ -0,0 +1,62 @@
+use super::CollectionMarker;
+use crate::manager::ManagerExt;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::async_runtime::spawn_blocking;
+use tauri::{AppHandle, Runtime};
+use tauri_store_utils::set_interval;
+use tokio::task::AbortHandle;
+
+/// Periodically removes the expired keys of every loaded store,
+/// so they don't linger until the store is accessed again.
+pub(crate) struct ExpirySweep {
+  interval: Duration,
+  abort_handle: Mutex<Option<AbortHandle>>,
+}
+
+impl ExpirySweep {
+  pub(crate) fn new(interval: Duration) -> Self {
+    Self {
+      interval,
+      abort_handle: Mutex::new(None),
+    }
+  }
+
+  pub(crate) fn start<R, C>(&self, app: &AppHandle<R>)
+  where
+    R: Runtime,
+    C: CollectionMarker,
+  {
+    self.stop();
+    let abort_handle = set_interval(app, self.interval, |app| async move {
+      let _ = spawn_blocking(move || {
+        app
+          .store_collection_with_marker::<C>()
+          .purge_expired()
+      })
+      .await;
+    });
+
+    *self
+      .abort_handle
+      .lock()
+      .expect("abort handle is poisoned") = Some(abort_handle);
+  }
+
+  pub(crate) fn stop(&self) {
+    if let Some(handle) = self
+      .abort_handle
+      .lock()
+      .expect("abort handle is poisoned")
+      .take()
+    {
+      handle.abort();
+    }
+  }
+}
+
+impl Drop for ExpirySweep {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
@@ -0,0 +1,283 @@
+use super::{CollectionMarker, StoreCollection};
+use crate::error::Result;
+use crate::io::{remove_file, write_file_atomic};
+use crate::io_err;
+use crate::store::{Batch, Delta, StoreGuard, StoreId, StoreResource, StoreState};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Runtime;
+
+/// Changes staged for multiple stores, which are committed together.
+///
+/// See [`StoreCollection::transaction`].
+pub struct Transaction<'a> {
+  stores: Vec<(StoreId, Batch<'a>)>,
+}
+
+impl<'a> Transaction<'a> {
+  /// Gets a reference to a raw value, taking into account the changes already staged.
+  pub fn get_raw(&self, store_id: impl AsRef<str>, key: impl AsRef<str>) -> Result<Option<&Value>> {
+    Ok(self.batch(store_id.as_ref())?.get_raw(key))
+  }
+
+  /// Gets a value and tries to parse it as an instance of type `T`.
+  pub fn get<T>(&self, store_id: impl AsRef<str>, key: impl AsRef<str>) -> Result<T>
+  where
+    T: DeserializeOwned,
+  {
+    let key = key.as_ref();
+    match self.get_raw(store_id, key)? {
+      Some(value) => Ok(serde_json::from_value(value.clone())?),
+      None => io_err!(NotFound, "key not found: {key}"),
+    }
+  }
+
+  /// Sets a key-value pair in a store.
+  pub fn set(
+    &mut self,
+    store_id: impl AsRef<str>,
+    key: impl AsRef<str>,
+    value: impl Into<Value>,
+  ) -> Result<&mut Self> {
+    self.batch_mut(store_id.as_ref())?.set(key, value);
+    Ok(self)
+  }
+
+  /// Removes a key from a store.
+  pub fn remove(&mut self, store_id: impl AsRef<str>, key: impl AsRef<str>) -> Result<&mut Self> {
+    self.batch_mut(store_id.as_ref())?.remove(key);
+    Ok(self)
+  }
+
+  /// Patches the state of a store.
+  pub fn patch(
+    &mut self,
+    store_id: impl AsRef<str>,
+    state: impl Into<StoreState>,
+  ) -> Result<&mut Self> {
+    self.batch_mut(store_id.as_ref())?.patch(state);
+    Ok(self)
+  }
+
+  fn batch(&self, id: &str) -> Result<&Batch<'a>> {
+    match self
+      .stores
+      .iter()
+      .find(|(it, _)| it.as_ref() == id)
+    {
+      Some((_, batch)) => Ok(batch),
+      None => io_err!(InvalidInput, "store is not part of the transaction: {id}"),
+    }
+  }
+
+  fn batch_mut(&mut self, id: &str) -> Result<&mut Batch<'a>> {
+    match self
+      .stores
+      .iter_mut()
+      .find(|(it, _)| it.as_ref() == id)
+    {
+      Some((_, batch)) => Ok(batch),
+      None => io_err!(InvalidInput, "store is not part of the transaction: {id}"),
+    }
+  }
+}
+
+/// Changes of a committed transaction, persisted before they are applied to the stores.
+///
+/// If the app is killed before every store is saved, the changes are applied again
+/// the next time the collection is built, so either all of them reach the disk or none do.
+#[derive(Deserialize, Serialize)]
+struct IntentLog {
+  stores: Vec<(StoreId, Vec<Delta>)>,
+}
+
+impl<R, C> StoreCollection<R, C>
+where
+  R: Runtime,
+  C: CollectionMarker,
+{
+  /// Updates multiple stores atomically.
+  ///
+  /// The stores are locked in a deterministic order, so concurrent transactions can't deadlock.
+  /// The changes are staged while the closure runs and are only applied if it succeeds.
+  /// Otherwise, every store is left untouched.
+  pub fn transaction<I, T, F, U>(&self, ids: I, f: F) -> Result<U>
+  where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+    F: FnOnce(&mut Transaction<'_>) -> Result<U>,
+  {
+    let mut ids = ids
+      .into_iter()
+      .map(|id| StoreId::from(id.as_ref()))
+      .collect::<Vec<_>>();
+
+    ids.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+    ids.dedup();
+
+    let resources = ids
+      .iter()
+      .map(|id| self.get_resource(id))
+      .collect::<Result<Vec<_>>>()?;
+
+    let guards = resources
+      .iter()
+      .map(|resource| resource.lock())
+      .collect::<Vec<_>>();
+
+    let (value, stores) = {
+      let mut tx = Transaction {
+        stores: ids
+          .iter()
+          .cloned()
+          .zip(
+            guards
+              .iter()
+              .map(|store| Batch::new(store.raw_state())),
+          )
+          .collect(),
+      };
+
+      let value = f(&mut tx)?;
+      let stores = tx
+        .stores
+        .into_iter()
+        .map(|(id, batch)| (id, batch.into_deltas()))
+        .filter(|(_, deltas)| !deltas.is_empty())
+        .collect::<Vec<_>>();
+
+      (value, stores)
+    };
+
+    let result = if stores.is_empty() {
+      drop(guards);
+      Ok(())
+    } else {
+      self.commit(guards, &resources, stores)
+    };
+
+    // The children of the stores can only be updated once every store is released.
+    self.flush_children();
+
+    result.map(|()| value)
+  }
+
+  /// Applies the changes while every store is locked,
+  /// but only waits for their files to be written once they are all released.
+  fn commit(
+    &self,
+    mut guards: Vec<StoreGuard<'_, R, C>>,
+    resources: &[Arc<StoreResource<R, C>>],
+    stores: Vec<(StoreId, Vec<Delta>)>,
+  ) -> Result<()> {
+    let path = self.intent_log_dir().join(intent_log_name());
+    let log = IntentLog { stores };
+    if !self.read_only {
+      write_file_atomic(&path, &serde_json::to_vec(&log)?)?;
+    }
+
+    // Once the intent is on the disk, the changes are applied even if one of the stores fails.
+    let mut result = Ok(());
+    for (id, deltas) in log.stores {
+      if let Some(store) = guards.iter_mut().find(|store| store.id == id) {
+        let applied = store
+          .apply_deltas(deltas, None::<&str>)
+          .and_then(|()| {
+            store.abort_pending_save();
+            store.save_now()
+          });
+
+        result = result.and(applied);
+      }
+    }
+
+    let pending = guards
+      .iter()
+      .map(|store| store.take_deferred_save())
+      .collect::<Vec<_>>();
+
+    drop(guards);
+
+    // The guards and the resources are in the same order.
+    for (resource, pending) in resources.iter().zip(pending) {
+      result = result.and(resource.finish_save(pending));
+    }
+
+    result?;
+    remove_file(&path)
+  }
+
+  /// Applies the changes of every transaction that was interrupted before it could be saved,
+  /// in the order they were committed.
+  pub(crate) fn recover_transactions(&self) -> Result<()> {
+    if self.read_only {
+      return Ok(());
+    }
+
+    let mut paths = match fs::read_dir(self.intent_log_dir()) {
+      Ok(entries) => entries
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?,
+      Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+      Err(err) => return Err(err.into()),
+    };
+
+    paths.sort();
+
+    for path in paths {
+      // The transaction was interrupted before its intent was fully written,
+      // so none of its changes were applied.
+      if path.extension().is_some_and(|it| it == "tmp") {
+        remove_file(&path)?;
+        continue;
+      }
+
+      let log = serde_json::from_slice::<IntentLog>(&fs::read(&path)?)?;
+
+      // The app may have been killed before the changes were published, and
+      // publishing them again is harmless, as the values are the same.
+      for (id, deltas) in log.stores {
+        let resource = self.get_resource(&id)?;
+        resource.locked(|store| store.apply_deltas(deltas, None::<&str>))?;
+        resource.save_now(false)?;
+      }
+
+      remove_file(&path)?;
+    }
+
+    Ok(())
+  }
+
+  /// Directory where each transaction writes its own intent log.
+  fn intent_log_dir(&self) -> PathBuf {
+    let dirname = if cfg!(debug_assertions) && self.debug_stores {
+      "transactions.dev.tauristore"
+    } else {
+      "transactions.tauristore"
+    };
+
+    self.path().join(dirname)
+  }
+}
+
+/// Returns a name for the intent log that no other transaction is using,
+/// and that sorts after the names of the transactions committed before.
+fn intent_log_name() -> String {
+  static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+  let nanos = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_nanos();
+
+  let sequence = SEQUENCE.fetch_add(1, Ordering::Relaxed);
+  format!("{nanos:032x}-{sequence:016x}-{}.json", process::id())
+}
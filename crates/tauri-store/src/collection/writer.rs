@@ -0,0 +1,236 @@
+use crate::error::{Error, Result};
+use crate::io::write_file;
+use crate::io_err;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::io::{self, ErrorKind};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::result::Result as StdResult;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+
+type WriteResult = StdResult<(), (ErrorKind, String)>;
+
+/// Writes the store files on dedicated threads, so that no store
+/// needs to stay locked while the disk is busy.
+///
+/// A file is always written by the same thread, in the order it was queued. If it is
+/// queued again before its previous write has started, only the most recent bytes are written.
+pub(crate) struct Writer {
+  queues: Vec<Arc<Queue>>,
+  threads: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Writer {
+  pub(crate) fn new(threads: usize) -> Self {
+    let (queues, threads) = (0..threads.max(1))
+      .map(|_| {
+        let queue = Arc::new(Queue::default());
+        let worker = Arc::clone(&queue);
+        let thread = thread::Builder::new()
+          .name(String::from("tauri-store-writer"))
+          .spawn(move || worker.run())
+          .expect("failed to spawn a writer thread");
+
+        (queue, thread)
+      })
+      .unzip();
+
+    Self { queues, threads: Mutex::new(threads) }
+  }
+
+  fn queue(&self, path: &Path) -> &Queue {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    let len = self.queues.len() as u64;
+    let index = usize::try_from(hasher.finish() % len).unwrap_or_default();
+    &self.queues[index]
+  }
+
+  /// Queues the bytes to be written to the file.
+  ///
+  /// Once they are, `saved` is raised to `changes`.
+  pub(crate) fn enqueue(
+    &self,
+    path: PathBuf,
+    bytes: Vec<u8>,
+    saved: Arc<AtomicU64>,
+    changes: u64,
+  ) -> WriteTicket {
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let queue = self.queue(&path);
+    let mut jobs = queue.lock();
+    if jobs.closed {
+      // There is no thread left to write it.
+      drop(jobs);
+      let job = Job {
+        bytes,
+        saved,
+        changes,
+        waiters: vec![sender],
+      };
+
+      write(&path, job);
+      return WriteTicket(receiver);
+    }
+
+    if let Some(job) = jobs.pending.get_mut(&path) {
+      job.bytes = bytes;
+      job.saved = saved;
+      job.changes = changes;
+      job.waiters.push(sender);
+    } else {
+      jobs.order.push_back(path.clone());
+      jobs.pending.insert(
+        path,
+        Job {
+          bytes,
+          saved,
+          changes,
+          waiters: vec![sender],
+        },
+      );
+    }
+
+    drop(jobs);
+    queue.changed.notify_all();
+    WriteTicket(receiver)
+  }
+
+  /// Blocks until every queued write has finished.
+  pub(crate) fn flush(&self) {
+    for queue in &self.queues {
+      let mut jobs = queue.lock();
+      while jobs.busy || !jobs.order.is_empty() {
+        jobs = queue
+          .changed
+          .wait(jobs)
+          .expect("writer queue is poisoned");
+      }
+    }
+  }
+
+  /// Stops the threads once they have written whatever is left in their queues,
+  /// and waits for them to do so.
+  ///
+  /// Files queued afterwards are written on the calling thread.
+  pub(crate) fn close(&self) {
+    for queue in &self.queues {
+      queue.lock().closed = true;
+      queue.changed.notify_all();
+    }
+
+    let threads = mem::take(
+      &mut *self
+        .threads
+        .lock()
+        .expect("writer threads are poisoned"),
+    );
+    for thread in threads {
+      let _ = thread.join();
+    }
+  }
+}
+
+impl Drop for Writer {
+  fn drop(&mut self) {
+    self.close();
+  }
+}
+
+/// Completion notification of a queued write.
+#[must_use]
+pub(crate) struct WriteTicket(Receiver<WriteResult>);
+
+impl WriteTicket {
+  /// Blocks until the write has finished.
+  pub(crate) fn wait(self) -> Result<()> {
+    match self.0.recv() {
+      Ok(Ok(())) => Ok(()),
+      Ok(Err((kind, message))) => Err(Error::Io(io::Error::new(kind, message))),
+      Err(_) => io_err!(Interrupted, "writer thread has stopped"),
+    }
+  }
+}
+
+#[derive(Default)]
+struct Queue {
+  jobs: Mutex<Jobs>,
+  changed: Condvar,
+}
+
+#[derive(Default)]
+struct Jobs {
+  order: VecDeque<PathBuf>,
+  pending: HashMap<PathBuf, Job>,
+  busy: bool,
+  closed: bool,
+}
+
+struct Job {
+  bytes: Vec<u8>,
+  saved: Arc<AtomicU64>,
+  changes: u64,
+  waiters: Vec<SyncSender<WriteResult>>,
+}
+
+impl Queue {
+  fn lock(&self) -> MutexGuard<'_, Jobs> {
+    self
+      .jobs
+      .lock()
+      .expect("writer queue is poisoned")
+  }
+
+  fn run(&self) {
+    while let Some((path, job)) = self.next() {
+      write(&path, job);
+      self.lock().busy = false;
+      self.changed.notify_all();
+    }
+  }
+
+  /// Waits for the next write, returning `None` once the writer is dropped and the queue is empty.
+  fn next(&self) -> Option<(PathBuf, Job)> {
+    let mut jobs = self.lock();
+    loop {
+      if let Some(path) = jobs.order.pop_front() {
+        let job = jobs
+          .pending
+          .remove(&path)
+          .expect("queued write is missing");
+        jobs.busy = true;
+        return Some((path, job));
+      }
+
+      if jobs.closed {
+        return None;
+      }
+
+      jobs = self
+        .changed
+        .wait(jobs)
+        .expect("writer queue is poisoned");
+    }
+  }
+}
+
+/// Writes the file, then notifies everyone waiting for it.
+fn write(path: &Path, job: Job) {
+  let result = write_file(path, &job.bytes).map_err(|err| match err {
+    Error::Io(err) => (err.kind(), err.to_string()),
+    err => (ErrorKind::Other, err.to_string()),
+  });
+
+  if result.is_ok() {
+    job.saved.fetch_max(job.changes, Ordering::AcqRel);
+  }
+
+  for waiter in job.waiters {
+    let _ = waiter.send(result.clone());
+  }
+}
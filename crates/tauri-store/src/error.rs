@@ -1,6 +1,8 @@
-use crate::store::MarshalingError;
+use crate::remote::SyncError;
+use crate::store::{MarshalingError, StoreId};
 use serde::{Serialize, Serializer};
 use std::error::Error as StdError;
+use std::path::PathBuf;
 use std::result::Result as StdResult;
 
 /// A [`Result`](std::result::Result) type with [`Error`](crate::Error) as the error variant.
@@ -19,12 +21,22 @@ pub enum Error {
   #[error(transparent)]
   FailedToSerialize(MarshalingError),
 
+  #[error("failed to sync with the remote: {0}")]
+  FailedToSync(SyncError),
+
   #[error(transparent)]
   Io(#[from] std::io::Error),
 
   #[error(transparent)]
   Json(#[from] serde_json::Error),
 
+  #[error("store collection is locked by another process: {}", .0.display())]
+  Locked(PathBuf),
+
+  /// Every failure of an operation on several stores, along with the store that caused it.
+  #[error("{} stores failed: {}", .0.len(), describe(.0))]
+  Multiple(Vec<(StoreId, Error)>),
+
   #[cfg(any(target_os = "android", target_os = "ios"))]
   #[error(transparent)]
   PluginInvoke(#[from] tauri::plugin::mobile::PluginInvokeError),
@@ -37,6 +49,26 @@ impl Error {
   pub const fn is_bad_rid(&self) -> bool {
     matches!(self, Self::Tauri(tauri::Error::BadResourceId(_)))
   }
+
+  pub const fn is_locked(&self) -> bool {
+    matches!(self, Self::Locked(_))
+  }
+
+  pub const fn is_sync_failure(&self) -> bool {
+    matches!(self, Self::FailedToSync(_))
+  }
+
+  pub const fn is_multiple(&self) -> bool {
+    matches!(self, Self::Multiple(_))
+  }
+}
+
+fn describe(errors: &[(StoreId, Error)]) -> String {
+  errors
+    .iter()
+    .map(|(id, err)| format!("{id}: {err}"))
+    .collect::<Vec<_>>()
+    .join("; ")
 }
 
 impl Serialize for Error {
@@ -5,7 +5,9 @@ use serde::Serialize;
 use tauri::{AppHandle, Emitter as _, EventTarget, Runtime, WebviewWindow, Window};
 
 pub const STORE_CONFIG_CHANGE_EVENT: &str = "tauri-store://config-change";
+pub const STORE_HISTORY_CHANGE_EVENT: &str = "tauri-store://history-change";
 pub const STORE_STATE_CHANGE_EVENT: &str = "tauri-store://state-change";
+pub const STORE_SYNC_STATUS_EVENT: &str = "tauri-store://sync-status";
 pub const STORE_UNLOAD_EVENT: &str = "tauri-store://unload";
 
 #[derive(Clone, Debug, Serialize)]
@@ -45,6 +47,28 @@ where
   }
 }
 
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HistoryPayload<'a> {
+  id: &'a StoreId,
+  can_undo: bool,
+  can_redo: bool,
+}
+
+impl<'a, R, C> From<&'a Store<R, C>> for HistoryPayload<'a>
+where
+  R: Runtime,
+  C: CollectionMarker,
+{
+  fn from(store: &'a Store<R, C>) -> Self {
+    Self {
+      id: &store.id,
+      can_undo: store.can_undo(),
+      can_redo: store.can_redo(),
+    }
+  }
+}
+
 pub(crate) fn emit<R, T, S>(app: &AppHandle<R>, event: &str, payload: &T, source: S) -> Result<()>
 where
   R: Runtime,
@@ -86,6 +110,7 @@ where
 }
 
 /// Source of a store event.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct EventSource(Option<String>);
 
 impl EventSource {
@@ -93,6 +118,12 @@ impl EventSource {
   pub const fn is_backend(&self) -> bool {
     self.0.is_none()
   }
+
+  /// Label of the window that caused the event, or `None` if it was the backend.
+  #[inline]
+  pub fn label(&self) -> Option<&str> {
+    self.0.as_deref()
+  }
 }
 
 impl From<&str> for EventSource {
@@ -0,0 +1,50 @@
+use crate::error::Result;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+/// Writes the bytes to a file, creating its parent directories if needed.
+pub(crate) fn write_file(path: &Path, bytes: &[u8]) -> Result<()> {
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+
+  let mut file = File::create(path)?;
+  file.write_all(bytes)?;
+  file.flush()?;
+
+  if cfg!(feature = "file-sync-all") {
+    file.sync_all()?;
+  }
+
+  Ok(())
+}
+
+/// Writes the bytes to a temporary file and then renames it to the target path.
+///
+/// Readers will either see the previous contents of the file or the new ones, never a mix of both.
+pub(crate) fn write_file_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+  let mut tmp = path.as_os_str().to_owned();
+  tmp.push(".tmp");
+
+  write_file(Path::new(&tmp), bytes)?;
+  fs::rename(&tmp, path)?;
+
+  Ok(())
+}
+
+/// Removes a file, ignoring the error if it does not exist.
+pub(crate) fn remove_file(path: &Path) -> Result<()> {
+  match fs::remove_file(path) {
+    Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+    _ => Ok(()),
+  }
+}
+
+/// Removes a directory and everything in it, ignoring the error if it does not exist.
+pub(crate) fn remove_dir_all(path: &Path) -> Result<()> {
+  match fs::remove_dir_all(path) {
+    Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+    _ => Ok(()),
+  }
+}
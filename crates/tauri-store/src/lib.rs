@@ -5,9 +5,11 @@
 mod collection;
 mod error;
 mod event;
+mod io;
 mod manager;
 mod migration;
 pub mod prelude;
+mod remote;
 mod store;
 
 #[cfg(feature = "plugin")]
@@ -16,16 +18,22 @@ mod command;
 #[cfg(feature = "plugin")]
 mod plugin;
 
-pub use collection::{CollectionMarker, DefaultMarker, Handle, OnLoadFn, StoreCollection};
+pub use collection::{
+  CollectionLayout, CollectionMarker, DefaultMarker, EvictionPolicy, Handle, LockPolicy, OnLoadFn,
+  StoreCollection, Transaction,
+};
 pub use error::{BoxResult, Error, Result};
 pub use event::{
-  EventSource, STORE_CONFIG_CHANGE_EVENT, STORE_STATE_CHANGE_EVENT, STORE_UNLOAD_EVENT,
+  EventSource, STORE_CONFIG_CHANGE_EVENT, STORE_HISTORY_CHANGE_EVENT, STORE_STATE_CHANGE_EVENT,
+  STORE_SYNC_STATUS_EVENT, STORE_UNLOAD_EVENT,
 };
 pub use manager::ManagerExt;
 pub use migration::{Migration, MigrationContext, Migrator};
+pub use remote::{RemoteChange, RemoteChanges, SyncAdapter, SyncError, SyncFuture, SyncStatus};
 pub use serde_json::Value as Json;
 pub use store::{
-  JsonMarshaler, Marshaler, MarshalingError, PrettyJsonMarshaler, SaveStrategy, Store, StoreId,
+  read_blob_upload, AtomicOp, Batch, BatchOp, ChangeReceiver, JsonMarshaler, KeyChange, Marshaler,
+  MarshalingError, PatchMode, PrettyJsonMarshaler, SaveStrategy, Store, StoreChange, StoreId,
   StoreOptions, StoreState, WatcherId,
 };
 
@@ -35,6 +43,9 @@ pub use tauri_store_macros::Collection;
 #[cfg(feature = "plugin")]
 pub use plugin::{init, Builder};
 
+#[cfg(feature = "sync-http")]
+pub use remote::HttpSyncAdapter;
+
 #[cfg(feature = "marshaler-cbor")]
 pub use store::CborMarshaler;
 #[cfg(feature = "marshaler-ron")]
@@ -1,5 +1,6 @@
 use crate::collection::CollectionMarker;
 use crate::error::Result;
+use crate::io::write_file;
 use crate::store::{StoreId, StoreState};
 use crate::ManagerExt;
 use itertools::Itertools;
@@ -8,8 +9,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::{from_slice, to_vec};
 use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::Write;
+use std::fs;
 use std::path::PathBuf;
 use std::sync::{Mutex, OnceLock};
 use tauri::{AppHandle, Runtime};
@@ -117,11 +117,27 @@ impl Migrator {
     self.before_each = Some(Box::new(f));
   }
 
+  pub(crate) fn history(&self) -> &MigrationHistory {
+    &self.history
+  }
+
   pub(crate) fn read<R, C>(&mut self, app: &AppHandle<R>) -> Result<()>
   where
     R: Runtime,
     C: CollectionMarker,
   {
+    let collection = app.store_collection_with_marker::<C>();
+    if collection.layout().is_container() {
+      self.history = collection
+        .container
+        .lock()
+        .expect("container is poisoned")
+        .migrations
+        .clone();
+
+      return Ok(());
+    }
+
     let path = path::<R, C>(app);
     if let Ok(bytes) = fs::read(&path) {
       self.history = from_slice(&bytes)?;
@@ -131,6 +147,30 @@ impl Migrator {
   }
 
   fn write<R, C>(&self, app: &AppHandle<R>) -> Result<()>
+  where
+    R: Runtime,
+    C: CollectionMarker,
+  {
+    let collection = app.store_collection_with_marker::<C>();
+    if collection.read_only {
+      return Ok(());
+    }
+
+    let layout = collection
+      .layout
+      .read()
+      .expect("layout is poisoned");
+
+    if layout.is_container() {
+      collection.update_container(|container| {
+        container.migrations = self.history.clone();
+      })
+    } else {
+      self.write_file::<R, C>(app)
+    }
+  }
+
+  pub(crate) fn write_file<R, C>(&self, app: &AppHandle<R>) -> Result<()>
   where
     R: Runtime,
     C: CollectionMarker,
@@ -141,18 +181,7 @@ impl Migrator {
       .lock()
       .expect("migrator file lock is poisoned");
 
-    if let Some(parent) = path.parent() {
-      fs::create_dir_all(parent)?;
-    }
-
-    let bytes = to_vec(&self.history)?;
-    let mut file = File::create(path)?;
-    file.write_all(&bytes)?;
-    file.flush()?;
-
-    if cfg!(feature = "file-sync-all") {
-      file.sync_all()?;
-    }
+    write_file(&path, &to_vec(&self.history)?)?;
 
     drop(lock);
 
@@ -160,7 +189,7 @@ impl Migrator {
   }
 }
 
-fn path<R, C>(app: &AppHandle<R>) -> PathBuf
+pub(crate) fn path<R, C>(app: &AppHandle<R>) -> PathBuf
 where
   R: Runtime,
   C: CollectionMarker,
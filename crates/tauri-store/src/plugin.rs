@@ -26,18 +26,32 @@ where
     .invoke_handler(tauri::generate_handler![
       command::allow_save,
       command::allow_sync,
+      command::apply_op,
+      command::batch,
+      command::can_redo,
+      command::can_undo,
       command::clear_autosave,
+      command::clear_history,
       command::deny_save,
       command::deny_sync,
       command::destroy,
+      command::get_blob,
       command::get_default_save_strategy,
+      command::get_path,
+      command::get_persisted_store_ids,
       command::get_save_strategy,
       command::get_store_collection_path,
       command::get_store_ids,
       command::get_store_path,
       command::get_store_state,
       command::load,
+      command::merge_patch,
       command::patch,
+      command::put_blob,
+      command::redo,
+      command::remove_blob,
+      command::remove_path,
+      command::reset,
       command::save,
       command::save_all,
       command::save_all_now,
@@ -45,8 +59,10 @@ where
       command::save_some,
       command::save_some_now,
       command::set_autosave,
+      command::set_path,
       command::set_save_strategy,
       command::set_store_options,
+      command::undo,
       command::unload
     ])
     .build()
@@ -1,9 +1,13 @@
 pub use crate::{
-  BoxResult, Error, Handle, Json, JsonMarshaler, Marshaler, MarshalingError, OnLoadFn,
-  PrettyJsonMarshaler, Result, SaveStrategy, Store, StoreCollection, StoreId, StoreOptions,
-  StoreState, WatcherId,
+  AtomicOp, Batch, BoxResult, ChangeReceiver, CollectionLayout, Error, Handle, Json, JsonMarshaler,
+  KeyChange, LockPolicy, Marshaler, MarshalingError, OnLoadFn, PatchMode, PrettyJsonMarshaler,
+  RemoteChange, RemoteChanges, Result, SaveStrategy, Store, StoreChange, StoreCollection, StoreId,
+  StoreOptions, StoreState, SyncAdapter, SyncStatus, Transaction, WatcherId,
 };
 
+#[cfg(feature = "sync-http")]
+pub use crate::HttpSyncAdapter;
+
 #[cfg(feature = "marshaler-cbor")]
 pub use crate::CborMarshaler;
 #[cfg(feature = "marshaler-ron")]
@@ -0,0 +1,347 @@
+use super::queue::OfflineQueue;
+use super::{RemoteChange, SyncAdapter, SyncStatus};
+use crate::collection::CollectionMarker;
+use crate::error::{Error, Result};
+use crate::event::{emit, STORE_SYNC_STATUS_EVENT};
+use crate::io::write_file_atomic;
+use crate::manager::ManagerExt;
+use crate::store::{Delta, StoreId};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use std::{fs, mem};
+use tauri::async_runtime::spawn_blocking;
+use tauri::{AppHandle, Runtime};
+use tauri_store_utils::set_interval;
+use tokio::sync::Semaphore;
+use tokio::task::AbortHandle;
+
+/// Longest time to wait before retrying after a failure.
+const MAX_BACKOFF: Duration = Duration::from_secs(600);
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusPayload {
+  status: SyncStatus,
+  pending: usize,
+  error: Option<String>,
+}
+
+#[derive(Default)]
+struct Backoff {
+  failures: u32,
+  retry_at: Option<Instant>,
+}
+
+/// Drives a [`SyncAdapter`], pushing the queued changes and pulling the remote ones.
+pub(crate) struct RemoteSync {
+  adapter: Box<dyn SyncAdapter>,
+  interval: Duration,
+  cursor_path: PathBuf,
+  read_only: bool,
+  queue: Mutex<OfflineQueue>,
+  /// Changes pushed since the last pull, which the remote may send back to this device.
+  pushed: Mutex<Vec<RemoteChange>>,
+  cursor: Mutex<Option<String>>,
+  status: Mutex<SyncStatus>,
+  backoff: Mutex<Backoff>,
+  semaphore: Semaphore,
+  abort_handle: Mutex<Option<AbortHandle>>,
+}
+
+impl RemoteSync {
+  pub(crate) fn new(
+    dir: &Path,
+    adapter: Box<dyn SyncAdapter>,
+    interval: Duration,
+    read_only: bool,
+  ) -> Result<Self> {
+    let dir = dir.join("remote.tauristore");
+    let queue = OfflineQueue::load(dir.join("queue.jsonl"), !read_only)?;
+
+    let cursor_path = dir.join("cursor");
+    let cursor = match fs::read_to_string(&cursor_path) {
+      Ok(cursor) => Some(cursor),
+      Err(err) if err.kind() == ErrorKind::NotFound => None,
+      Err(err) => return Err(err.into()),
+    };
+
+    Ok(Self {
+      adapter,
+      interval,
+      cursor_path,
+      read_only,
+      queue: Mutex::new(queue),
+      pushed: Mutex::new(Vec::new()),
+      cursor: Mutex::new(cursor),
+      status: Mutex::new(SyncStatus::Idle),
+      backoff: Mutex::new(Backoff::default()),
+      semaphore: Semaphore::new(1),
+      abort_handle: Mutex::new(None),
+    })
+  }
+
+  pub(crate) fn start<R, C>(&self, app: &AppHandle<R>)
+  where
+    R: Runtime,
+    C: CollectionMarker,
+  {
+    let abort_handle = set_interval(app, self.interval, |app| async move {
+      let collection = app.store_collection_with_marker::<C>();
+      if let Some(remote) = &collection.remote_sync {
+        if remote.is_due() {
+          let _ = remote.sync::<R, C>(&app).await;
+        }
+      }
+    });
+
+    self
+      .abort_handle
+      .lock()
+      .expect("abort handle is poisoned")
+      .replace(abort_handle);
+  }
+
+  pub(crate) fn stop(&self) {
+    if let Some(handle) = self
+      .abort_handle
+      .lock()
+      .expect("abort handle is poisoned")
+      .take()
+    {
+      handle.abort();
+    }
+  }
+
+  pub(crate) fn status(&self) -> SyncStatus {
+    *self.status.lock().expect("status is poisoned")
+  }
+
+  /// Amount of changes waiting to be pushed.
+  pub(crate) fn pending(&self) -> usize {
+    self.queue().len()
+  }
+
+  /// Queues changes made locally to be pushed later.
+  ///
+  /// The caller is expected to hold the lock on the store.
+  pub(crate) fn enqueue(&self, id: &StoreId, deltas: &[Delta]) -> Result<()> {
+    if self.read_only {
+      return Ok(());
+    }
+
+    let changes = deltas
+      .iter()
+      .cloned()
+      .map(|delta| RemoteChange::from_delta(id, delta))
+      .collect();
+
+    self.queue().push(changes)
+  }
+
+  /// Pushes the queued changes, then pulls the remote ones.
+  ///
+  /// If another sync is already running, waits for it to finish first.
+  pub(crate) async fn sync<R, C>(&self, app: &AppHandle<R>) -> Result<()>
+  where
+    R: Runtime,
+    C: CollectionMarker,
+  {
+    let _permit = self
+      .semaphore
+      .acquire()
+      .await
+      .expect("semaphore will not be closed");
+
+    self.set_status(app, SyncStatus::Syncing, None)?;
+
+    match self.push_and_pull::<R, C>(app).await {
+      Ok(()) => {
+        *self.backoff() = Backoff::default();
+        self.set_status(app, SyncStatus::Idle, None)
+      }
+      Err(err) => {
+        let mut backoff = self.backoff();
+        let delay = self
+          .interval
+          .saturating_mul(2_u32.saturating_pow(backoff.failures))
+          .min(MAX_BACKOFF);
+
+        backoff.failures = backoff.failures.saturating_add(1);
+        backoff.retry_at = Some(Instant::now() + delay);
+        drop(backoff);
+
+        self.set_status(app, SyncStatus::Offline, Some(err.to_string()))?;
+        Err(err)
+      }
+    }
+  }
+
+  async fn push_and_pull<R, C>(&self, app: &AppHandle<R>) -> Result<()>
+  where
+    R: Runtime,
+    C: CollectionMarker,
+  {
+    if !self.read_only {
+      let changes = self.queue().changes().to_vec();
+      if !changes.is_empty() {
+        self
+          .adapter
+          .push(&changes)
+          .await
+          .map_err(Error::FailedToSync)?;
+
+        self.queue().drain(changes.len())?;
+        self.pushed().extend(changes);
+      }
+    }
+
+    let cursor = self
+      .cursor
+      .lock()
+      .expect("cursor is poisoned")
+      .clone();
+
+    let pulled = self
+      .adapter
+      .pull(cursor.as_deref())
+      .await
+      .map_err(Error::FailedToSync)?;
+
+    // Locking the stores would block the async runtime.
+    let handle = app.clone();
+    spawn_blocking(move || {
+      let collection = handle.store_collection_with_marker::<C>();
+      match &collection.remote_sync {
+        Some(remote) => remote.apply::<R, C>(&handle, pulled.changes),
+        None => Ok(()),
+      }
+    })
+    .await??;
+
+    if let Some(cursor) = pulled.cursor {
+      if !self.read_only {
+        write_file_atomic(&self.cursor_path, cursor.as_bytes())?;
+      }
+
+      *self.cursor.lock().expect("cursor is poisoned") = Some(cursor);
+    }
+
+    Ok(())
+  }
+
+  /// Applies the pulled changes to the local stores.
+  ///
+  /// Changes to keys that still have pending local changes are ignored,
+  /// as those will overwrite them on the remote once they are pushed.
+  fn apply<R, C>(&self, app: &AppHandle<R>, changes: Vec<RemoteChange>) -> Result<()>
+  where
+    R: Runtime,
+    C: CollectionMarker,
+  {
+    let mut stores: Vec<(StoreId, Vec<Delta>)> = Vec::new();
+    for change in self.skip_echoes(changes) {
+      let (id, delta) = change.into_delta();
+      match stores.iter_mut().find(|(it, _)| *it == id) {
+        Some((_, deltas)) => deltas.push(delta),
+        None => stores.push((id, vec![delta])),
+      }
+    }
+
+    let collection = app.store_collection_with_marker::<C>();
+    for (id, deltas) in stores {
+      collection.get_resource(&id)?.locked(|store| {
+        let deltas = {
+          let queue = self.queue();
+          deltas
+            .into_iter()
+            .filter(|delta| !queue.contains(id.as_ref(), delta.key()))
+            .collect::<Vec<_>>()
+        };
+
+        if deltas.is_empty() {
+          Ok(())
+        } else {
+          store.apply_remote_deltas(deltas)
+        }
+      })?;
+    }
+
+    Ok(())
+  }
+
+  /// Removes the changes that this device pushed itself, as the stores already have them.
+  ///
+  /// Only the last change to each key matters, so a key is skipped entirely
+  /// if its last change came from this device.
+  fn skip_echoes(&self, changes: Vec<RemoteChange>) -> Vec<RemoteChange> {
+    let mut pushed = mem::take(&mut *self.pushed());
+    let changes = changes
+      .into_iter()
+      .map(|change| {
+        let is_echo = pushed
+          .iter()
+          .position(|it| *it == change)
+          .map(|index| pushed.remove(index))
+          .is_some();
+
+        (change, is_echo)
+      })
+      .collect::<Vec<_>>();
+
+    let mut seen = HashSet::new();
+    let mut changes = changes
+      .into_iter()
+      .rev()
+      .filter(|(change, _)| seen.insert((change.store().clone(), change.key().to_owned())))
+      .filter_map(|(change, is_echo)| (!is_echo).then_some(change))
+      .collect::<Vec<_>>();
+
+    changes.reverse();
+    changes
+  }
+
+  /// Whether the next periodic sync should happen, given the previous failures.
+  fn is_due(&self) -> bool {
+    self
+      .backoff()
+      .retry_at
+      .map_or(true, |retry_at| Instant::now() >= retry_at)
+  }
+
+  fn set_status<R>(
+    &self,
+    app: &AppHandle<R>,
+    status: SyncStatus,
+    error: Option<String>,
+  ) -> Result<()>
+  where
+    R: Runtime,
+  {
+    *self.status.lock().expect("status is poisoned") = status;
+    let payload = StatusPayload {
+      status,
+      pending: self.pending(),
+      error,
+    };
+    emit(app, STORE_SYNC_STATUS_EVENT, &payload, None::<&str>)
+  }
+
+  fn queue(&self) -> MutexGuard<'_, OfflineQueue> {
+    self.queue.lock().expect("queue is poisoned")
+  }
+
+  fn pushed(&self) -> MutexGuard<'_, Vec<RemoteChange>> {
+    self
+      .pushed
+      .lock()
+      .expect("pushed changes are poisoned")
+  }
+
+  fn backoff(&self) -> MutexGuard<'_, Backoff> {
+    self.backoff.lock().expect("backoff is poisoned")
+  }
+}
@@ -0,0 +1,85 @@
+use super::{RemoteChange, RemoteChanges, SyncAdapter, SyncFuture};
+use crate::error::{Error, Result};
+use reqwest::{Client, Url};
+use serde::Serialize;
+
+/// Synchronizes the stores with an HTTP server.
+///
+/// Changes are pushed with a `POST` request to `{url}/push`, whose body is a JSON object
+/// with a `changes` array. They are pulled with a `GET` request to `{url}/pull`, passing
+/// the cursor as a query parameter, to which the server should respond with a
+/// [`RemoteChanges`] object. Any status other than a success is treated as an error.
+///
+/// No TLS backend is enabled by default. To use HTTPS, enable one of the TLS features
+/// of `reqwest` in your app and pass your own client to [`HttpSyncAdapter::with_client`].
+pub struct HttpSyncAdapter {
+  client: Client,
+  url: Url,
+}
+
+#[derive(Serialize)]
+struct PushBody<'a> {
+  changes: &'a [RemoteChange],
+}
+
+impl HttpSyncAdapter {
+  /// Creates an adapter for the server at the given base URL, using a default client.
+  ///
+  /// Fails if the URL can't be parsed.
+  pub fn new(url: &str) -> Result<Self> {
+    Self::with_client(Client::new(), url)
+  }
+
+  /// Uses a preconfigured client, such as one with default headers for authentication.
+  pub fn with_client(client: Client, url: &str) -> Result<Self> {
+    let url = Url::parse(url).map_err(|err| Error::FailedToSync(Box::new(err)))?;
+    Ok(Self { client, url })
+  }
+
+  fn endpoint(&self, path: &str) -> Url {
+    let mut url = self.url.clone();
+    if let Ok(mut segments) = url.path_segments_mut() {
+      segments.pop_if_empty().push(path);
+    }
+
+    url
+  }
+}
+
+impl SyncAdapter for HttpSyncAdapter {
+  fn push<'a>(&'a self, changes: &'a [RemoteChange]) -> SyncFuture<'a, ()> {
+    Box::pin(async move {
+      self
+        .client
+        .post(self.endpoint("push"))
+        .json(&PushBody { changes })
+        .send()
+        .await?
+        .error_for_status()?;
+
+      Ok(())
+    })
+  }
+
+  fn pull<'a>(&'a self, cursor: Option<&'a str>) -> SyncFuture<'a, RemoteChanges> {
+    Box::pin(async move {
+      let mut url = self.endpoint("pull");
+      if let Some(cursor) = cursor {
+        url
+          .query_pairs_mut()
+          .append_pair("cursor", cursor);
+      }
+
+      let changes = self
+        .client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+      Ok(changes)
+    })
+  }
+}
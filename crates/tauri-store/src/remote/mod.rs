@@ -0,0 +1,112 @@
+mod driver;
+mod queue;
+
+#[cfg(feature = "sync-http")]
+mod http;
+
+use crate::store::{Delta, StoreId};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+
+pub(crate) use driver::RemoteSync;
+
+#[cfg(feature = "sync-http")]
+pub use http::HttpSyncAdapter;
+
+/// Generic sync error.
+pub type SyncError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Future returned by a [`SyncAdapter`].
+pub type SyncFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, SyncError>> + Send + 'a>>;
+
+/// Describes how the stores should be synchronized with a remote, such as a backend server.
+///
+/// The [`StoreCollection`](crate::StoreCollection) periodically pushes the changes
+/// made locally, then pulls the changes made elsewhere since the last time it did so.
+pub trait SyncAdapter: Send + Sync {
+  /// Sends the local changes to the remote, in the order in which they were made.
+  ///
+  /// If this fails, the same changes will be pushed again later.
+  fn push<'a>(&'a self, changes: &'a [RemoteChange]) -> SyncFuture<'a, ()>;
+
+  /// Fetches the changes made since the cursor returned by the previous pull.
+  ///
+  /// The cursor is `None` when pulling for the first time.
+  fn pull<'a>(&'a self, cursor: Option<&'a str>) -> SyncFuture<'a, RemoteChanges>;
+}
+
+/// A single change to a key of a store.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum RemoteChange {
+  Set {
+    store: StoreId,
+    key: String,
+    value: Value,
+  },
+  Remove {
+    store: StoreId,
+    key: String,
+  },
+}
+
+impl RemoteChange {
+  pub(crate) fn from_delta(store: &StoreId, delta: Delta) -> Self {
+    let store = store.clone();
+    match delta {
+      Delta::Set { key, value } => Self::Set { store, key, value },
+      Delta::Remove { key } => Self::Remove { store, key },
+    }
+  }
+
+  pub(crate) fn into_delta(self) -> (StoreId, Delta) {
+    match self {
+      Self::Set { store, key, value } => (store, Delta::Set { key, value }),
+      Self::Remove { store, key } => (store, Delta::Remove { key }),
+    }
+  }
+
+  /// Store that was changed.
+  pub fn store(&self) -> &StoreId {
+    match self {
+      Self::Set { store, .. } | Self::Remove { store, .. } => store,
+    }
+  }
+
+  /// Key that was changed.
+  pub fn key(&self) -> &str {
+    match self {
+      Self::Set { key, .. } | Self::Remove { key, .. } => key,
+    }
+  }
+}
+
+/// Changes pulled from the remote.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteChanges {
+  /// Changes made since the previous pull, in the order in which they should be applied.
+  #[serde(default)]
+  pub changes: Vec<RemoteChange>,
+  /// Cursor to be used in the next pull.
+  ///
+  /// If `None`, the previous cursor is kept.
+  #[serde(default)]
+  pub cursor: Option<String>,
+}
+
+/// Status of the synchronization with the remote.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncStatus {
+  /// The last attempt succeeded, or there was none yet.
+  #[default]
+  Idle,
+  /// Changes are being pushed or pulled.
+  Syncing,
+  /// The last attempt failed, so the changes are queued until it succeeds.
+  Offline,
+}
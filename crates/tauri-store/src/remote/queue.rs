@@ -0,0 +1,121 @@
+use super::RemoteChange;
+use crate::error::Result;
+use crate::io::{remove_file, write_file_atomic};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+
+/// Changes that were made locally but not yet pushed to the remote.
+///
+/// The queue is persisted as JSON lines, so the changes survive a restart of the app.
+pub(crate) struct OfflineQueue {
+  path: PathBuf,
+  changes: Vec<RemoteChange>,
+  persist: bool,
+}
+
+impl OfflineQueue {
+  /// Reads the queue from the disk.
+  ///
+  /// If the last change is incomplete, which happens when the app is killed mid-write, it is ignored.
+  pub(crate) fn load(path: PathBuf, persist: bool) -> Result<Self> {
+    let bytes = match fs::read(&path) {
+      Ok(bytes) => bytes,
+      Err(err) if err.kind() == ErrorKind::NotFound => Vec::new(),
+      Err(err) => return Err(err.into()),
+    };
+
+    let mut changes = Vec::new();
+    for line in bytes.split(|byte| *byte == b'\n') {
+      if let Ok(change) = serde_json::from_slice(line) {
+        changes.push(change);
+      }
+    }
+
+    let mut queue = Self { path, changes, persist };
+
+    // Rewrite the file without the incomplete change.
+    if !bytes.is_empty() && !bytes.ends_with(b"\n") {
+      queue.write()?;
+    }
+
+    Ok(queue)
+  }
+
+  /// Changes currently in the queue.
+  pub(crate) fn changes(&self) -> &[RemoteChange] {
+    &self.changes
+  }
+
+  pub(crate) fn len(&self) -> usize {
+    self.changes.len()
+  }
+
+  /// Whether there is a pending change to the key.
+  pub(crate) fn contains(&self, store: &str, key: &str) -> bool {
+    self
+      .changes
+      .iter()
+      .any(|change| change.store().as_ref() == store && change.key() == key)
+  }
+
+  /// Appends the changes to the queue.
+  pub(crate) fn push(&mut self, changes: Vec<RemoteChange>) -> Result<()> {
+    if changes.is_empty() {
+      return Ok(());
+    }
+
+    if self.persist {
+      let mut bytes = Vec::new();
+      for change in &changes {
+        serde_json::to_writer(&mut bytes, change)?;
+        bytes.push(b'\n');
+      }
+
+      if let Some(parent) = self.path.parent() {
+        fs::create_dir_all(parent)?;
+      }
+
+      let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&self.path)?;
+
+      file.write_all(&bytes)?;
+      file.flush()?;
+
+      if cfg!(feature = "file-sync-all") {
+        file.sync_all()?;
+      }
+    }
+
+    self.changes.extend(changes);
+
+    Ok(())
+  }
+
+  /// Removes the first `len` changes, which have been pushed to the remote.
+  pub(crate) fn drain(&mut self, len: usize) -> Result<()> {
+    let len = len.min(self.changes.len());
+    self.changes.drain(..len);
+    self.write()
+  }
+
+  fn write(&mut self) -> Result<()> {
+    if !self.persist {
+      return Ok(());
+    }
+
+    if self.changes.is_empty() {
+      return remove_file(&self.path);
+    }
+
+    let mut bytes = Vec::new();
+    for change in &self.changes {
+      serde_json::to_writer(&mut bytes, change)?;
+      bytes.push(b'\n');
+    }
+
+    write_file_atomic(&self.path, &bytes)
+  }
+}
@@ -0,0 +1,93 @@
+use super::{Delta, StoreState};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Changes to be applied to a store at once.
+///
+/// See [`Store::batch`](crate::Store::batch).
+pub struct Batch<'a> {
+  state: &'a StoreState,
+  deltas: Vec<Delta>,
+}
+
+impl<'a> Batch<'a> {
+  pub(crate) fn new(state: &'a StoreState) -> Self {
+    Self { state, deltas: Vec::new() }
+  }
+
+  /// Gets a reference to a raw value, taking into account the changes already made in the batch.
+  pub fn get_raw(&self, key: impl AsRef<str>) -> Option<&Value> {
+    let key = key.as_ref();
+    match self
+      .deltas
+      .iter()
+      .rev()
+      .find(|it| it.key() == key)
+    {
+      Some(delta) => delta.value(),
+      None => self.state.get_raw(key),
+    }
+  }
+
+  /// Sets a key-value pair.
+  pub fn set(&mut self, key: impl AsRef<str>, value: impl Into<Value>) -> &mut Self {
+    self
+      .deltas
+      .push(Delta::set(key.as_ref(), value.into()));
+    self
+  }
+
+  /// Removes a key.
+  pub fn remove(&mut self, key: impl AsRef<str>) -> &mut Self {
+    self.deltas.push(Delta::remove(key.as_ref()));
+    self
+  }
+
+  /// Sets every key-value pair of the state.
+  pub fn patch(&mut self, state: impl Into<StoreState>) -> &mut Self {
+    let deltas = state
+      .into()
+      .into_inner()
+      .into_iter()
+      .map(|(key, value)| Delta::set(key, value));
+
+    self.deltas.extend(deltas);
+    self
+  }
+
+  /// Amount of changes in the batch.
+  pub fn len(&self) -> usize {
+    self.deltas.len()
+  }
+
+  /// Whether the batch has no changes.
+  pub fn is_empty(&self) -> bool {
+    self.deltas.is_empty()
+  }
+
+  pub(crate) fn into_deltas(self) -> Vec<Delta> {
+    self.deltas
+  }
+}
+
+impl Extend<BatchOp> for Batch<'_> {
+  fn extend<I>(&mut self, ops: I)
+  where
+    I: IntoIterator<Item = BatchOp>,
+  {
+    let deltas = ops.into_iter().map(|op| match op {
+      BatchOp::Set { key, value } => Delta::set(key, value),
+      BatchOp::Remove { key } => Delta::remove(key),
+    });
+
+    self.deltas.extend(deltas);
+  }
+}
+
+/// A change sent by the frontend as part of a batch.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum BatchOp {
+  Set { key: String, value: Value },
+  Remove { key: String },
+}
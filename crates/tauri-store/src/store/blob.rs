@@ -0,0 +1,157 @@
+use super::shard::decode;
+use super::StoreId;
+use crate::error::Result;
+use crate::io::{remove_file, write_file_atomic};
+use crate::io_err;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use tauri::ipc::{InvokeBody, Request};
+
+/// Key of the object used to reference a blob in the store state.
+pub(crate) const BLOB_REF_KEY: &str = "$blob";
+
+/// Header with the percent-encoded id of the store a blob is uploaded to.
+const UPLOAD_STORE_HEADER: &str = "x-store-id";
+/// Header with the percent-encoded key a blob is uploaded to.
+const UPLOAD_KEY_HEADER: &str = "x-store-key";
+
+/// Reads a blob uploaded by the frontend, returning the store and the key it belongs to.
+///
+/// The bytes are sent as the raw body of the request, so they cross the IPC boundary as they are.
+#[doc(hidden)]
+pub fn read_blob_upload<'a>(request: &'a Request<'_>) -> Result<(StoreId, String, &'a [u8])> {
+  let InvokeBody::Raw(bytes) = request.body() else {
+    return io_err!(InvalidInput, "blob must be sent as raw bytes");
+  };
+
+  let id = upload_header(request, UPLOAD_STORE_HEADER)?;
+  let key = upload_header(request, UPLOAD_KEY_HEADER)?;
+
+  Ok((StoreId::from(id), key, bytes))
+}
+
+fn upload_header(request: &Request<'_>, name: &str) -> Result<String> {
+  let value = request
+    .headers()
+    .get(name)
+    .and_then(|value| value.to_str().ok())
+    .and_then(decode);
+
+  match value {
+    Some(value) => Ok(value),
+    None => io_err!(InvalidInput, "missing or invalid header: {name}"),
+  }
+}
+
+/// Writes the bytes to the directory, returning their hash.
+///
+/// Blobs are content-addressed, so writing the same bytes twice is a no-op.
+pub(crate) fn write(dir: &Path, bytes: &[u8]) -> Result<String> {
+  let hash = hash(bytes);
+  let path = dir.join(&hash);
+  if !path.is_file() {
+    write_file_atomic(&path, bytes)?;
+  }
+
+  Ok(hash)
+}
+
+/// Reads the blob with the given hash.
+pub(crate) fn read(dir: &Path, hash: &str) -> Result<Option<Vec<u8>>> {
+  if !is_hash(hash) {
+    return Ok(None);
+  }
+
+  match fs::read(dir.join(hash)) {
+    Ok(bytes) => Ok(Some(bytes)),
+    Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+    Err(err) => Err(err.into()),
+  }
+}
+
+/// Creates the value that references a blob in the store state.
+pub(crate) fn to_ref(hash: &str) -> Value {
+  json!({ BLOB_REF_KEY: hash })
+}
+
+/// Gets the hash of the blob referenced by the value, if it is a reference.
+pub(crate) fn from_ref(value: &Value) -> Option<&str> {
+  let object = value.as_object()?;
+  if object.len() == 1 {
+    object
+      .get(BLOB_REF_KEY)
+      .and_then(Value::as_str)
+      .filter(|hash| is_hash(hash))
+  } else {
+    None
+  }
+}
+
+/// Adds the hashes of every blob referenced anywhere in the value, however deeply nested.
+pub(crate) fn collect_refs(value: &Value, refs: &mut HashSet<String>) {
+  if let Some(hash) = from_ref(value) {
+    refs.insert(hash.to_owned());
+    return;
+  }
+
+  match value {
+    Value::Array(values) => {
+      for value in values {
+        collect_refs(value, refs);
+      }
+    }
+    Value::Object(object) => {
+      for value in object.values() {
+        collect_refs(value, refs);
+      }
+    }
+    _ => {}
+  }
+}
+
+/// Removes every blob in the directory that is not referenced,
+/// returning how many of them were removed.
+pub(crate) fn collect_garbage(dir: &Path, referenced: &HashSet<String>) -> Result<usize> {
+  let entries = match fs::read_dir(dir) {
+    Ok(entries) => entries,
+    Err(err) if err.kind() == ErrorKind::NotFound => return Ok(0),
+    Err(err) => return Err(err.into()),
+  };
+
+  let mut removed = 0;
+  for entry in entries {
+    let path = entry?.path();
+    let is_garbage = path
+      .file_name()
+      .and_then(|name| name.to_str())
+      .is_some_and(|name| is_hash(name) && !referenced.contains(name));
+
+    if is_garbage && path.is_file() {
+      remove_file(&path)?;
+      removed += 1;
+    }
+  }
+
+  Ok(removed)
+}
+
+fn hash(bytes: &[u8]) -> String {
+  Sha256::digest(bytes)
+    .iter()
+    .fold(String::with_capacity(64), |mut hash, byte| {
+      let _ = write!(hash, "{byte:02x}");
+      hash
+    })
+}
+
+fn is_hash(value: &str) -> bool {
+  value.len() == 64
+    && value
+      .bytes()
+      .all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'))
+}
@@ -0,0 +1,385 @@
+use super::StoreState;
+use crate::error::Result;
+use crate::io::{remove_file, write_file_atomic};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How long a removal is remembered, in milliseconds.
+///
+/// A replica that has been offline for longer than this may bring a removed path back.
+const TOMBSTONE_TTL: u64 = 30 * 24 * 60 * 60 * 1000;
+
+/// Operation recorded for a path.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+enum Op {
+  Set { value: Value },
+  Remove,
+}
+
+/// Last write to a path, identified by a hybrid logical clock and the replica that made it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+  path: Vec<String>,
+  ts: u64,
+  replica: String,
+  #[serde(flatten)]
+  op: Op,
+  /// Clock of this replica when it got the entry, which is only kept in its own file.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  received: Option<u64>,
+}
+
+impl Entry {
+  /// Compares the versions of two entries. Ties are broken by the replica id,
+  /// so every replica picks the same winner regardless of the order of the updates.
+  fn cmp_version(&self, other: &Self) -> CmpOrdering {
+    self
+      .ts
+      .cmp(&other.ts)
+      .then_with(|| self.replica.cmp(&other.replica))
+  }
+
+  /// Whether the entry is a removal old enough to be forgotten.
+  fn is_expired_tombstone(&self, now: u64) -> bool {
+    matches!(self.op, Op::Remove) && now.saturating_sub(self.ts) > TOMBSTONE_TTL
+  }
+}
+
+/// Set of entries exchanged between replicas.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Update {
+  entries: Vec<Entry>,
+}
+
+/// Contents of the file where a replica keeps its entries.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Sidecar {
+  replica: Option<String>,
+  #[serde(default)]
+  entries: Vec<Entry>,
+}
+
+/// Last-writer-wins map of the store state, keyed by the path of each leaf value.
+///
+/// Objects are split into their keys, while any other value, including arrays and empty objects,
+/// is a leaf. Removed paths are kept as tombstones for a while, so a removal is not undone by an
+/// older update. The entries are persisted to a sidecar file next to the store, along with the id
+/// of the replica, which must stay the same for as long as its entries exist.
+pub(crate) struct Crdt {
+  path: PathBuf,
+  replica: String,
+  clock: u64,
+  entries: HashMap<Vec<String>, Entry>,
+  dirty: AtomicBool,
+}
+
+impl Crdt {
+  /// Reads the entries from the disk.
+  pub(crate) fn load(path: PathBuf) -> Result<Self> {
+    let sidecar = match fs::read(&path) {
+      Ok(bytes) => serde_json::from_slice::<Sidecar>(&bytes)?,
+      Err(err) if err.kind() == ErrorKind::NotFound => Sidecar::default(),
+      Err(err) => return Err(err.into()),
+    };
+
+    let mut crdt = Self {
+      path,
+      replica: sidecar.replica.unwrap_or_else(replica_id),
+      clock: 0,
+      entries: HashMap::with_capacity(sidecar.entries.len()),
+      dirty: AtomicBool::new(false),
+    };
+
+    for entry in sidecar.entries {
+      let received = entry.received.unwrap_or(entry.ts);
+      crdt.clock = crdt.clock.max(entry.ts).max(received);
+      crdt.entries.insert(entry.path.clone(), entry);
+    }
+
+    crdt.compact();
+
+    Ok(crdt)
+  }
+
+  /// Highest timestamp seen by this replica.
+  pub(crate) fn clock(&self) -> u64 {
+    self.clock
+  }
+
+  /// Records a local change to the key.
+  pub(crate) fn record(&mut self, key: &str, old: Option<&Value>, new: Option<&Value>) {
+    let ts = self.tick();
+    self.diff(vec![key.to_owned()], old, new, ts);
+  }
+
+  /// Records whatever differs between the entries and the state.
+  ///
+  /// This catches changes that were saved to the store but not to the entries,
+  /// such as those made by migrations or before the store became a CRDT.
+  pub(crate) fn track(&mut self, state: &StoreState) {
+    let current = self.materialize();
+    let keys = current
+      .keys()
+      .chain(state.keys())
+      .cloned()
+      .collect::<HashSet<_>>();
+
+    for key in keys {
+      let old = current.get(&key);
+      let new = state.get_raw(&key);
+      if old != new {
+        self.record(&key, old, new);
+      }
+    }
+  }
+
+  /// Serializes the entries that this replica got after its clock was at `since`, or all of them.
+  ///
+  /// This includes entries merged from other replicas, even if they were made long before.
+  pub(crate) fn export(&self, since: Option<u64>) -> Result<Vec<u8>> {
+    let mut entries = self
+      .entries
+      .values()
+      .filter(|entry| since.map_or(true, |since| entry.received.unwrap_or(entry.ts) > since))
+      .cloned()
+      .map(|entry| Entry { received: None, ..entry })
+      .collect::<Vec<_>>();
+
+    entries.sort_by(|a, b| a.cmp_version(b).then_with(|| a.path.cmp(&b.path)));
+
+    Ok(serde_json::to_vec(&Update { entries })?)
+  }
+
+  /// Merges an update from another replica, returning whether anything changed.
+  pub(crate) fn import(&mut self, bytes: &[u8]) -> Result<bool> {
+    let update = serde_json::from_slice::<Update>(bytes)?;
+    let changed = self.merge(update);
+    if changed {
+      self.compact();
+    }
+
+    Ok(changed)
+  }
+
+  /// Builds the state from the entries.
+  pub(crate) fn materialize(&self) -> Map<String, Value> {
+    let mut entries = self.entries.values().collect::<Vec<_>>();
+
+    // Parents must be applied before their children, as setting a path replaces its descendants.
+    entries.sort_by(|a, b| {
+      a.cmp_version(b)
+        .then_with(|| a.path.len().cmp(&b.path.len()))
+        .then_with(|| a.path.cmp(&b.path))
+    });
+
+    let mut root = Map::new();
+    for entry in entries {
+      match &entry.op {
+        Op::Set { value } => set_path(&mut root, &entry.path, value.clone()),
+        Op::Remove => remove_path(&mut root, &entry.path),
+      }
+    }
+
+    root
+  }
+
+  /// Saves the entries to the disk if they changed since the last write.
+  pub(crate) fn write(&self) -> Result<()> {
+    if self.dirty.swap(false, Ordering::AcqRel) {
+      let sidecar = Sidecar {
+        replica: Some(self.replica.clone()),
+        entries: self.entries.values().cloned().collect(),
+      };
+
+      if let Err(err) = write_file_atomic(&self.path, &serde_json::to_vec(&sidecar)?) {
+        self.dirty.store(true, Ordering::Release);
+        return Err(err);
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Discards the entries and deletes their file.
+  pub(crate) fn remove(&mut self) -> Result<()> {
+    self.entries.clear();
+    self.dirty.store(false, Ordering::Release);
+    remove_file(&self.path)
+  }
+
+  /// Forgets the entries that can no longer affect the state: removals that are old enough,
+  /// and anything replaced by a newer entry for one of its ancestors.
+  fn compact(&mut self) {
+    let now = now();
+    let len = self.entries.len();
+    let superseded = self
+      .entries
+      .values()
+      .filter(|entry| {
+        entry.is_expired_tombstone(now)
+          || (1..entry.path.len()).any(|depth| {
+            self
+              .entries
+              .get(&entry.path[..depth])
+              .is_some_and(|ancestor| ancestor.cmp_version(entry).is_gt())
+          })
+      })
+      .map(|entry| entry.path.clone())
+      .collect::<Vec<_>>();
+
+    for path in superseded {
+      self.entries.remove(&path);
+    }
+
+    if self.entries.len() < len {
+      self.dirty.store(true, Ordering::Release);
+    }
+  }
+
+  fn merge(&mut self, update: Update) -> bool {
+    let now = now();
+    let mut received = None;
+    for mut entry in update.entries {
+      self.clock = self.clock.max(entry.ts);
+      let is_newer = self
+        .entries
+        .get(&entry.path)
+        .map_or(true, |current| entry.cmp_version(current).is_gt());
+
+      if !is_newer {
+        continue;
+      }
+
+      entry.received = Some(*received.get_or_insert_with(|| self.tick()));
+
+      // An expired removal is forgotten right away, along with the entry it replaces.
+      if entry.is_expired_tombstone(now) {
+        self.entries.remove(&entry.path);
+      } else {
+        self.entries.insert(entry.path.clone(), entry);
+      }
+    }
+
+    let changed = received.is_some();
+    if changed {
+      self.dirty.store(true, Ordering::Release);
+    }
+
+    changed
+  }
+
+  /// Advances the clock, which never goes backwards even if the system time does.
+  fn tick(&mut self) -> u64 {
+    self.clock = now().max(self.clock.saturating_add(1));
+    self.clock
+  }
+
+  fn diff(&mut self, path: Vec<String>, old: Option<&Value>, new: Option<&Value>, ts: u64) {
+    match new {
+      None => {
+        if old.is_some() {
+          self.insert(path, Op::Remove, ts);
+        }
+      }
+      Some(Value::Object(new)) if !new.is_empty() => {
+        let old = old.and_then(Value::as_object);
+        for (key, value) in new {
+          let mut child = path.clone();
+          child.push(key.clone());
+          self.diff(child, old.and_then(|it| it.get(key)), Some(value), ts);
+        }
+
+        if let Some(old) = old {
+          for (key, value) in old {
+            if !new.contains_key(key) {
+              let mut child = path.clone();
+              child.push(key.clone());
+              self.diff(child, Some(value), None, ts);
+            }
+          }
+        }
+      }
+      Some(new) => {
+        if old != Some(new) {
+          let value = new.clone();
+          self.insert(path, Op::Set { value }, ts);
+        }
+      }
+    }
+  }
+
+  fn insert(&mut self, path: Vec<String>, op: Op, ts: u64) {
+    let replica = self.replica.clone();
+    let entry = Entry {
+      path: path.clone(),
+      ts,
+      replica,
+      op,
+      received: Some(ts),
+    };
+    self.entries.insert(path, entry);
+    self.dirty.store(true, Ordering::Release);
+  }
+}
+
+fn set_path(root: &mut Map<String, Value>, path: &[String], value: Value) {
+  let Some((last, parents)) = path.split_last() else {
+    return;
+  };
+
+  let mut map = root;
+  for key in parents {
+    let child = map
+      .entry(key.clone())
+      .or_insert_with(|| Value::Object(Map::new()));
+
+    if !child.is_object() {
+      *child = Value::Object(Map::new());
+    }
+
+    map = child.as_object_mut().expect("value is an object");
+  }
+
+  map.insert(last.clone(), value);
+}
+
+fn remove_path(root: &mut Map<String, Value>, path: &[String]) {
+  let Some((last, parents)) = path.split_last() else {
+    return;
+  };
+
+  let mut map = root;
+  for key in parents {
+    match map.get_mut(key) {
+      Some(Value::Object(child)) => map = child,
+      _ => return,
+    }
+  }
+
+  map.remove(last);
+}
+
+fn now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |it| u64::try_from(it.as_millis()).unwrap_or(u64::MAX))
+}
+
+/// Identifies this replica. It only needs to be unique among the replicas of a store.
+fn replica_id() -> String {
+  let nanos = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |it| it.as_nanos());
+
+  format!("{:x}-{nanos:x}", process::id())
+}
@@ -0,0 +1,108 @@
+use crate::error::Result;
+use crate::io::{remove_file, write_file_atomic};
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// When each key of a store expires, in milliseconds since the Unix epoch.
+///
+/// The deadlines are persisted to a sidecar file next to the store,
+/// so keys set with a time-to-live still expire after a restart.
+pub(crate) struct Expiry {
+  path: PathBuf,
+  deadlines: HashMap<String, u64>,
+  dirty: AtomicBool,
+}
+
+impl Expiry {
+  /// Reads the deadlines from the disk.
+  pub(crate) fn load(path: PathBuf) -> Result<Self> {
+    let deadlines = match fs::read(&path) {
+      Ok(bytes) => serde_json::from_slice(&bytes)?,
+      Err(err) if err.kind() == ErrorKind::NotFound => HashMap::new(),
+      Err(err) => return Err(err.into()),
+    };
+
+    Ok(Self {
+      path,
+      deadlines,
+      dirty: AtomicBool::new(false),
+    })
+  }
+
+  /// Sets the key to expire after the given duration.
+  pub(crate) fn set(&mut self, key: &str, ttl: Duration) {
+    let ttl = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX);
+    let deadline = now().saturating_add(ttl);
+    self.deadlines.insert(key.to_owned(), deadline);
+    self.dirty.store(true, Ordering::Release);
+  }
+
+  /// Makes the key persistent again.
+  pub(crate) fn clear(&mut self, key: &str) {
+    if self.deadlines.remove(key).is_some() {
+      self.dirty.store(true, Ordering::Release);
+    }
+  }
+
+  /// Time left until the key expires, if it has a deadline.
+  pub(crate) fn ttl(&self, key: &str) -> Option<Duration> {
+    let deadline = *self.deadlines.get(key)?;
+    Some(Duration::from_millis(deadline.saturating_sub(now())))
+  }
+
+  /// Keys whose deadline has passed.
+  pub(crate) fn expired(&self) -> Vec<String> {
+    if self.deadlines.is_empty() {
+      return Vec::new();
+    }
+
+    let now = now();
+    self
+      .deadlines
+      .iter()
+      .filter(|(_, deadline)| **deadline <= now)
+      .map(|(key, _)| key.clone())
+      .collect()
+  }
+
+  /// Saves the deadlines to the disk if they changed since the last write.
+  pub(crate) fn write(&self) -> Result<()> {
+    if self.dirty.swap(false, Ordering::AcqRel) {
+      let result = if self.deadlines.is_empty() {
+        remove_file(&self.path)
+      } else {
+        serde_json::to_vec(&self.deadlines)
+          .map_err(Into::into)
+          .and_then(|bytes| write_file_atomic(&self.path, &bytes))
+      };
+
+      if result.is_err() {
+        self.dirty.store(true, Ordering::Release);
+      }
+
+      return result;
+    }
+
+    Ok(())
+  }
+
+  /// Discards the deadlines and deletes their file.
+  pub(crate) fn remove(&mut self) -> Result<()> {
+    self.deadlines.clear();
+    self.dirty.store(false, Ordering::Release);
+    remove_file(&self.path)
+  }
+}
+
+fn now() -> u64 {
+  let millis = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_millis();
+
+  u64::try_from(millis).unwrap_or(u64::MAX)
+}
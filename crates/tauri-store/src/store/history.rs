@@ -0,0 +1,92 @@
+use super::{Delta, KeyChange};
+use std::collections::VecDeque;
+
+/// Bounded undo and redo stacks of a store.
+///
+/// Each entry holds the inverse of a change, so applying it reverts the change.
+/// As a batch is a single change, it's also undone at once.
+#[derive(Debug, Default)]
+pub(super) struct History {
+  depth: usize,
+  undo: VecDeque<Vec<Delta>>,
+  redo: Vec<Vec<Delta>>,
+}
+
+impl History {
+  pub(super) fn depth(&self) -> usize {
+    self.depth
+  }
+
+  /// Sets the maximum amount of changes that can be undone, discarding the oldest ones.
+  pub(super) fn set_depth(&mut self, depth: usize) {
+    self.depth = depth;
+    let excess = self.undo.len().saturating_sub(depth);
+    self.undo.drain(..excess);
+    let excess = self.redo.len().saturating_sub(depth);
+    self.redo.drain(..excess);
+  }
+
+  pub(super) fn is_enabled(&self) -> bool {
+    self.depth > 0
+  }
+
+  pub(super) fn can_undo(&self) -> bool {
+    !self.undo.is_empty()
+  }
+
+  pub(super) fn can_redo(&self) -> bool {
+    !self.redo.is_empty()
+  }
+
+  /// Records a new change, which can't be followed by the changes previously undone.
+  pub(super) fn record(&mut self, keys: &[KeyChange]) {
+    self.redo.clear();
+    self.push_undo(keys);
+  }
+
+  pub(super) fn push_undo(&mut self, keys: &[KeyChange]) {
+    if self.is_enabled() {
+      if self.undo.len() >= self.depth {
+        self.undo.pop_front();
+      }
+
+      self.undo.push_back(inverse(keys));
+    }
+  }
+
+  pub(super) fn push_redo(&mut self, keys: &[KeyChange]) {
+    if self.is_enabled() {
+      self.redo.push(inverse(keys));
+    }
+  }
+
+  pub(super) fn pop_undo(&mut self) -> Option<Vec<Delta>> {
+    self.undo.pop_back()
+  }
+
+  pub(super) fn pop_redo(&mut self) -> Option<Vec<Delta>> {
+    self.redo.pop()
+  }
+
+  /// Every delta that can still be applied by undoing or redoing.
+  pub(super) fn deltas(&self) -> impl Iterator<Item = &Delta> {
+    self.undo.iter().chain(&self.redo).flatten()
+  }
+
+  pub(super) fn clear(&mut self) {
+    self.undo.clear();
+    self.redo.clear();
+  }
+}
+
+/// Deltas that revert the changes, in the reverse order they were made.
+fn inverse(keys: &[KeyChange]) -> Vec<Delta> {
+  keys
+    .iter()
+    .rev()
+    .map(|change| match &change.old {
+      Some(value) => Delta::set(change.key.clone(), value.clone()),
+      None => Delta::remove(change.key.clone()),
+    })
+    .collect()
+}
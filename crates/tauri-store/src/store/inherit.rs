@@ -0,0 +1,46 @@
+use super::{StoreId, StoreState};
+
+/// State a store inherits from its parent chain.
+///
+/// Every key the store doesn't have on its own falls back to the parent, which in turn
+/// falls back to its own parent. Unlike layers, values are never merged.
+#[derive(Debug)]
+pub(super) struct Inheritance {
+  parent: StoreId,
+  inherited: StoreState,
+  effective: StoreState,
+}
+
+impl Inheritance {
+  pub(super) fn new(parent: StoreId, inherited: StoreState) -> Self {
+    Self {
+      parent,
+      inherited,
+      effective: StoreState::default(),
+    }
+  }
+
+  pub(super) fn parent(&self) -> &StoreId {
+    &self.parent
+  }
+
+  /// State seen by every reader of the store.
+  pub(super) fn effective(&self) -> &StoreState {
+    &self.effective
+  }
+
+  /// Replaces the state of the parent, which must be [refreshed](Inheritance::refresh) afterwards.
+  pub(super) fn set_inherited(&mut self, inherited: StoreState) {
+    self.inherited = inherited;
+  }
+
+  /// Puts the state of the store on top of the inherited one again, after either has changed.
+  pub(super) fn refresh(&mut self, own: &StoreState) {
+    let mut effective = self.inherited.clone();
+    for (key, value) in own.entries() {
+      effective.set(key, value.clone());
+    }
+
+    self.effective = effective;
+  }
+}
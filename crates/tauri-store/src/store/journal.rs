@@ -0,0 +1,163 @@
+use super::StoreState;
+use crate::error::Result;
+use crate::io::remove_file;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A single change to the store state.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub(crate) enum Delta {
+  Set { key: String, value: Value },
+  Remove { key: String },
+}
+
+impl Delta {
+  pub(crate) fn set(key: impl Into<String>, value: Value) -> Self {
+    Self::Set { key: key.into(), value }
+  }
+
+  pub(crate) fn remove(key: impl Into<String>) -> Self {
+    Self::Remove { key: key.into() }
+  }
+
+  /// Key changed by the delta.
+  pub(crate) fn key(&self) -> &str {
+    match self {
+      Self::Set { key, .. } | Self::Remove { key } => key,
+    }
+  }
+
+  /// Value set by the delta, or `None` if it removes the key.
+  pub(crate) fn value(&self) -> Option<&Value> {
+    match self {
+      Self::Set { value, .. } => Some(value),
+      Self::Remove { .. } => None,
+    }
+  }
+
+  pub(crate) fn apply(self, state: &mut StoreState) {
+    match self {
+      Self::Set { key, value } => {
+        state.set(key, value);
+      }
+      Self::Remove { key } => {
+        state.remove(key);
+      }
+    }
+  }
+}
+
+/// Append-only write-ahead log of a store.
+///
+/// Each line of the file is a JSON-encoded [`Delta`], regardless of the marshaler used by the store.
+/// The log is replayed when the store is loaded and is discarded once the store is saved.
+pub(crate) struct Journal {
+  path: PathBuf,
+  len: AtomicUsize,
+}
+
+impl Journal {
+  pub(crate) fn new(path: PathBuf) -> Self {
+    Self { path, len: AtomicUsize::new(0) }
+  }
+
+  /// Amount of records currently in the log.
+  pub(crate) fn len(&self) -> usize {
+    self.len.load(Ordering::Acquire)
+  }
+
+  /// Applies every record in the log to the state.
+  ///
+  /// If the last record is incomplete, which happens when the app is killed mid-write,
+  /// it is discarded and the log is truncated so that new records can be safely appended.
+  pub(crate) fn replay(&self, state: &mut StoreState) -> Result<()> {
+    let bytes = match fs::read(&self.path) {
+      Ok(bytes) => bytes,
+      Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+      Err(err) => return Err(err.into()),
+    };
+
+    let mut len = 0;
+    let mut offset = 0;
+    for line in bytes.split_inclusive(|byte| *byte == b'\n') {
+      if line.ends_with(b"\n") {
+        if line.len() > 1 {
+          serde_json::from_slice::<Delta>(line)?.apply(state);
+          len += 1;
+        }
+
+        offset += line.len();
+      } else {
+        OpenOptions::new()
+          .write(true)
+          .open(&self.path)?
+          .set_len(offset as u64)?;
+
+        break;
+      }
+    }
+
+    self.len.store(len, Ordering::Release);
+
+    Ok(())
+  }
+
+  /// Appends the deltas to the log.
+  pub(crate) fn append(&self, deltas: &[Delta]) -> Result<()> {
+    if deltas.is_empty() {
+      return Ok(());
+    }
+
+    let mut bytes = Vec::new();
+    for delta in deltas {
+      serde_json::to_writer(&mut bytes, delta)?;
+      bytes.push(b'\n');
+    }
+
+    if let Some(parent) = self.path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(&self.path)?;
+
+    file.write_all(&bytes)?;
+    file.flush()?;
+
+    if cfg!(feature = "file-sync-all") {
+      file.sync_all()?;
+    }
+
+    self.len.fetch_add(deltas.len(), Ordering::AcqRel);
+
+    Ok(())
+  }
+
+  /// Discards the log if it has exactly `len` records.
+  ///
+  /// This should be called after the state containing those records is saved.
+  /// If more records were appended in the meantime, the log is kept, as replaying
+  /// an already saved record is harmless, while losing an unsaved one is not.
+  pub(crate) fn compact(&self, len: usize) -> Result<()> {
+    if len > 0 && self.len() == len {
+      remove_file(&self.path)?;
+      self.len.store(0, Ordering::Release);
+    }
+
+    Ok(())
+  }
+
+  /// Deletes the log.
+  pub(crate) fn remove(&self) -> Result<()> {
+    remove_file(&self.path)?;
+    self.len.store(0, Ordering::Release);
+    Ok(())
+  }
+}
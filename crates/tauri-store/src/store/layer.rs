@@ -0,0 +1,116 @@
+use super::StoreState;
+use serde_json::{Map, Value};
+use std::env;
+
+/// Layers of a layered store, apart from the persisted state.
+///
+/// From the lowest to the highest precedence, the effective state is made of the defaults,
+/// the persisted state, the environment and the overrides. Nested objects are merged across layers.
+#[derive(Debug, Default)]
+pub(super) struct Layers {
+  defaults: StoreState,
+  env: StoreState,
+  overrides: StoreState,
+  effective: StoreState,
+}
+
+impl Layers {
+  pub(super) fn new(defaults: StoreState, env: StoreState) -> Self {
+    Self { defaults, env, ..Self::default() }
+  }
+
+  /// State seen by every reader of the store.
+  pub(super) fn effective(&self) -> &StoreState {
+    &self.effective
+  }
+
+  /// Merges the layers again, after any of them has changed.
+  pub(super) fn refresh(&mut self, persisted: &StoreState) {
+    let mut effective = self.defaults.clone();
+    for layer in [persisted, &self.env, &self.overrides] {
+      for (key, value) in layer.entries() {
+        match effective.get_raw_mut(key) {
+          Some(current) => overlay(current, value),
+          None => {
+            effective.set(key, value.clone());
+          }
+        }
+      }
+    }
+
+    self.effective = effective;
+  }
+
+  pub(super) fn set_override(&mut self, key: &str, value: Value) {
+    self.overrides.set(key, value);
+  }
+
+  pub(super) fn remove_override(&mut self, key: &str) -> Option<Value> {
+    self.overrides.remove(key)
+  }
+
+  pub(super) fn has_overrides(&self) -> bool {
+    !self.overrides.is_empty()
+  }
+
+  /// Removes every override, returning their keys.
+  pub(super) fn clear_overrides(&mut self) -> Vec<String> {
+    let keys = self.overrides.keys().cloned().collect();
+    self.overrides.clear();
+    keys
+  }
+}
+
+/// Reads the layer set by the environment variables with the given prefix.
+///
+/// `{PREFIX}__{KEY}` sets `key`, and each further `__` goes one level deeper into it.
+/// Values are parsed as JSON, falling back to a string.
+pub(super) fn from_env(prefix: &str) -> StoreState {
+  let prefix = format!("{prefix}__");
+  let mut state = StoreState::new();
+  for (name, value) in env::vars() {
+    let Some(path) = name.strip_prefix(&prefix) else {
+      continue;
+    };
+
+    let mut segments = path
+      .split("__")
+      .filter(|segment| !segment.is_empty())
+      .map(str::to_lowercase);
+
+    let Some(key) = segments.next() else {
+      continue;
+    };
+
+    let mut value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+    for segment in segments.collect::<Vec<_>>().into_iter().rev() {
+      value = Value::Object(Map::from_iter([(segment, value)]));
+    }
+
+    match state.get_raw_mut(&key) {
+      Some(current) => overlay(current, &value),
+      None => {
+        state.set(key, value);
+      }
+    }
+  }
+
+  state
+}
+
+/// Puts the value on top of the target, merging their objects.
+fn overlay(target: &mut Value, value: &Value) {
+  match (target, value) {
+    (Value::Object(target), Value::Object(value)) => {
+      for (key, value) in value {
+        match target.get_mut(key) {
+          Some(current) => overlay(current, value),
+          None => {
+            target.insert(key.clone(), value.clone());
+          }
+        }
+      }
+    }
+    (target, value) => *target = value.clone(),
+  }
+}
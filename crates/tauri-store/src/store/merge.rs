@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// How [`Store::patch`](crate::Store::patch) applies a partial state to a store.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PatchMode {
+  /// Each key of the patch replaces the whole value of the same key in the store.
+  #[default]
+  Shallow,
+  /// The patch is applied as a [JSON Merge Patch](https://datatracker.ietf.org/doc/html/rfc7396),
+  /// so nested objects are merged and `null` removes a key.
+  ///
+  /// See [`Store::merge_patch`](crate::Store::merge_patch).
+  Merge,
+}
+
+impl PatchMode {
+  /// Whether the mode is [`Shallow`](PatchMode::Shallow).
+  #[inline]
+  pub const fn is_shallow(&self) -> bool {
+    matches!(self, Self::Shallow)
+  }
+
+  /// Whether the mode is [`Merge`](PatchMode::Merge).
+  #[inline]
+  pub const fn is_merge(&self) -> bool {
+    matches!(self, Self::Merge)
+  }
+}
+
+/// Applies a JSON Merge Patch to the value.
+pub(crate) fn merge(target: &mut Value, patch: Value) {
+  let Value::Object(patch) = patch else {
+    *target = patch;
+    return;
+  };
+
+  if !target.is_object() {
+    *target = Value::Object(Map::new());
+  }
+
+  if let Value::Object(target) = target {
+    for (key, value) in patch {
+      if value.is_null() {
+        #[cfg(feature = "preserve-order")]
+        target.shift_remove(&key);
+        #[cfg(not(feature = "preserve-order"))]
+        target.remove(&key);
+      } else {
+        merge(target.entry(key).or_insert(Value::Null), value);
+      }
+    }
+  }
+}
+
+/// Creates a JSON Merge Patch that turns `from` into `to`, or `None` if they are equal.
+pub(crate) fn diff(from: &Value, to: &Value) -> Option<Value> {
+  if from == to {
+    return None;
+  }
+
+  let (Value::Object(from), Value::Object(to)) = (from, to) else {
+    return Some(to.clone());
+  };
+
+  let mut patch = Map::new();
+  for key in from.keys() {
+    if !to.contains_key(key) {
+      patch.insert(key.clone(), Value::Null);
+    }
+  }
+
+  for (key, value) in to {
+    let change = match from.get(key) {
+      Some(old) => diff(old, value),
+      None => Some(value.clone()),
+    };
+
+    if let Some(change) = change {
+      patch.insert(key.clone(), change);
+    }
+  }
+
+  Some(Value::Object(patch))
+}
@@ -1,42 +1,66 @@
+mod batch;
+mod blob;
+mod crdt;
+mod expiry;
+mod history;
 mod id;
+mod inherit;
+mod journal;
+mod layer;
 mod marshaler;
+mod merge;
+mod op;
 mod options;
+mod pointer;
 mod resource;
 mod save;
+pub(crate) mod shard;
 mod state;
 mod watch;
 
 use crate::collection::CollectionMarker;
 use crate::error::{Error, Result};
 use crate::event::{
-  emit, ConfigPayload, EventSource, StatePayload, STORE_CONFIG_CHANGE_EVENT,
-  STORE_STATE_CHANGE_EVENT,
+  emit, ConfigPayload, EventSource, HistoryPayload, StatePayload, STORE_CONFIG_CHANGE_EVENT,
+  STORE_HISTORY_CHANGE_EVENT, STORE_STATE_CHANGE_EVENT,
 };
+use crate::io::remove_dir_all;
 use crate::manager::ManagerExt;
-use crate::StoreCollection;
+use crate::{io_err, StoreCollection};
+use crdt::Crdt;
+use expiry::Expiry;
+use history::History;
+use inherit::Inheritance;
+use journal::Journal;
+use layer::Layers;
 use options::set_options;
-use save::{debounce, throttle, SaveHandle};
+use save::{debounce, throttle, PendingSave, SaveHandle};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::ErrorKind;
-use std::io::Write;
+use shard::ShardTracker;
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
 use std::path::PathBuf;
-use std::sync::{Arc, OnceLock};
-use std::{fmt, fs};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use std::{fmt, iter};
 use tauri::async_runtime::spawn_blocking;
 use tauri::{AppHandle, ResourceId, Runtime};
 use watch::Watcher;
+pub use watch::{ChangeReceiver, KeyChange, StoreChange, WatcherId};
 
+pub use batch::{Batch, BatchOp};
+pub use blob::read_blob_upload;
 pub use id::StoreId;
+pub(crate) use journal::Delta;
 pub use marshaler::{JsonMarshaler, Marshaler, MarshalingError, PrettyJsonMarshaler};
+pub use merge::PatchMode;
+pub use op::AtomicOp;
 pub use options::StoreOptions;
-pub(crate) use resource::StoreResource;
+pub(crate) use resource::{is_holding_store, StoreGuard, StoreResource};
 pub use save::SaveStrategy;
 pub use state::StoreState;
-pub use watch::WatcherId;
 
 #[cfg(feature = "marshaler-cbor")]
 pub use marshaler::CborMarshaler;
@@ -56,8 +80,23 @@ where
   app: AppHandle<R>,
   pub(crate) id: StoreId,
   state: StoreState,
+  pub(crate) journal: Journal,
+  shards: ShardTracker,
+  crdt: Option<Crdt>,
+  expiry: Expiry,
+  layers: Option<Layers>,
+  inheritance: Option<Inheritance>,
+  history: History,
+  revision: u64,
+  /// Changes made to the persisted state since the store was loaded.
+  changes: u64,
+  /// Amount of changes the last save included.
+  saved_changes: Arc<AtomicU64>,
+  /// Save started while the store was locked, to be finished once it is released.
+  deferred_save: Mutex<Option<PendingSave>>,
   pub(crate) save_on_exit: bool,
   save_on_change: bool,
+  pub(crate) evictable: bool,
   save_strategy: Option<SaveStrategy>,
   debounce_save_handle: OnceLock<SaveHandle<R>>,
   throttle_save_handle: OnceLock<SaveHandle<R>>,
@@ -73,22 +112,75 @@ where
   pub(crate) fn load(app: &AppHandle<R>, id: impl AsRef<str>) -> Result<ResourceTuple<R, C>> {
     let id = StoreId::from(id.as_ref());
     let collection = app.store_collection_with_marker::<C>();
-    let marshaler = collection.marshaler_table.get(&id);
-    let path = make_path::<R, C>(&collection, &id, marshaler.extension());
-    let state = match fs::read(&path) {
-      Ok(bytes) => marshaler
-        .deserialize(&bytes)
-        .map_err(Error::FailedToDeserialize)?,
-      Err(err) if err.kind() == ErrorKind::NotFound => StoreState::default(),
-      Err(err) => return Err(Error::Io(err)),
+    let mut state = if collection.layout().is_container() {
+      collection
+        .container
+        .lock()
+        .expect("container is poisoned")
+        .stores
+        .get(&id)
+        .cloned()
+        .unwrap_or_default()
+    } else {
+      collection.read_store_file(&id)?
+    };
+
+    // The log is replayed even if the store is no longer journaled.
+    // Otherwise, any change made since the last save would be lost.
+    let journal = Journal::new(collection.journal_path(&id));
+    journal.replay(&mut state)?;
+
+    let crdt = if collection.crdt.contains(&id) {
+      Some(Crdt::load(collection.crdt_path(&id))?)
+    } else {
+      None
+    };
+
+    let expiry = Expiry::load(collection.expiry_path(&id))?;
+
+    let layers = collection.layered.contains(&id).then(|| {
+      let defaults = collection
+        .defaults
+        .get(&id)
+        .cloned()
+        .unwrap_or_default();
+
+      let env = collection
+        .env_prefixes
+        .get(&id)
+        .map(|prefix| layer::from_env(prefix))
+        .unwrap_or_default();
+
+      Layers::new(defaults, env)
+    });
+
+    // Loading the parent also loads the rest of the chain.
+    let inheritance = match collection.parents.get(&id) {
+      Some(parent) => {
+        let inherited = collection.raw_state(parent)?;
+        Some(Inheritance::new(parent.clone(), inherited))
+      }
+      None => None,
     };
 
     let mut store = Self {
       app: app.clone(),
       id,
       state,
+      journal,
+      shards: ShardTracker::default(),
+      crdt,
+      expiry,
+      layers,
+      inheritance,
+      history: History::default(),
+      revision: 0,
+      changes: 0,
+      saved_changes: Arc::new(AtomicU64::new(0)),
+      deferred_save: Mutex::new(None),
       save_on_change: false,
       save_on_exit: true,
+      evictable: true,
       save_strategy: None,
       debounce_save_handle: OnceLock::new(),
       throttle_save_handle: OnceLock::new(),
@@ -96,19 +188,85 @@ where
       phantom: PhantomData,
     };
 
+    // Whatever is left in the log has yet to be written to the store file.
+    if store.journal.len() > 0 {
+      store.changes += 1;
+    }
+
     store.run_pending_migrations()?;
+    store.seed_defaults();
+
+    store.refresh_view();
+
+    if let Some(crdt) = &mut store.crdt {
+      crdt.track(&store.state);
+    }
 
     Ok(StoreResource::create(app, store))
   }
 
   fn run_pending_migrations(&mut self) -> Result<()> {
+    let collection = self.app.store_collection_with_marker::<C>();
+    let mut migrator = collection
+      .migrator
+      .lock()
+      .expect("migrator is poisoned");
+
+    let version = migrator.history().get(&self.id).cloned();
+    let result = migrator.migrate::<R, C>(&self.app, &self.id, &mut self.state);
+
+    // We can't know which keys a migration has changed.
+    if migrator.history().get(&self.id) != version.as_ref() {
+      self.shards.mark_all();
+      self.changes += 1;
+    }
+
+    result
+  }
+
+  /// Defaults that belong in the persisted state, which is never the case for a layered store.
+  fn persisted_defaults(&self) -> Option<&StoreState> {
+    if self.is_layered() {
+      return None;
+    }
+
     self
       .app
       .store_collection_with_marker::<C>()
-      .migrator
-      .lock()
-      .expect("migrator is poisoned")
-      .migrate::<R, C>(&self.app, &self.id, &mut self.state)
+      .inner()
+      .defaults
+      .get(&self.id)
+  }
+
+  /// Sets the keys that are missing to their default values, which are saved like any other change.
+  ///
+  /// The defaults of a layered store are a layer of their own, so they're never seeded.
+  fn seed_defaults(&mut self) {
+    let Some(defaults) = self.persisted_defaults() else {
+      return;
+    };
+
+    let missing = defaults
+      .entries()
+      .filter(|(key, _)| !self.state.has(key))
+      .map(|(key, value)| (key.clone(), value.clone()))
+      .collect::<Vec<_>>();
+
+    if missing.is_empty() {
+      return;
+    }
+
+    if self.is_sharded() {
+      self
+        .shards
+        .mark(missing.iter().map(|(key, _)| key.as_str()));
+    }
+
+    for (key, value) in missing {
+      self.state.set(key, value);
+    }
+
+    self.changes += 1;
   }
 
   /// The id of the store.
@@ -117,11 +275,27 @@ where
     self.id.clone()
   }
 
+  /// Amount of changes made to the store since it was loaded.
+  #[inline]
+  pub fn revision(&self) -> u64 {
+    self.revision
+  }
+
   /// Path to the store file.
+  ///
+  /// If the collection uses the [container layout](crate::CollectionLayout::Container),
+  /// this is the path to the container file, which is shared by all the stores.
+  /// If the store is [sharded](crate::StoreCollectionBuilder::shard), this is the directory
+  /// where its keys are saved.
   pub fn path(&self) -> PathBuf {
     let collection = self.app.store_collection_with_marker::<C>();
-    let marshaler = collection.marshaler_table.get(&self.id);
-    make_path::<R, C>(&collection, &self.id, marshaler.extension())
+    if collection.layout().is_container() {
+      collection.container_path()
+    } else if collection.is_sharded(&self.id) {
+      collection.shard_dir(&self.id)
+    } else {
+      collection.store_file_path(&self.id)
+    }
   }
 
   /// Gets a handle to the application instance.
@@ -130,17 +304,56 @@ where
   }
 
   /// Gets a reference to the raw store state.
+  ///
+  /// If the store is [layered](Store::is_layered), this is the merge of all its layers.
   #[inline]
   pub fn raw_state(&self) -> &StoreState {
+    self.view()
+  }
+
+  /// State that is saved to the disk, which is the whole state unless the store is layered.
+  pub(crate) fn persisted_state(&self) -> &StoreState {
     &self.state
   }
 
+  /// State seen by the readers of the store.
+  fn view(&self) -> &StoreState {
+    match &self.inheritance {
+      Some(inheritance) => inheritance.effective(),
+      None => self.own_view(),
+    }
+  }
+
+  /// State of the store without what it inherits from its parent.
+  fn own_view(&self) -> &StoreState {
+    self
+      .layers
+      .as_ref()
+      .map_or(&self.state, Layers::effective)
+  }
+
+  /// Computes the state seen by the readers again, after any part of it has changed.
+  fn refresh_view(&mut self) {
+    if let Some(layers) = &mut self.layers {
+      layers.refresh(&self.state);
+    }
+
+    if let Some(inheritance) = &mut self.inheritance {
+      let own = self
+        .layers
+        .as_ref()
+        .map_or(&self.state, Layers::effective);
+
+      inheritance.refresh(own);
+    }
+  }
+
   /// Tries to parse the store state as an instance of type `T`.
   pub fn state<T>(&self) -> Result<T>
   where
     T: DeserializeOwned,
   {
-    let value = Value::from(&self.state);
+    let value = Value::from(self.view());
     Ok(serde_json::from_value(value)?)
   }
 
@@ -176,7 +389,7 @@ where
 
   /// Gets a reference to a raw value from the store.
   pub fn get_raw(&self, key: impl AsRef<str>) -> Option<&Value> {
-    self.state.get_raw(key)
+    self.view().get_raw(key)
   }
 
   /// Gets a reference to a raw value from the store.
@@ -185,7 +398,7 @@ where
   ///
   /// This is *undefined behavior* if the key doesn't exist in the store.
   pub unsafe fn get_raw_unchecked(&self, key: impl AsRef<str>) -> &Value {
-    unsafe { self.state.get_raw_unchecked(key) }
+    unsafe { self.view().get_raw_unchecked(key) }
   }
 
   /// Gets a value from the store and tries to parse it as an instance of type `T`.
@@ -193,7 +406,22 @@ where
   where
     T: DeserializeOwned,
   {
-    self.state.get(key)
+    self.view().get(key)
+  }
+
+  /// Gets a reference to the raw value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901),
+  /// such as `/settings/editor/fontSize`, where the first token is the key.
+  pub fn get_raw_path(&self, pointer: impl AsRef<str>) -> Option<&Value> {
+    self.view().get_raw_path(pointer)
+  }
+
+  /// Gets the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901)
+  /// and tries to parse it as an instance of type `T`.
+  pub fn get_path<T>(&self, pointer: impl AsRef<str>) -> Result<T>
+  where
+    T: DeserializeOwned,
+  {
+    self.view().get_path(pointer)
   }
 
   /// Gets a value from the store and tries to parse it as an instance of type `T`.
@@ -203,7 +431,7 @@ where
   where
     T: DeserializeOwned,
   {
-    self.state.get_or(key, default)
+    self.view().get_or(key, default)
   }
 
   /// Gets a value from the store and tries to parse it as an instance of type `T`.
@@ -213,7 +441,7 @@ where
   where
     T: DeserializeOwned + Default,
   {
-    self.state.get_or_default(key)
+    self.view().get_or_default(key)
   }
 
   /// Gets a value from the store and tries to parse it as an instance of type `T`.
@@ -223,7 +451,7 @@ where
   where
     T: DeserializeOwned,
   {
-    self.state.get_or_else(key, f)
+    self.view().get_or_else(key, f)
   }
 
   /// Gets a value from the store and parses it as an instance of type `T`.
@@ -236,13 +464,350 @@ where
   where
     T: DeserializeOwned,
   {
-    unsafe { self.state.get_unchecked(key) }
+    unsafe { self.view().get_unchecked(key) }
   }
 
   /// Sets a key-value pair in the store.
   pub fn set(&mut self, key: impl AsRef<str>, value: impl Into<Value>) -> Result<()> {
-    self.state.set(key, value);
-    self.on_state_change(None::<&str>)
+    let delta = Delta::set(key.as_ref(), value.into());
+    self.apply_deltas(vec![delta], None::<&str>)
+  }
+
+  /// Restores a key to its default value, removing it if it has none.
+  ///
+  /// As the defaults of a layered store are a layer of their own,
+  /// this only removes the key from its persisted state.
+  pub fn reset_key(&mut self, key: impl AsRef<str>) -> Result<()> {
+    let key = key.as_ref();
+    let default = self
+      .persisted_defaults()
+      .and_then(|defaults| defaults.get_raw(key))
+      .cloned();
+
+    if self.state.get_raw(key) == default.as_ref() {
+      return Ok(());
+    }
+
+    let delta = match default {
+      Some(value) => Delta::set(key, value),
+      None => Delta::remove(key),
+    };
+
+    self.apply_deltas(vec![delta], None::<&str>)
+  }
+
+  /// Restores the store to its default values, removing every key that has none.
+  ///
+  /// Every window receives the new state, including the one that asked for the reset.
+  pub fn reset(&mut self) -> Result<()> {
+    let defaults = self.persisted_defaults();
+    let mut deltas = Vec::new();
+    for key in self.state.keys() {
+      if !defaults.is_some_and(|defaults| defaults.has(key)) {
+        deltas.push(Delta::remove(key.as_str()));
+      }
+    }
+
+    for (key, value) in defaults.into_iter().flat_map(StoreState::entries) {
+      if self.state.get_raw(key) != Some(value) {
+        deltas.push(Delta::set(key.as_str(), value.clone()));
+      }
+    }
+
+    if !deltas.is_empty() {
+      self.apply_deltas(deltas, None::<&str>)?;
+    }
+
+    Ok(())
+  }
+
+  /// Sets a key-value pair in the store that is removed once the given duration elapses.
+  ///
+  /// The deadline is saved along with the store, so the key still expires after a restart.
+  /// Setting or removing the key again in any other way makes it persistent.
+  pub fn set_with_ttl(
+    &mut self,
+    key: impl AsRef<str>,
+    value: impl Into<Value>,
+    ttl: Duration,
+  ) -> Result<()> {
+    let key = key.as_ref();
+    let deltas = vec![Delta::set(key, value.into())];
+    self.publish_deltas(&deltas)?;
+    let keys = self.commit_deltas(deltas)?;
+    self.expiry.set(key, ttl);
+    self.on_local_change(None::<&str>, keys)
+  }
+
+  /// Time left until a key expires, if it was set with a [time-to-live](Store::set_with_ttl).
+  pub fn ttl(&self, key: impl AsRef<str>) -> Option<Duration> {
+    self.expiry.ttl(key.as_ref())
+  }
+
+  /// Removes every key whose [time-to-live](Store::set_with_ttl) has elapsed,
+  /// returning how many were removed.
+  ///
+  /// This already happens whenever the store is accessed through its collection,
+  /// and periodically in the background, so calling it manually is rarely needed.
+  pub fn purge_expired(&mut self) -> Result<usize> {
+    let expired = self.expiry.expired();
+    if expired.is_empty() {
+      return Ok(0);
+    }
+
+    let mut deltas = Vec::with_capacity(expired.len());
+    for key in expired {
+      if self.state.has(&key) {
+        deltas.push(Delta::remove(key));
+      } else {
+        self.expiry.clear(&key);
+      }
+    }
+
+    if deltas.is_empty() {
+      return Ok(0);
+    }
+
+    let len = deltas.len();
+    self.publish_deltas(&deltas)?;
+    let keys = self.commit_deltas(deltas)?;
+
+    // An expired key shouldn't come back when undoing something else.
+    self.on_state_change(None::<&str>, keys)?;
+    Ok(len)
+  }
+
+  /// Replaces the value of a key with the one returned by the closure, which receives the
+  /// current value, or `None` if the key does not exist.
+  ///
+  /// The store stays locked from the read to the write, so no other change can happen in between.
+  /// Returns the new value.
+  pub fn update<F>(&mut self, key: impl AsRef<str>, f: F) -> Result<Value>
+  where
+    F: FnOnce(Option<&Value>) -> Result<Value>,
+  {
+    self.update_with_source(key, f, None::<&str>)
+  }
+
+  /// Replaces the value of a key with the one returned by the closure,
+  /// optionally having a window as the source.
+  #[doc(hidden)]
+  pub fn update_with_source<F, E>(&mut self, key: impl AsRef<str>, f: F, source: E) -> Result<Value>
+  where
+    F: FnOnce(Option<&Value>) -> Result<Value>,
+    E: Into<EventSource>,
+  {
+    let key = key.as_ref();
+    let value = f(self.view().get_raw(key))?;
+    let delta = Delta::set(key, value.clone());
+    self.apply_deltas(vec![delta], source)?;
+    Ok(value)
+  }
+
+  /// Applies an atomic operation to the value of a key, returning the new value.
+  pub fn apply_op(&mut self, key: impl AsRef<str>, op: AtomicOp) -> Result<Value> {
+    self.update(key, |current| op.apply(current))
+  }
+
+  /// Applies an atomic operation to the value of a key, optionally having a window as the source.
+  #[doc(hidden)]
+  pub fn apply_op_with_source<E>(
+    &mut self,
+    key: impl AsRef<str>,
+    op: AtomicOp,
+    source: E,
+  ) -> Result<Value>
+  where
+    E: Into<EventSource>,
+  {
+    self.update_with_source(key, |current| op.apply(current), source)
+  }
+
+  /// Sets the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901),
+  /// such as `/settings/editor/fontSize`, where the first token is the key.
+  ///
+  /// Intermediate objects are created if they don't exist. As in JSON Patch,
+  /// `-` can be used as the last token to append to an array.
+  pub fn set_path(&mut self, pointer: impl AsRef<str>, value: impl Into<Value>) -> Result<()> {
+    self.set_path_with_source(pointer, value, None::<&str>)
+  }
+
+  /// Sets the value at a JSON Pointer, optionally having a window as the source.
+  #[doc(hidden)]
+  pub fn set_path_with_source<E>(
+    &mut self,
+    pointer: impl AsRef<str>,
+    value: impl Into<Value>,
+    source: E,
+  ) -> Result<()>
+  where
+    E: Into<EventSource>,
+  {
+    let (key, rest) = pointer::split(pointer.as_ref())?;
+    let mut value = value.into();
+    if !rest.is_empty() {
+      // The value may be inherited, in which case the store gets its own copy of it.
+      let mut current = self
+        .view()
+        .get_raw(&key)
+        .cloned()
+        .unwrap_or_default();
+
+      pointer::set(&mut current, rest, value)?;
+      value = current;
+    }
+
+    self.apply_deltas(vec![Delta::set(key, value)], source)
+  }
+
+  /// Removes the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901),
+  /// returning it, if any.
+  pub fn remove_path(&mut self, pointer: impl AsRef<str>) -> Result<Option<Value>> {
+    self.remove_path_with_source(pointer, None::<&str>)
+  }
+
+  /// Removes the value at a JSON Pointer, optionally having a window as the source.
+  #[doc(hidden)]
+  pub fn remove_path_with_source<E>(
+    &mut self,
+    pointer: impl AsRef<str>,
+    source: E,
+  ) -> Result<Option<Value>>
+  where
+    E: Into<EventSource>,
+  {
+    let (key, rest) = pointer::split(pointer.as_ref())?;
+
+    // Only the store's own value can be removed, but a nested one can also be removed from a copy
+    // of the inherited value.
+    let state = if rest.is_empty() {
+      &self.state
+    } else {
+      self.view()
+    };
+    let Some(mut current) = state.get_raw(&key).cloned() else {
+      return Ok(None);
+    };
+
+    let (removed, delta) = if rest.is_empty() {
+      (Some(current), Delta::remove(key))
+    } else {
+      match pointer::remove(&mut current, rest) {
+        Some(removed) => (Some(removed), Delta::set(key, current)),
+        None => return Ok(None),
+      }
+    };
+
+    self.apply_deltas(vec![delta], source)?;
+    Ok(removed)
+  }
+
+  /// Saves the bytes as a blob and sets a reference to it in the store.
+  ///
+  /// Blobs are saved to their own files next to the store file, named after the hash of their
+  /// contents, so binary data doesn't need to be encoded into the store state.
+  pub fn put_blob(&mut self, key: impl AsRef<str>, bytes: impl AsRef<[u8]>) -> Result<()> {
+    self.put_blob_with_source(key, bytes, None::<&str>)
+  }
+
+  /// Saves the bytes as a blob and sets a reference to it in the store,
+  /// optionally having a window as the source.
+  #[doc(hidden)]
+  pub fn put_blob_with_source<E>(
+    &mut self,
+    key: impl AsRef<str>,
+    bytes: impl AsRef<[u8]>,
+    source: E,
+  ) -> Result<()>
+  where
+    E: Into<EventSource>,
+  {
+    let collection = self.app.store_collection_with_marker::<C>();
+    if collection.read_only {
+      return Err(collection.locked_error());
+    }
+
+    let dir = collection.blob_dir(&self.id);
+    let hash = blob::write(&dir, bytes.as_ref())?;
+    let delta = Delta::set(key.as_ref(), blob::to_ref(&hash));
+    self.apply_deltas(vec![delta], source)
+  }
+
+  /// Reads the blob referenced by the key.
+  ///
+  /// Returns `None` if the key does not exist or its value is not a reference to a blob.
+  pub fn get_blob(&self, key: impl AsRef<str>) -> Result<Option<Vec<u8>>> {
+    let Some(hash) = self.view().get_raw(key).and_then(blob::from_ref) else {
+      return Ok(None);
+    };
+
+    // An inherited blob is saved next to the store it was put in, which may be any ancestor.
+    let collection = self.app.store_collection_with_marker::<C>();
+    for id in iter::successors(Some(&self.id), |id| collection.parents.get(*id)) {
+      if let Some(bytes) = blob::read(&collection.blob_dir(id), hash)? {
+        return Ok(Some(bytes));
+      }
+    }
+
+    Ok(None)
+  }
+
+  /// Removes the reference to a blob from the store, returning whether it existed.
+  ///
+  /// The blob itself is only deleted when the garbage is [collected](StoreCollection::collect_blobs).
+  pub fn remove_blob(&mut self, key: impl AsRef<str>) -> Result<bool> {
+    self.remove_blob_with_source(key, None::<&str>)
+  }
+
+  /// Removes the reference to a blob from the store, optionally having a window as the source.
+  #[doc(hidden)]
+  pub fn remove_blob_with_source<E>(&mut self, key: impl AsRef<str>, source: E) -> Result<bool>
+  where
+    E: Into<EventSource>,
+  {
+    let key = key.as_ref();
+    let is_blob = self
+      .state
+      .get_raw(key)
+      .and_then(blob::from_ref)
+      .is_some();
+
+    if !is_blob {
+      return Ok(false);
+    }
+
+    self.apply_deltas(vec![Delta::remove(key)], source)?;
+
+    Ok(true)
+  }
+
+  /// Hashes of the blobs referenced by the state, including those that undoing or redoing would restore.
+  pub(crate) fn blob_refs(&self) -> HashSet<String> {
+    let mut refs = HashSet::new();
+    let values = self
+      .state
+      .values()
+      .chain(self.history.deltas().filter_map(Delta::value));
+
+    for value in values {
+      blob::collect_refs(value, &mut refs);
+    }
+
+    refs
+  }
+
+  /// Deletes every blob that is neither referenced by the store nor in the given set,
+  /// returning how many of them were deleted.
+  pub(crate) fn sweep_blobs(&self, mut referenced: HashSet<String>) -> Result<usize> {
+    referenced.extend(self.blob_refs());
+    blob::collect_garbage(&self.blob_dir(), &referenced)
+  }
+
+  fn blob_dir(&self) -> PathBuf {
+    self
+      .app
+      .store_collection_with_marker::<C>()
+      .blob_dir(&self.id)
   }
 
   /// Patches the store state, optionally having a window as the source.
@@ -252,11 +817,33 @@ where
     S: Into<StoreState>,
     E: Into<EventSource>,
   {
-    self.state.patch(state);
-    self.on_state_change(source)
+    let collection = self.app.store_collection_with_marker::<C>();
+    if collection.default_patch_mode().is_merge() {
+      return self.merge_patch_with_source(state, source);
+    }
+
+    // The frontend sends the whole state of a layered or child store back, including the values
+    // that come from the other layers or the parent. Those must not end up in the persisted state.
+    let derived = self.layers.is_some() || self.inheritance.is_some();
+    let deltas = state
+      .into()
+      .into_inner()
+      .into_iter()
+      .filter(|(key, value)| !derived || self.view().get_raw(key) != Some(value))
+      .map(|(key, value)| Delta::set(key, value))
+      .collect::<Vec<_>>();
+
+    if derived && deltas.is_empty() {
+      return Ok(());
+    }
+
+    self.apply_deltas(deltas, source)
   }
 
   /// Patches the store state.
+  ///
+  /// By default, each key replaces the whole value of the same key in the store.
+  /// If the collection was built with [`PatchMode::Merge`], this is the same as [`Store::merge_patch`].
   pub fn patch<S>(&mut self, state: S) -> Result<()>
   where
     S: Into<StoreState>,
@@ -264,40 +851,365 @@ where
     self.patch_with_source(state, None::<&str>)
   }
 
+  /// Applies a [JSON Merge Patch](https://datatracker.ietf.org/doc/html/rfc7396) to the store state.
+  ///
+  /// Nested objects are merged instead of replaced, and keys whose value in the patch is `null` are removed.
+  pub fn merge_patch<S>(&mut self, patch: S) -> Result<()>
+  where
+    S: Into<StoreState>,
+  {
+    self.merge_patch_with_source(patch, None::<&str>)
+  }
+
+  /// Applies a JSON Merge Patch to the store state, optionally having a window as the source.
+  #[doc(hidden)]
+  pub fn merge_patch_with_source<S, E>(&mut self, patch: S, source: E) -> Result<()>
+  where
+    S: Into<StoreState>,
+    E: Into<EventSource>,
+  {
+    let deltas = patch
+      .into()
+      .into_inner()
+      .into_iter()
+      .filter_map(|(key, value)| {
+        if value.is_null() {
+          return self.state.has(&key).then(|| Delta::remove(key));
+        }
+
+        // An inherited value is merged into a copy that the store then owns.
+        let mut current = self
+          .view()
+          .get_raw(&key)
+          .cloned()
+          .unwrap_or_default();
+
+        merge::merge(&mut current, value);
+        Some(Delta::set(key, current))
+      })
+      .collect::<Vec<_>>();
+
+    if !deltas.is_empty() {
+      self.apply_deltas(deltas, source)?;
+    }
+
+    Ok(())
+  }
+
+  /// Applies every change made in the closure at once.
+  ///
+  /// Watchers are called, the change event is emitted and the store is saved only once,
+  /// instead of once for each change. Nothing happens if the batch is empty.
+  pub fn batch<F, T>(&mut self, f: F) -> Result<T>
+  where
+    F: FnOnce(&mut Batch<'_>) -> T,
+  {
+    self.batch_with_source(f, None::<&str>)
+  }
+
+  /// Applies every change made in the closure at once, optionally having a window as the source.
+  #[doc(hidden)]
+  pub fn batch_with_source<F, T, E>(&mut self, f: F, source: E) -> Result<T>
+  where
+    F: FnOnce(&mut Batch<'_>) -> T,
+    E: Into<EventSource>,
+  {
+    let mut batch = Batch::new(self.view());
+    let value = f(&mut batch);
+    let deltas = batch.into_deltas();
+
+    if !deltas.is_empty() {
+      self.apply_deltas(deltas, source)?;
+    }
+
+    Ok(value)
+  }
+
+  /// Applies changes made by this process, then notifies about them.
+  pub(crate) fn apply_deltas(
+    &mut self,
+    deltas: Vec<Delta>,
+    source: impl Into<EventSource>,
+  ) -> Result<()> {
+    self.publish_deltas(&deltas)?;
+    let keys = self.commit_deltas(deltas)?;
+    self.on_local_change(source, keys)
+  }
+
+  /// Records a change made by this process to the history, then notifies about it.
+  fn on_local_change(
+    &mut self,
+    source: impl Into<EventSource>,
+    keys: Vec<KeyChange>,
+  ) -> Result<()> {
+    if self.history.is_enabled() {
+      self.history.record(&keys);
+      self.emit_history_change()?;
+    }
+
+    self.on_state_change(source, keys)
+  }
+
+  /// Shares changes made by this process with other processes and the remote.
+  fn publish_deltas(&self, deltas: &[Delta]) -> Result<()> {
+    let collection = self.app.store_collection_with_marker::<C>();
+    if let Some(sync) = &collection.process_sync {
+      sync.publish(&self.id, deltas)?;
+    }
+
+    if let Some(remote) = &collection.remote_sync {
+      remote.enqueue(&self.id, deltas)?;
+    }
+
+    Ok(())
+  }
+
+  /// Reverts the last change, returning whether there was anything to undo.
+  ///
+  /// This only works if the store has a [history depth](StoreOptions::history_depth).
+  /// A [batch](Store::batch) is undone at once.
+  pub fn undo(&mut self) -> Result<bool> {
+    let Some(deltas) = self.history.pop_undo() else {
+      return Ok(false);
+    };
+
+    self.publish_deltas(&deltas)?;
+    let keys = self.commit_deltas(deltas)?;
+    self.history.push_redo(&keys);
+    self.emit_history_change()?;
+
+    // Every window must receive the new state, including the one that asked for the undo.
+    self.on_state_change(None::<&str>, keys)?;
+    Ok(true)
+  }
+
+  /// Reapplies the last change that was undone, returning whether there was anything to redo.
+  pub fn redo(&mut self) -> Result<bool> {
+    let Some(deltas) = self.history.pop_redo() else {
+      return Ok(false);
+    };
+
+    self.publish_deltas(&deltas)?;
+    let keys = self.commit_deltas(deltas)?;
+    self.history.push_undo(&keys);
+    self.emit_history_change()?;
+
+    self.on_state_change(None::<&str>, keys)?;
+    Ok(true)
+  }
+
+  /// Whether there is a change to undo.
+  pub fn can_undo(&self) -> bool {
+    self.history.can_undo()
+  }
+
+  /// Whether there is a change to redo.
+  pub fn can_redo(&self) -> bool {
+    self.history.can_redo()
+  }
+
+  /// Maximum amount of changes that can be undone.
+  ///
+  /// The history is disabled when this is zero, which is the default.
+  pub fn history_depth(&self) -> usize {
+    self.history.depth()
+  }
+
+  /// Sets the maximum amount of changes that can be undone, discarding the oldest ones.
+  ///
+  /// Setting it to zero disables the history.
+  pub fn set_history_depth(&mut self, depth: usize) -> Result<()> {
+    self.history.set_depth(depth);
+    self.emit_history_change()
+  }
+
+  /// Discards every change that could be undone or redone.
+  pub fn clear_history(&mut self) -> Result<()> {
+    self.history.clear();
+    self.emit_history_change()
+  }
+
+  /// Applies changes that have already been shared, then notifies about them.
+  ///
+  /// These are either made by another process or replayed from an interrupted transaction.
+  /// Unlike local changes, they are not published again.
+  pub(crate) fn apply_remote_deltas(&mut self, deltas: Vec<Delta>) -> Result<()> {
+    let keys = self.commit_deltas(deltas)?;
+    self.on_state_change(None::<&str>, keys)
+  }
+
+  fn commit_deltas(&mut self, deltas: Vec<Delta>) -> Result<Vec<KeyChange>> {
+    self.commit(deltas, true)
+  }
+
+  /// Applies deltas merged from the CRDT, which must not be recorded to it again.
+  fn commit_merged_deltas(&mut self, deltas: Vec<Delta>) -> Result<Vec<KeyChange>> {
+    self.commit(deltas, false)
+  }
+
+  fn commit(&mut self, deltas: Vec<Delta>, record: bool) -> Result<Vec<KeyChange>> {
+    self.log_deltas(&deltas)?;
+    self.changes += 1;
+    let mut keys = Vec::with_capacity(deltas.len());
+    for delta in deltas {
+      if let Some(crdt) = self.crdt.as_mut().filter(|_| record) {
+        crdt.record(delta.key(), self.state.get_raw(delta.key()), delta.value());
+      }
+
+      self.expiry.clear(delta.key());
+
+      keys.push(self.apply_delta(delta));
+    }
+
+    self.refresh_view();
+
+    Ok(keys)
+  }
+
+  /// Applies a delta to the state, returning what it changed.
+  fn apply_delta(&mut self, delta: Delta) -> KeyChange {
+    let key = delta.key().to_owned();
+    let old = self.state.get_raw(&key).cloned();
+    let new = delta.value().cloned();
+    delta.apply(&mut self.state);
+    KeyChange { key, old, new }
+  }
+
+  /// Records the deltas to the journal and marks their keys to be saved.
+  fn log_deltas(&self, deltas: &[Delta]) -> Result<()> {
+    if self.is_journaled() {
+      self.journal.append(deltas)?;
+    }
+
+    if self.is_sharded() {
+      self.shards.mark(deltas.iter().map(Delta::key));
+    }
+
+    Ok(())
+  }
+
+  /// Whether the store is backed by a CRDT.
+  pub fn is_crdt(&self) -> bool {
+    self.crdt.is_some()
+  }
+
+  /// Clock of the CRDT, which can be passed to [`Store::export_update`]
+  /// to get only the changes made after this point.
+  pub fn crdt_clock(&self) -> Result<u64> {
+    Ok(self.crdt()?.clock())
+  }
+
+  /// Encodes the changes to the store as an update that can be merged into other replicas
+  /// with [`Store::import_update`].
+  ///
+  /// If `since` is provided, only the changes that this replica got after its [clock](Store::crdt_clock)
+  /// was at that point are included, whether they were made here or merged from another replica.
+  /// A full update is always safe to send, as merging is idempotent.
+  pub fn export_update(&self, since: Option<u64>) -> Result<Vec<u8>> {
+    self.crdt()?.export(since)
+  }
+
+  /// Merges an update exported by another replica of the store.
+  ///
+  /// Concurrent changes to the same path are resolved by keeping the latest one,
+  /// so every replica ends up with the same state regardless of the order of the updates.
+  pub fn import_update(&mut self, update: impl AsRef<[u8]>) -> Result<()> {
+    let Some(crdt) = &mut self.crdt else {
+      return io_err!(InvalidInput, "store is not a crdt: {}", self.id);
+    };
+
+    if !crdt.import(update.as_ref())? {
+      return Ok(());
+    }
+
+    let mut merged = crdt.materialize();
+    let mut deltas = Vec::new();
+    for (key, value) in self.state.entries() {
+      match merged.remove(key) {
+        Some(new) if new == *value => {}
+        Some(new) => deltas.push(Delta::set(key.clone(), new)),
+        None => deltas.push(Delta::remove(key.clone())),
+      }
+    }
+
+    deltas.extend(
+      merged
+        .into_iter()
+        .map(|(key, value)| Delta::set(key, value)),
+    );
+
+    if deltas.is_empty() {
+      return Ok(());
+    }
+
+    let keys = self.commit_merged_deltas(deltas)?;
+    self.on_state_change(None::<&str>, keys)
+  }
+
+  fn crdt(&self) -> Result<&Crdt> {
+    match &self.crdt {
+      Some(crdt) => Ok(crdt),
+      None => io_err!(InvalidInput, "store is not a crdt: {}", self.id),
+    }
+  }
+
+  /// Saves the files kept alongside the state, such as the CRDT and the expiry deadlines.
+  pub(crate) fn save_sidecars(&self) -> Result<()> {
+    if self
+      .app
+      .store_collection_with_marker::<C>()
+      .read_only
+    {
+      return Ok(());
+    }
+
+    if let Some(crdt) = &self.crdt {
+      crdt.write()?;
+    }
+
+    self.expiry.write()
+  }
+
   /// Whether the store has a key.
   pub fn has(&self, key: impl AsRef<str>) -> bool {
-    self.state.has(key)
+    self.view().has(key)
   }
 
   /// Creates an iterator over the store keys.
   pub fn keys(&self) -> impl Iterator<Item = &String> {
-    self.state.keys()
+    self.view().keys()
   }
 
   /// Creates an iterator over the store values.
   pub fn values(&self) -> impl Iterator<Item = &Value> {
-    self.state.values()
+    self.view().values()
   }
 
   /// Creates an iterator over the store entries.
   pub fn entries(&self) -> impl Iterator<Item = (&String, &Value)> {
-    self.state.entries()
+    self.view().entries()
   }
 
   /// Returns the amount of items in the store.
   #[inline]
   pub fn len(&self) -> usize {
-    self.state.len()
+    self.view().len()
   }
 
   /// Whether the store is empty.
   #[inline]
   pub fn is_empty(&self) -> bool {
-    self.state.is_empty()
+    self.view().is_empty()
   }
 
   /// Save the store state to the disk.
+  ///
+  /// This does nothing if the store has not changed since it was last saved.
   pub fn save(&self) -> Result<()> {
+    if !self.is_dirty() {
+      return Ok(());
+    }
+
     match self.save_strategy() {
       SaveStrategy::Immediate => self.save_now()?,
       SaveStrategy::Debounce(duration) => {
@@ -318,31 +1230,123 @@ where
   }
 
   /// Save the store immediately, ignoring the save strategy.
+  ///
+  /// This does nothing if the store has not changed since it was last saved.
+  ///
+  /// The state is serialized right away, but the file is only waited for once the store is released,
+  /// so other commands aren't blocked in the meantime. If the write fails, the store stays dirty
+  /// and is saved again the next time. Use [`StoreCollection::save_now`] to know whether it succeeded.
   pub fn save_now(&self) -> Result<()> {
-    let collection = self.app.store_collection_with_marker::<C>();
-    if collection.save_denylist.contains(&self.id) {
-      return Ok(());
-    }
+    self.defer_save(false)
+  }
 
-    let marshaler = collection.marshaler_table.get(&self.id);
-    let bytes = marshaler
-      .serialize(&self.state)
-      .map_err(Error::FailedToSerialize)?;
+  /// Save the store immediately, even if it has not changed since it was last saved.
+  ///
+  /// As with [`Store::save_now`], the file is only waited for once the store is released.
+  pub fn force_save_now(&self) -> Result<()> {
+    self.defer_save(true)
+  }
 
-    let path = self.path();
-    if let Some(parent) = path.parent() {
-      fs::create_dir_all(parent)?;
+  fn defer_save(&self, force: bool) -> Result<()> {
+    if let Some(pending) = self.begin_save(force)? {
+      // The most recent save also covers the changes of the previous ones.
+      *self
+        .deferred_save
+        .lock()
+        .expect("deferred save is poisoned") = Some(pending);
     }
 
-    let mut file = File::create(path)?;
-    file.write_all(&bytes)?;
-    file.flush()?;
+    Ok(())
+  }
 
-    if cfg!(feature = "file-sync-all") {
-      file.sync_all()?;
+  /// Takes the save started while the store was locked, so it can be finished once it is released.
+  pub(crate) fn take_deferred_save(&self) -> Option<PendingSave> {
+    self
+      .deferred_save
+      .lock()
+      .expect("deferred save is poisoned")
+      .take()
+  }
+
+  fn has_deferred_save(&self) -> bool {
+    self
+      .deferred_save
+      .lock()
+      .expect("deferred save is poisoned")
+      .is_some()
+  }
+
+  /// Snapshots the state and hands it to the collection's writer thread,
+  /// so that the caller can release the lock before waiting for the file to be written.
+  ///
+  /// Stores in a [container](crate::CollectionLayout::Container) or [sharded](crate::StoreCollectionBuilder::shard)
+  /// are still written before this returns. The container is shared by every store, and has to be written
+  /// while its lock is held, while a sharded store writes only the keys that have changed, each to its own file.
+  ///
+  /// Returns `None` if there is nothing to save.
+  pub(crate) fn begin_save(&self, force: bool) -> Result<Option<PendingSave>> {
+    let collection = self.app.store_collection_with_marker::<C>();
+    if (!force && !self.is_dirty())
+      || collection.read_only
+      || collection.save_denylist.contains(&self.id)
+    {
+      return Ok(None);
     }
 
-    Ok(())
+    let changes = self.changes;
+    let journal_len = self.journal.len();
+    let layout = collection
+      .layout
+      .read()
+      .expect("layout is poisoned");
+
+    let ticket = if layout.is_container() {
+      collection.write_stores(*layout, [(&self.id, &self.state)])?;
+      self.mark_saved(changes);
+      None
+    } else if collection.is_sharded(&self.id) {
+      let dir = collection.shard_dir(&self.id);
+      let marshaler = collection.marshaler_table.get(&self.id);
+      self.shards.flush(&dir, marshaler, &self.state)?;
+      self.mark_saved(changes);
+      None
+    } else {
+      let bytes = collection
+        .marshaler_table
+        .get(&self.id)
+        .serialize(&self.state)
+        .map_err(Error::FailedToSerialize)?;
+
+      let path = collection.store_file_path(&self.id);
+      let saved = Arc::clone(&self.saved_changes);
+      Some(
+        collection
+          .writer
+          .enqueue(path, bytes, saved, changes),
+      )
+    };
+
+    self.save_sidecars()?;
+    Ok(Some(PendingSave { ticket, journal_len }))
+  }
+
+  /// Whether the store has changed since it was last saved.
+  pub fn is_dirty(&self) -> bool {
+    self.changes > self.saved_changes.load(Ordering::Acquire)
+  }
+
+  /// Amount of changes made to the persisted state since the store was loaded.
+  ///
+  /// After being written, this should be passed to [`Store::mark_saved`].
+  pub(crate) fn changes(&self) -> u64 {
+    self.changes
+  }
+
+  /// Records that the state, as it was after the given amount of changes, has been written.
+  pub(crate) fn mark_saved(&self, changes: u64) {
+    self
+      .saved_changes
+      .fetch_max(changes, Ordering::AcqRel);
   }
 
   /// Whether to save the store on exit.
@@ -358,6 +1362,28 @@ where
     self.save_on_change = enabled;
   }
 
+  /// Whether the store can be unloaded by the collection's eviction policy.
+  /// This is enabled by default.
+  ///
+  /// Even if enabled, the store isn't evicted while it has watchers, undo or redo history,
+  /// or overrides, as they would be lost once it is unloaded.
+  #[inline]
+  pub fn evictable(&mut self, enabled: bool) {
+    self.evictable = enabled;
+  }
+
+  /// Whether the eviction policy may unload the store without losing anything that isn't saved.
+  pub(crate) fn can_evict(&self) -> bool {
+    self.evictable
+      && self.watchers.is_empty()
+      && !self.history.can_undo()
+      && !self.history.can_redo()
+      && !self
+        .layers
+        .as_ref()
+        .is_some_and(Layers::has_overrides)
+  }
+
   /// Current save strategy used by this store.
   pub fn save_strategy(&self) -> SaveStrategy {
     self.save_strategy.unwrap_or_else(|| {
@@ -386,6 +1412,124 @@ where
     self.save_strategy = Some(strategy);
   }
 
+  /// Whether the store is layered, in which case its state is the merge of its defaults,
+  /// its persisted state, the environment and its overrides.
+  pub fn is_layered(&self) -> bool {
+    self.layers.is_some()
+  }
+
+  /// Sets a value that takes precedence over every other layer of a layered store.
+  ///
+  /// Overrides are never saved, lasting only until the store is unloaded.
+  pub fn set_override(&mut self, key: impl AsRef<str>, value: impl Into<Value>) -> Result<()> {
+    let key = key.as_ref();
+    let value = value.into();
+    self.change_overrides(|layers| {
+      layers.set_override(key, value);
+      ((), vec![key.to_owned()])
+    })
+  }
+
+  /// Removes an override from a layered store, returning its value, if any.
+  pub fn remove_override(&mut self, key: impl AsRef<str>) -> Result<Option<Value>> {
+    let key = key.as_ref();
+    self.change_overrides(|layers| {
+      let value = layers.remove_override(key);
+      let keys = value.iter().map(|_| key.to_owned()).collect();
+      (value, keys)
+    })
+  }
+
+  /// Removes every override from a layered store.
+  pub fn clear_overrides(&mut self) -> Result<()> {
+    self.change_overrides(|layers| ((), layers.clear_overrides()))
+  }
+
+  /// Changes the overrides, then notifies about the keys the closure returns.
+  fn change_overrides<F, T>(&mut self, f: F) -> Result<T>
+  where
+    F: FnOnce(&mut Layers) -> (T, Vec<String>),
+  {
+    let old = self.view().clone();
+    let Some(layers) = &mut self.layers else {
+      return io_err!(InvalidInput, "store is not layered: {}", self.id);
+    };
+
+    let (value, keys) = f(layers);
+    if keys.is_empty() {
+      return Ok(value);
+    }
+
+    self.refresh_view();
+    let keys = keys
+      .into_iter()
+      .map(|key| KeyChange {
+        old: old.get_raw(&key).cloned(),
+        new: self.view().get_raw(&key).cloned(),
+        key,
+      })
+      .collect();
+
+    // Overrides are not saved, so there's no need to go through `on_state_change`.
+    self.notify_change(None::<&str>, keys)?;
+    Ok(value)
+  }
+
+  /// The store this one [inherits](crate::StoreCollection::builder) its missing keys from, if any.
+  pub fn parent(&self) -> Option<StoreId> {
+    self
+      .inheritance
+      .as_ref()
+      .map(|inheritance| inheritance.parent().clone())
+  }
+
+  /// Replaces the state inherited from the parent, then notifies about the keys it affects.
+  pub(crate) fn inherit(&mut self, state: StoreState, keys: &[KeyChange]) -> Result<()> {
+    if self.inheritance.is_none() {
+      return Ok(());
+    }
+
+    let old = keys
+      .iter()
+      .map(|change| (&change.key, self.view().get_raw(&change.key).cloned()))
+      .collect::<Vec<_>>();
+
+    if let Some(inheritance) = &mut self.inheritance {
+      inheritance.set_inherited(state);
+    }
+
+    self.refresh_view();
+
+    let keys = old
+      .into_iter()
+      .filter_map(|(key, old)| {
+        let new = self.view().get_raw(key).cloned();
+        (old != new).then(|| KeyChange { key: key.clone(), old, new })
+      })
+      .collect::<Vec<_>>();
+
+    if keys.is_empty() {
+      return Ok(());
+    }
+
+    self.notify_change(None::<&str>, keys)
+  }
+
+  /// Whether the changes to the store are being recorded to a write-ahead log.
+  pub fn is_journaled(&self) -> bool {
+    let collection = self.app.store_collection_with_marker::<C>();
+    !collection.read_only
+      && collection.journal.contains(&self.id)
+      && !collection.save_denylist.contains(&self.id)
+  }
+
+  fn is_sharded(&self) -> bool {
+    self
+      .app
+      .store_collection_with_marker::<C>()
+      .is_sharded(&self.id)
+  }
+
   /// Watches the store for changes.
   pub fn watch<F>(&mut self, f: F) -> WatcherId
   where
@@ -396,6 +1540,64 @@ where
     id
   }
 
+  /// Watches the store for changes, receiving what has changed.
+  pub fn watch_changes<F>(&mut self, f: F) -> WatcherId
+  where
+    F: Fn(AppHandle<R>, &StoreChange) -> Result<()> + Send + Sync + 'static,
+  {
+    let (id, listener) = Watcher::with_change(None, f);
+    self.watchers.insert(id, listener);
+    id
+  }
+
+  /// Watches a key for changes.
+  pub fn watch_key<F>(&mut self, key: impl AsRef<str>, f: F) -> WatcherId
+  where
+    F: Fn(AppHandle<R>, &StoreChange) -> Result<()> + Send + Sync + 'static,
+  {
+    self.watch_keys([key], f)
+  }
+
+  /// Watches some keys for changes.
+  ///
+  /// The watcher is called whenever any of the keys changes.
+  pub fn watch_keys<I, T, F>(&mut self, keys: I, f: F) -> WatcherId
+  where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+    F: Fn(AppHandle<R>, &StoreChange) -> Result<()> + Send + Sync + 'static,
+  {
+    let keys = keys
+      .into_iter()
+      .map(|it| it.as_ref().to_owned())
+      .collect();
+
+    let (id, listener) = Watcher::with_change(Some(keys), f);
+    self.watchers.insert(id, listener);
+    id
+  }
+
+  /// Watches the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) for changes.
+  ///
+  /// Unlike [`Store::watch_key`], the watcher is only called when the value
+  /// at the pointer changes, not when any other part of the key does.
+  pub fn watch_path<F>(&mut self, pointer: impl AsRef<str>, f: F) -> Result<WatcherId>
+  where
+    F: Fn(AppHandle<R>, &StoreChange) -> Result<()> + Send + Sync + 'static,
+  {
+    let pointer = pointer.as_ref().to_owned();
+    let (key, _) = pointer::split(&pointer)?;
+    let id = self.watch_key(key, move |app, change| {
+      if change.contains_path(&pointer) {
+        f(app, change)
+      } else {
+        Ok(())
+      }
+    });
+
+    Ok(id)
+  }
+
   /// Removes a listener from this store.
   pub fn unwatch(&mut self, id: impl Into<WatcherId>) -> bool {
     self.watchers.remove(&id.into()).is_some()
@@ -416,17 +1618,63 @@ where
     self.on_config_change(source)
   }
 
-  fn on_state_change(&self, source: impl Into<EventSource>) -> Result<()> {
-    self.emit_state_change(source)?;
-    self.call_watchers();
+  fn on_state_change(
+    &mut self,
+    source: impl Into<EventSource>,
+    keys: Vec<KeyChange>,
+  ) -> Result<()> {
+    self.notify_change(source, keys)?;
+
+    // A journaled store has already persisted the change to its log,
+    // so it only needs to be saved when the log grows too large.
+    // If a save is already waiting for the store to be released, the log will be compacted then.
+    if self.is_journaled() {
+      let limit = self
+        .app
+        .store_collection_with_marker::<C>()
+        .journal_limit;
 
-    if self.save_on_change {
+      if self.journal.len() >= limit && !self.has_deferred_save() {
+        self.abort_pending_save();
+        self.save_now()?;
+      }
+    } else if self.save_on_change {
       self.save()?;
     }
 
     Ok(())
   }
 
+  /// Lets the frontend, the subscribers and the watchers know about a change.
+  ///
+  /// The children of the store only receive it once the store is released.
+  fn notify_change(&mut self, source: impl Into<EventSource>, keys: Vec<KeyChange>) -> Result<()> {
+    let source: EventSource = source.into();
+    self.revision += 1;
+    self.emit_state_change(source.clone())?;
+
+    let change = Arc::new(StoreChange {
+      id: self.id.clone(),
+      keys,
+      source,
+      revision: self.revision,
+    });
+
+    self
+      .app
+      .store_collection_with_marker::<C>()
+      .publish_change(&change);
+
+    self.call_watchers(&change);
+
+    self
+      .app
+      .store_collection_with_marker::<C>()
+      .queue_children(&self.id, self.view(), &change.keys);
+
+    Ok(())
+  }
+
   fn emit_state_change(&self, source: impl Into<EventSource>) -> Result<()> {
     let source: EventSource = source.into();
 
@@ -450,6 +1698,15 @@ where
     )
   }
 
+  fn emit_history_change(&self) -> Result<()> {
+    emit(
+      &self.app,
+      STORE_HISTORY_CHANGE_EVENT,
+      &HistoryPayload::from(self),
+      None::<&str>,
+    )
+  }
+
   fn on_config_change(&self, source: impl Into<EventSource>) -> Result<()> {
     self.emit_config_change(source)
   }
@@ -463,16 +1720,19 @@ where
     )
   }
 
-  /// Calls all watchers currently attached to the store.
-  fn call_watchers(&self) {
+  /// Calls all watchers currently attached to the store that are interested in the change.
+  fn call_watchers(&self, change: &Arc<StoreChange>) {
     if self.watchers.is_empty() {
       return;
     }
 
     for watcher in self.watchers.values() {
-      let app = self.app.clone();
-      let watcher = watcher.clone();
-      spawn_blocking(move || watcher.call(app));
+      if watcher.matches(change) {
+        let app = self.app.clone();
+        let watcher = watcher.clone();
+        let change = Arc::clone(change);
+        spawn_blocking(move || watcher.call(app, &change));
+      }
     }
   }
 
@@ -491,8 +1751,35 @@ where
   pub(crate) fn destroy(&mut self) -> Result<()> {
     self.abort_pending_save();
     self.state.clear();
-    fs::remove_file(self.path())?;
-    Ok(())
+
+    let collection = self.app.store_collection_with_marker::<C>();
+    if collection.read_only {
+      return Ok(());
+    }
+
+    // A queued write would bring the file back.
+    collection.writer.flush();
+
+    self.journal.remove()?;
+    self.expiry.remove()?;
+    remove_dir_all(&self.blob_dir())?;
+
+    if let Some(crdt) = &mut self.crdt {
+      crdt.remove()?;
+    }
+
+    let layout = collection
+      .layout
+      .read()
+      .expect("layout is poisoned");
+
+    if layout.is_container() {
+      collection.update_container(|container| {
+        container.stores.remove(&self.id);
+      })
+    } else {
+      collection.remove_store_file(&self.id)
+    }
   }
 }
 
@@ -513,7 +1800,11 @@ where
   }
 }
 
-fn make_path<R, C>(collection: &StoreCollection<R, C>, id: &StoreId, extension: &str) -> PathBuf
+pub(crate) fn make_path<R, C>(
+  collection: &StoreCollection<R, C>,
+  id: &StoreId,
+  extension: &str,
+) -> PathBuf
 where
   R: Runtime,
   C: CollectionMarker,
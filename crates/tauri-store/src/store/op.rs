@@ -0,0 +1,105 @@
+use crate::error::Result;
+use crate::io_err;
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+
+/// A read-modify-write operation on the value of a key.
+///
+/// See [`Store::apply_op`](crate::Store::apply_op).
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum AtomicOp {
+  /// Adds to a number, which is `1` by default. A missing key is treated as `0`.
+  Increment { by: Option<Number> },
+  /// Subtracts from a number, which is `1` by default. A missing key is treated as `0`.
+  Decrement { by: Option<Number> },
+  /// Appends a value to an array. A missing key is treated as an empty array.
+  Push { value: Value },
+  /// Removes the element at the index of an array.
+  RemoveAt { index: usize },
+  /// Negates a boolean. A missing key is treated as `false`.
+  Toggle,
+  /// Inserts every key of an object into another. A missing key is treated as an empty object.
+  Merge { value: Map<String, Value> },
+}
+
+impl AtomicOp {
+  /// Applies the operation to the current value, returning the new one.
+  pub(crate) fn apply(self, current: Option<&Value>) -> Result<Value> {
+    let current = current.filter(|it| !it.is_null());
+    match self {
+      Self::Increment { by } => add(current, by, false),
+      Self::Decrement { by } => add(current, by, true),
+      Self::Push { value } => match current {
+        None => Ok(Value::Array(vec![value])),
+        Some(Value::Array(array)) => {
+          let mut array = array.clone();
+          array.push(value);
+          Ok(Value::Array(array))
+        }
+        Some(_) => io_err!(InvalidInput, "cannot push to a value that is not an array"),
+      },
+      Self::RemoveAt { index } => match current {
+        Some(Value::Array(array)) if index < array.len() => {
+          let mut array = array.clone();
+          array.remove(index);
+          Ok(Value::Array(array))
+        }
+        Some(Value::Array(_)) => io_err!(InvalidInput, "index out of bounds: {index}"),
+        _ => io_err!(
+          InvalidInput,
+          "cannot remove from a value that is not an array"
+        ),
+      },
+      Self::Toggle => match current {
+        None => Ok(Value::Bool(true)),
+        Some(Value::Bool(value)) => Ok(Value::Bool(!value)),
+        Some(_) => io_err!(InvalidInput, "cannot toggle a value that is not a boolean"),
+      },
+      Self::Merge { value } => match current {
+        None => Ok(Value::Object(value)),
+        Some(Value::Object(object)) => {
+          let mut object = object.clone();
+          object.extend(value);
+          Ok(Value::Object(object))
+        }
+        Some(_) => io_err!(
+          InvalidInput,
+          "cannot merge into a value that is not an object"
+        ),
+      },
+    }
+  }
+}
+
+fn add(current: Option<&Value>, by: Option<Number>, negate: bool) -> Result<Value> {
+  let current = match current {
+    None => Number::from(0),
+    Some(Value::Number(number)) => number.clone(),
+    Some(_) => return io_err!(InvalidInput, "cannot add to a value that is not a number"),
+  };
+
+  let by = by.unwrap_or_else(|| Number::from(1));
+
+  // Integers stay integers, as long as they don't overflow.
+  if let (Some(current), Some(by)) = (current.as_i64(), by.as_i64()) {
+    let sum = if negate {
+      current.checked_sub(by)
+    } else {
+      current.checked_add(by)
+    };
+
+    if let Some(sum) = sum {
+      return Ok(Value::from(sum));
+    }
+  }
+
+  let current = current.as_f64().unwrap_or_default();
+  let by = by.as_f64().unwrap_or_default();
+  let sum = if negate { current - by } else { current + by };
+
+  match Number::from_f64(sum) {
+    Some(sum) => Ok(Value::Number(sum)),
+    None => io_err!(InvalidInput, "result is not a finite number"),
+  }
+}
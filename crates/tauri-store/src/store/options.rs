@@ -12,6 +12,10 @@ pub struct StoreOptions {
   pub save_on_exit: Option<bool>,
   pub save_on_change: Option<bool>,
   pub save_strategy: Option<SaveStrategy>,
+  /// Maximum amount of changes that can be undone. Zero disables the history.
+  pub history_depth: Option<usize>,
+  /// Whether the store can be unloaded by the collection's eviction policy.
+  pub evictable: Option<bool>,
 }
 
 impl<R, C> From<&Store<R, C>> for StoreOptions
@@ -24,6 +28,8 @@ where
       save_on_exit: Some(store.save_on_exit),
       save_on_change: Some(store.save_on_change),
       save_strategy: store.save_strategy,
+      history_depth: Some(store.history.depth()),
+      evictable: Some(store.evictable),
     }
   }
 }
@@ -45,4 +51,12 @@ where
   if let Some(strategy) = options.save_strategy {
     store.set_save_strategy(strategy);
   }
+
+  if let Some(depth) = options.history_depth {
+    store.history.set_depth(depth);
+  }
+
+  if let Some(enabled) = options.evictable {
+    store.evictable = enabled;
+  }
 }
@@ -0,0 +1,108 @@
+use crate::error::Result;
+use crate::io_err;
+use serde_json::{Map, Value};
+
+/// Splits a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) into the key
+/// of the store and the pointer to the value inside of it, which may be empty.
+pub(crate) fn split(pointer: &str) -> Result<(String, &str)> {
+  let Some(path) = pointer.strip_prefix('/') else {
+    return io_err!(InvalidInput, "invalid json pointer: {pointer}");
+  };
+
+  let (key, rest) = match path.find('/') {
+    Some(index) => path.split_at(index),
+    None => (path, ""),
+  };
+
+  Ok((unescape(key), rest))
+}
+
+/// Sets the value at the pointer, creating the intermediate objects that don't exist.
+pub(crate) fn set(target: &mut Value, pointer: &str, value: Value) -> Result<()> {
+  let (parent, token) = split_last(pointer);
+  let parent = traverse(target, parent)?;
+  if parent.is_null() {
+    *parent = Value::Object(Map::new());
+  }
+
+  match parent {
+    Value::Object(object) => {
+      object.insert(token, value);
+    }
+    Value::Array(array) => {
+      // As in JSON Patch, `-` refers to the position after the last element.
+      let index = if token == "-" {
+        array.len()
+      } else {
+        index(&token, array.len() + 1)?
+      };
+
+      if index == array.len() {
+        array.push(value);
+      } else {
+        array[index] = value;
+      }
+    }
+    _ => {
+      return io_err!(
+        InvalidInput,
+        "cannot set a property of a primitive: {pointer}"
+      )
+    }
+  }
+
+  Ok(())
+}
+
+/// Removes the value at the pointer, returning it, if any.
+pub(crate) fn remove(target: &mut Value, pointer: &str) -> Option<Value> {
+  let (parent, token) = split_last(pointer);
+  match target.pointer_mut(parent)? {
+    #[cfg(feature = "preserve-order")]
+    Value::Object(object) => object.shift_remove(&token),
+    #[cfg(not(feature = "preserve-order"))]
+    Value::Object(object) => object.remove(&token),
+    Value::Array(array) => {
+      let index = index(&token, array.len()).ok()?;
+      Some(array.remove(index))
+    }
+    _ => None,
+  }
+}
+
+/// Goes down the pointer, creating the objects that don't exist along the way.
+fn traverse<'a>(mut target: &'a mut Value, pointer: &str) -> Result<&'a mut Value> {
+  for token in pointer.split('/').skip(1).map(unescape) {
+    if target.is_null() {
+      *target = Value::Object(Map::new());
+    }
+
+    target = match target {
+      Value::Object(object) => object.entry(token).or_insert(Value::Null),
+      Value::Array(array) => {
+        let index = index(&token, array.len())?;
+        &mut array[index]
+      }
+      _ => return io_err!(InvalidInput, "cannot traverse a primitive: {pointer}"),
+    };
+  }
+
+  Ok(target)
+}
+
+fn split_last(pointer: &str) -> (&str, String) {
+  let index = pointer.rfind('/').unwrap_or_default();
+  (&pointer[..index], unescape(&pointer[index + 1..]))
+}
+
+/// Parses the index of an array, which must be less than `len`.
+fn index(token: &str, len: usize) -> Result<usize> {
+  match token.parse::<usize>() {
+    Ok(index) if index < len => Ok(index),
+    _ => io_err!(InvalidInput, "invalid array index: {token}"),
+  }
+}
+
+fn unescape(token: &str) -> String {
+  token.replace("~1", "/").replace("~0", "~")
+}
@@ -1,15 +1,31 @@
+use super::save::PendingSave;
 use super::{ResourceTuple, Store};
 use crate::collection::CollectionMarker;
 use crate::error::Result;
-use std::sync::{Arc, Mutex};
+use crate::manager::ManagerExt;
+use std::cell::Cell;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
 use tauri::{AppHandle, Manager, Resource, ResourceId, Runtime};
 
+thread_local! {
+  /// Amount of stores locked by the current thread.
+  static HELD: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Whether the current thread holds the lock on any store.
+pub(crate) fn is_holding_store() -> bool {
+  HELD.with(|held| held.get() > 0)
+}
+
 pub(crate) struct StoreResource<R, C>
 where
   R: Runtime,
   C: CollectionMarker,
 {
   pub(crate) inner: Mutex<Store<R, C>>,
+  last_access: Mutex<Instant>,
 }
 
 impl<R, C> StoreResource<R, C>
@@ -18,7 +34,26 @@ where
   C: CollectionMarker,
 {
   fn new(store: Store<R, C>) -> Self {
-    Self { inner: Mutex::new(store) }
+    Self {
+      inner: Mutex::new(store),
+      last_access: Mutex::new(Instant::now()),
+    }
+  }
+
+  /// Records that the store has just been accessed.
+  pub(crate) fn touch(&self) {
+    *self
+      .last_access
+      .lock()
+      .expect("last access is poisoned") = Instant::now();
+  }
+
+  /// When the store was last accessed.
+  pub(crate) fn last_access(&self) -> Instant {
+    *self
+      .last_access
+      .lock()
+      .expect("last access is poisoned")
   }
 
   pub(super) fn create(app: &AppHandle<R>, store: Store<R, C>) -> ResourceTuple<R, C> {
@@ -46,23 +81,147 @@ where
       .map_err(Into::into)
   }
 
-  pub(crate) fn save(app: &AppHandle<R>, rid: ResourceId) -> Result<()> {
-    Self::get(app, rid)?.locked(|store| store.save())
+  /// Saves the store according to its save strategy.
+  ///
+  /// If the strategy is immediate, the store is only locked while its state is serialized.
+  pub(crate) fn save(&self) -> Result<()> {
+    let pending = self.locked(|store| {
+      if store.save_strategy().is_immediate() {
+        store.begin_save(false)
+      } else {
+        store.save().map(|()| None)
+      }
+    })?;
+
+    self.finish_save(pending)
   }
 
-  pub(crate) fn save_now(app: &AppHandle<R>, rid: ResourceId) -> Result<()> {
-    Self::get(app, rid)?.locked(|store| {
+  /// Saves the store immediately, only locking it while its state is serialized.
+  pub(crate) fn save_now(&self, force: bool) -> Result<()> {
+    let pending = self.locked(|store| {
       store.abort_pending_save();
-      store.save_now()
-    })
+      store.begin_save(force)
+    })?;
+
+    self.finish_save(pending)
+  }
+
+  /// Saves the store, then deletes every blob that it no longer references,
+  /// returning how many of them were deleted.
+  ///
+  /// The store is released while its file is written, so any blob referenced by the saved state
+  /// is kept, even if it's no longer referenced once the store is locked again.
+  pub(crate) fn collect_blobs(&self) -> Result<usize> {
+    let (pending, saved) = self.locked(|store| {
+      store.abort_pending_save();
+      let saved = store.blob_refs();
+      store
+        .begin_save(false)
+        .map(|pending| (pending, saved))
+    })?;
+
+    self.finish_save(pending)?;
+    self.locked(|store| store.sweep_blobs(saved))
+  }
+
+  /// Waits for a save started by [`Store::begin_save`] without holding the lock,
+  /// then locks the store again to discard the log it has made redundant.
+  pub(crate) fn finish_save(&self, pending: Option<PendingSave>) -> Result<()> {
+    if let Some(pending) = pending {
+      let len = pending.wait()?;
+      self.locked(|store| store.journal.compact(len))?;
+    }
+
+    Ok(())
+  }
+
+  /// Locks the store, returning a guard that releases the lock when dropped.
+  ///
+  /// Any key whose time-to-live has elapsed is removed before the guard is returned.
+  pub(crate) fn lock(&self) -> StoreGuard<'_, R, C> {
+    let mut store = StoreGuard::new(self.inner.lock().unwrap());
+    let _ = store.purge_expired();
+    store
   }
 
   /// Locks the store and then calls the provided closure with a mutable reference to it.
+  ///
+  /// Once the lock is released, any save started by the closure is waited for,
+  /// and the children of the store receive its new state.
   pub(crate) fn locked<F, T>(&self, f: F) -> T
   where
     F: FnOnce(&mut Store<R, C>) -> T,
   {
-    f(&mut *self.inner.lock().unwrap())
+    let mut store = self.lock();
+    let value = f(&mut store);
+    let pending = store.take_deferred_save();
+
+    let collection = store.app.store_collection_with_marker::<C>();
+    let app = (!collection.inherit_queue.is_empty()).then(|| store.app.clone());
+    drop(store);
+
+    // If the write fails, the store stays dirty and is saved again later.
+    let _ = self.finish_save(pending);
+
+    if let Some(app) = app {
+      app
+        .store_collection_with_marker::<C>()
+        .flush_children();
+    }
+
+    value
+  }
+}
+
+/// Exclusive access to a store, keeping track of how many stores the current thread holds.
+pub(crate) struct StoreGuard<'a, R, C>
+where
+  R: Runtime,
+  C: CollectionMarker,
+{
+  guard: MutexGuard<'a, Store<R, C>>,
+}
+
+impl<'a, R, C> StoreGuard<'a, R, C>
+where
+  R: Runtime,
+  C: CollectionMarker,
+{
+  fn new(guard: MutexGuard<'a, Store<R, C>>) -> Self {
+    HELD.with(|held| held.set(held.get() + 1));
+    Self { guard }
+  }
+}
+
+impl<R, C> Deref for StoreGuard<'_, R, C>
+where
+  R: Runtime,
+  C: CollectionMarker,
+{
+  type Target = Store<R, C>;
+
+  fn deref(&self) -> &Self::Target {
+    &self.guard
+  }
+}
+
+impl<R, C> DerefMut for StoreGuard<'_, R, C>
+where
+  R: Runtime,
+  C: CollectionMarker,
+{
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.guard
+  }
+}
+
+impl<R, C> Drop for StoreGuard<'_, R, C>
+where
+  R: Runtime,
+  C: CollectionMarker,
+{
+  fn drop(&mut self) {
+    HELD.with(|held| held.set(held.get() - 1));
   }
 }
 
@@ -1,4 +1,6 @@
 use super::StoreId;
+use crate::collection::writer::WriteTicket;
+use crate::error::Result;
 use crate::manager::ManagerExt;
 use crate::CollectionMarker;
 use futures::future::BoxFuture;
@@ -58,10 +60,12 @@ where
     let id = id.clone();
     Box::pin(async move {
       let task = spawn_blocking(move || {
-        app
+        let resource = app
           .store_collection_with_marker::<C>()
-          .get_resource(&id)?
-          .locked(|store| store.save_now())
+          .get_resource(&id)?;
+
+        let pending = resource.locked(|store| store.begin_save(false))?;
+        resource.finish_save(pending)
       });
 
       let _ = task.await;
@@ -69,6 +73,24 @@ where
   })
 }
 
+/// A save whose file may still be waiting to be written by the collection's writer thread.
+pub(crate) struct PendingSave {
+  pub(super) ticket: Option<WriteTicket>,
+  /// Length of the log when the state was snapshotted.
+  pub(super) journal_len: usize,
+}
+
+impl PendingSave {
+  /// Blocks until the file is written, returning the length of the log it has made redundant.
+  pub(crate) fn wait(self) -> Result<usize> {
+    if let Some(ticket) = self.ticket {
+      ticket.wait()?;
+    }
+
+    Ok(self.journal_len)
+  }
+}
+
 /// The strategy to use when saving a store.
 ///
 /// For a detailed explanation of the differences between debouncing and throttling,
@@ -111,6 +133,12 @@ impl SaveStrategy {
     Self::Throttle(Duration::from_secs(secs))
   }
 
+  /// Whether the strategy is [`Immediate`](SaveStrategy::Immediate).
+  #[inline]
+  pub const fn is_immediate(&self) -> bool {
+    matches!(self, Self::Immediate)
+  }
+
   /// Whether the strategy is [`Debounce`](SaveStrategy::Debounce).
   #[inline]
   pub const fn is_debounce(&self) -> bool {
@@ -0,0 +1,238 @@
+use super::{Marshaler, StoreState};
+use crate::error::{Error, Result};
+use crate::io::{remove_file, write_file};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Longest name a shard file can have before its extension,
+/// leaving enough room under the limit of 255 bytes most file systems have.
+const MAX_NAME_LEN: usize = 200;
+
+/// Separates the beginning of a long key from its hash in the name of its shard.
+/// Keys never contain it once encoded, so it can't be mistaken for part of one.
+const HASH_SEPARATOR: char = '~';
+
+/// Keys of a sharded store that changed since it was last saved.
+#[derive(Debug, Default)]
+pub(crate) struct ShardTracker(Mutex<Changes>);
+
+#[derive(Debug, Default)]
+struct Changes {
+  keys: HashSet<String>,
+  all: bool,
+}
+
+impl ShardTracker {
+  pub(crate) fn mark<I, K>(&self, keys: I)
+  where
+    I: IntoIterator<Item = K>,
+    K: Into<String>,
+  {
+    let mut changes = self.lock();
+    if !changes.all {
+      changes
+        .keys
+        .extend(keys.into_iter().map(Into::into));
+    }
+  }
+
+  /// Marks every key as changed, including those that only exist on the disk.
+  pub(crate) fn mark_all(&self) {
+    let mut changes = self.lock();
+    changes.keys.clear();
+    changes.all = true;
+  }
+
+  /// Writes the keys that have changed, removing the files of those that no longer exist.
+  pub(crate) fn flush(
+    &self,
+    dir: &Path,
+    marshaler: &dyn Marshaler,
+    state: &StoreState,
+  ) -> Result<()> {
+    let mut changes = self.lock();
+    if changes.all {
+      write_all(dir, marshaler, state)?;
+    } else {
+      write_keys(dir, marshaler, state, changes.keys.iter())?;
+    }
+
+    *changes = Changes::default();
+
+    Ok(())
+  }
+
+  fn lock(&self) -> std::sync::MutexGuard<'_, Changes> {
+    self.0.lock().expect("shard tracker is poisoned")
+  }
+}
+
+/// Reads every key saved in the directory.
+pub(crate) fn read(dir: &Path, marshaler: &dyn Marshaler) -> Result<StoreState> {
+  let mut state = StoreState::new();
+  for (_, path) in scan(dir, marshaler)? {
+    let bytes = fs::read(path)?;
+    let shard = marshaler
+      .deserialize(&bytes)
+      .map_err(Error::FailedToDeserialize)?;
+
+    state.patch(shard);
+  }
+
+  Ok(state)
+}
+
+/// Writes every key in the state, removing the files of keys that are not in it.
+pub(crate) fn write_all(dir: &Path, marshaler: &dyn Marshaler, state: &StoreState) -> Result<()> {
+  let removed = scan(dir, marshaler)?
+    .into_iter()
+    .filter(|(key, _)| !state.has(key))
+    .map(|(key, _)| key)
+    .collect::<Vec<_>>();
+
+  write_keys(dir, marshaler, state, state.keys().chain(&removed))
+}
+
+fn write_keys<'a, I>(
+  dir: &Path,
+  marshaler: &dyn Marshaler,
+  state: &StoreState,
+  keys: I,
+) -> Result<()>
+where
+  I: Iterator<Item = &'a String>,
+{
+  for key in keys {
+    let path = path_of(dir, key, marshaler.extension());
+    if let Some(value) = state.get_raw(key) {
+      let shard = StoreState::from([(key.as_str(), value.clone())]);
+      let bytes = marshaler
+        .serialize(&shard)
+        .map_err(Error::FailedToSerialize)?;
+
+      write_file(&path, &bytes)?;
+    } else {
+      remove_file(&path)?;
+    }
+  }
+
+  Ok(())
+}
+
+/// Lists the keys saved in the directory, along with the paths to their files.
+fn scan(dir: &Path, marshaler: &dyn Marshaler) -> Result<Vec<(String, PathBuf)>> {
+  let entries = match fs::read_dir(dir) {
+    Ok(entries) => entries,
+    Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+    Err(err) => return Err(err.into()),
+  };
+
+  let suffix = format!(".{}", marshaler.extension());
+  let mut keys = Vec::new();
+  for entry in entries {
+    let path = entry?.path();
+    if !path.is_file() {
+      continue;
+    }
+
+    let Some(name) = path
+      .file_name()
+      .and_then(|name| name.to_str())
+      .and_then(|name| name.strip_suffix(&suffix))
+    else {
+      continue;
+    };
+
+    // A hashed name doesn't have the whole key, but the shard itself does.
+    let key = if name.contains(HASH_SEPARATOR) {
+      read_key(&path, marshaler)?
+    } else {
+      decode(name)
+    };
+
+    if let Some(key) = key {
+      keys.push((key, path));
+    }
+  }
+
+  Ok(keys)
+}
+
+fn read_key(path: &Path, marshaler: &dyn Marshaler) -> Result<Option<String>> {
+  let bytes = fs::read(path)?;
+  let shard = marshaler
+    .deserialize(&bytes)
+    .map_err(Error::FailedToDeserialize)?;
+
+  let key = shard.keys().next().cloned();
+  Ok(key)
+}
+
+fn path_of(dir: &Path, key: &str, extension: &str) -> PathBuf {
+  dir.join(format!("{}.{extension}", file_name(key)))
+}
+
+/// Name of the file where the key is saved.
+///
+/// Keys too long to be encoded in full are cut short, followed by their hash.
+fn file_name(key: &str) -> String {
+  let encoded = encode(key);
+  if encoded.len() <= MAX_NAME_LEN {
+    return encoded;
+  }
+
+  let hash = Sha256::digest(key.as_bytes());
+  let mut end = MAX_NAME_LEN - hash.len() * 2 - HASH_SEPARATOR.len_utf8();
+
+  // The name mustn't end in the middle of an escape.
+  if let Some(escape) = encoded[end.saturating_sub(2)..end].find('%') {
+    end = end - 2 + escape;
+  }
+
+  let mut name = encoded[..end].to_owned();
+  name.push(HASH_SEPARATOR);
+  for byte in hash {
+    let _ = write!(name, "{byte:02x}");
+  }
+
+  name
+}
+
+/// Percent-encodes the key so that it can be safely used as a file name.
+///
+/// Uppercase letters are encoded too, so keys that only differ in case
+/// don't share the same file on case-insensitive file systems.
+fn encode(key: &str) -> String {
+  let mut encoded = String::with_capacity(key.len());
+  for byte in key.bytes() {
+    if byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-' || byte == b'_' {
+      encoded.push(char::from(byte));
+    } else {
+      let _ = write!(encoded, "%{byte:02X}");
+    }
+  }
+
+  encoded
+}
+
+/// Decodes a percent-encoded string.
+pub(super) fn decode(name: &str) -> Option<String> {
+  let mut bytes = Vec::with_capacity(name.len());
+  let mut iter = name.bytes();
+  while let Some(byte) = iter.next() {
+    if byte == b'%' {
+      let hex = [iter.next()?, iter.next()?];
+      let hex = std::str::from_utf8(&hex).ok()?;
+      bytes.push(u8::from_str_radix(hex, 16).ok()?);
+    } else {
+      bytes.push(byte);
+    }
+  }
+
+  String::from_utf8(bytes).ok()
+}
@@ -1,3 +1,5 @@
+use super::merge::{diff, merge};
+use super::pointer;
 use crate::error::Result;
 use crate::io_err;
 use serde::de::DeserializeOwned;
@@ -72,6 +74,27 @@ impl StoreState {
     Ok(serde_json::from_value(value)?)
   }
 
+  /// Gets a reference to the raw value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901),
+  /// such as `/settings/editor/fontSize`, where the first token is the key.
+  pub fn get_raw_path(&self, pointer: impl AsRef<str>) -> Option<&Value> {
+    let (key, rest) = pointer::split(pointer.as_ref()).ok()?;
+    self.0.get(&key)?.pointer(rest)
+  }
+
+  /// Gets the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901)
+  /// and tries to parse it as an instance of type `T`.
+  pub fn get_path<T>(&self, pointer: impl AsRef<str>) -> Result<T>
+  where
+    T: DeserializeOwned,
+  {
+    let pointer = pointer.as_ref();
+    let Some(value) = self.get_raw_path(pointer).cloned() else {
+      return io_err!(NotFound, "path not found: {pointer}");
+    };
+
+    Ok(serde_json::from_value(value)?)
+  }
+
   /// Gets a value and tries to parse it as an instance of type `T`.
   ///
   /// If it does not exist, returns the provided default value.
@@ -126,6 +149,48 @@ impl StoreState {
     self.0.extend(state.into().0);
   }
 
+  /// Applies a [JSON Merge Patch](https://datatracker.ietf.org/doc/html/rfc7396) to the state.
+  ///
+  /// Unlike [`StoreState::patch`], nested objects are merged instead of replaced,
+  /// and keys whose value in the patch is `null` are removed.
+  pub fn merge_patch(&mut self, patch: impl Into<StoreState>) {
+    for (key, value) in patch.into().0 {
+      if value.is_null() {
+        self.remove(&key);
+      } else {
+        merge(self.0.entry(key).or_insert(Value::Null), value);
+      }
+    }
+  }
+
+  /// Creates a [JSON Merge Patch](https://datatracker.ietf.org/doc/html/rfc7396)
+  /// that turns this state into `other` when applied with [`StoreState::merge_patch`].
+  ///
+  /// As merge patches use `null` to remove keys, values that are set to `null`
+  /// in `other` are removed instead.
+  #[must_use]
+  pub fn diff(&self, other: &StoreState) -> StoreState {
+    let mut patch = StoreState::new();
+    for key in self.0.keys() {
+      if !other.0.contains_key(key) {
+        patch.set(key, Value::Null);
+      }
+    }
+
+    for (key, value) in &other.0 {
+      let change = match self.0.get(key) {
+        Some(old) => diff(old, value),
+        None => Some(value.clone()),
+      };
+
+      if let Some(change) = change {
+        patch.set(key, change);
+      }
+    }
+
+    patch
+  }
+
   /// Whether a key exists.
   pub fn has(&self, key: impl AsRef<str>) -> bool {
     self.0.contains_key(key.as_ref())
@@ -1,18 +1,32 @@
+use super::{pointer, StoreId};
 use crate::error::Result;
+use crate::event::EventSource;
+use serde_json::Value;
+use std::collections::HashSet;
 use std::fmt;
 use std::ops::Deref;
 use std::sync::atomic::AtomicU32;
 use std::sync::atomic::Ordering::Relaxed;
 use std::sync::Arc;
 use tauri::{AppHandle, Runtime};
+use tokio::sync::broadcast;
 
 static CURRENT_ID: AtomicU32 = AtomicU32::new(0);
 
 type WatcherFn<R> = dyn Fn(AppHandle<R>) -> Result<()> + Send + Sync;
+type ChangeWatcherFn<R> = dyn Fn(AppHandle<R>, &StoreChange) -> Result<()> + Send + Sync;
+
+enum Callback<R: Runtime> {
+  Any(Arc<WatcherFn<R>>),
+  Change {
+    keys: Option<Arc<HashSet<String>>>,
+    f: Arc<ChangeWatcherFn<R>>,
+  },
+}
 
 pub(crate) struct Watcher<R: Runtime> {
   id: WatcherId,
-  inner: Arc<WatcherFn<R>>,
+  inner: Callback<R>,
 }
 
 impl<R: Runtime> Watcher<R> {
@@ -20,21 +34,50 @@ impl<R: Runtime> Watcher<R> {
   where
     F: Fn(AppHandle<R>) -> Result<()> + Send + Sync + 'static,
   {
+    Self::with_callback(Callback::Any(Arc::new(f)))
+  }
+
+  /// Creates a watcher that receives the change, optionally only for some keys.
+  pub(crate) fn with_change<F>(keys: Option<HashSet<String>>, f: F) -> (WatcherId, Self)
+  where
+    F: Fn(AppHandle<R>, &StoreChange) -> Result<()> + Send + Sync + 'static,
+  {
+    let keys = keys.map(Arc::new);
+    Self::with_callback(Callback::Change { keys, f: Arc::new(f) })
+  }
+
+  fn with_callback(inner: Callback<R>) -> (WatcherId, Self) {
     let id = WatcherId(CURRENT_ID.fetch_add(1, Relaxed));
-    (id, Self { id, inner: Arc::new(f) })
+    (id, Self { id, inner })
+  }
+
+  /// Whether the watcher should be called for the change.
+  pub(crate) fn matches(&self, change: &StoreChange) -> bool {
+    match &self.inner {
+      Callback::Change { keys: Some(keys), .. } => change
+        .keys
+        .iter()
+        .any(|it| keys.contains(&it.key)),
+      _ => true,
+    }
   }
 
-  pub(crate) fn call(&self, app: AppHandle<R>) {
-    let _ = (self.inner)(app);
+  pub(crate) fn call(&self, app: AppHandle<R>, change: &StoreChange) {
+    let _ = match &self.inner {
+      Callback::Any(f) => f(app),
+      Callback::Change { f, .. } => f(app, change),
+    };
   }
 }
 
 impl<R: Runtime> Clone for Watcher<R> {
   fn clone(&self) -> Self {
-    Self {
-      id: self.id,
-      inner: Arc::clone(&self.inner),
-    }
+    let inner = match &self.inner {
+      Callback::Any(f) => Callback::Any(Arc::clone(f)),
+      Callback::Change { keys, f } => Callback::Change { keys: keys.clone(), f: Arc::clone(f) },
+    };
+
+    Self { id: self.id, inner }
   }
 }
 
@@ -63,3 +106,87 @@ impl From<u32> for WatcherId {
     Self(id)
   }
 }
+
+/// Receives the changes of a store, as returned by
+/// [`StoreCollection::subscribe`](crate::StoreCollection::subscribe).
+pub type ChangeReceiver = broadcast::Receiver<Arc<StoreChange>>;
+
+/// A change to the state of a store.
+#[derive(Clone, Debug)]
+pub struct StoreChange {
+  /// Store that has changed.
+  pub id: StoreId,
+  /// Keys that were set or removed, in the order the changes were made.
+  pub keys: Vec<KeyChange>,
+  /// Window that caused the change, if any.
+  pub source: EventSource,
+  /// Revision of the store after the change.
+  pub revision: u64,
+}
+
+impl StoreChange {
+  /// Keys that were set.
+  pub fn changed(&self) -> impl Iterator<Item = &str> {
+    self
+      .keys
+      .iter()
+      .filter(|it| it.new.is_some())
+      .map(|it| it.key.as_str())
+  }
+
+  /// Keys that were removed.
+  pub fn removed(&self) -> impl Iterator<Item = &str> {
+    self
+      .keys
+      .iter()
+      .filter(|it| it.new.is_none())
+      .map(|it| it.key.as_str())
+  }
+
+  /// Whether the key has changed.
+  pub fn contains(&self, key: impl AsRef<str>) -> bool {
+    self.get(key).is_some()
+  }
+
+  /// Whether the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901),
+  /// such as `/settings/editor/fontSize`, has changed.
+  pub fn contains_path(&self, pointer: impl AsRef<str>) -> bool {
+    let Ok((key, rest)) = pointer::split(pointer.as_ref()) else {
+      return false;
+    };
+
+    self
+      .get(key)
+      .is_some_and(|it| it.old_path(rest) != it.new_path(rest))
+  }
+
+  /// Gets the latest change to the key.
+  pub fn get(&self, key: impl AsRef<str>) -> Option<&KeyChange> {
+    let key = key.as_ref();
+    self.keys.iter().rev().find(|it| it.key == key)
+  }
+}
+
+/// A change to a single key of a store.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyChange {
+  pub key: String,
+  /// Value before the change, or `None` if the key did not exist.
+  pub old: Option<Value>,
+  /// Value after the change, or `None` if the key was removed.
+  pub new: Option<Value>,
+}
+
+impl KeyChange {
+  /// Value before the change at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901)
+  /// relative to the key, where an empty pointer refers to the whole value.
+  pub fn old_path(&self, pointer: impl AsRef<str>) -> Option<&Value> {
+    self.old.as_ref()?.pointer(pointer.as_ref())
+  }
+
+  /// Value after the change at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901)
+  /// relative to the key, where an empty pointer refers to the whole value.
+  pub fn new_path(&self, pointer: impl AsRef<str>) -> Option<&Value> {
+    self.new.as_ref()?.pointer(pointer.as_ref())
+  }
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::{build, temp_dir};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use tauri_store::ManagerExt;
+
+const STORE_ID: &str = "store";
+
+#[test]
+fn put_and_get_blob() {
+  let app = build(&temp_dir("blob", "put-and-get"));
+  let collection = app.store_collection();
+  collection
+    .put_blob(STORE_ID, "key", [1, 2, 3])
+    .unwrap();
+
+  let value = collection.get_raw(STORE_ID, "key").unwrap();
+  assert!(value.get("$blob").is_some_and(Value::is_string));
+
+  let bytes = collection.get_blob(STORE_ID, "key").unwrap();
+  assert_eq!(bytes, Some(vec![1, 2, 3]));
+
+  collection.set(STORE_ID, "other", 42).unwrap();
+  let bytes = collection.get_blob(STORE_ID, "other").unwrap();
+  assert!(bytes.is_none());
+}
+
+#[test]
+fn deduplicate_blobs() {
+  let dir = temp_dir("blob", "deduplicate");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection
+    .put_blob(STORE_ID, "key-1", b"blob")
+    .unwrap();
+  collection
+    .put_blob(STORE_ID, "key-2", b"blob")
+    .unwrap();
+
+  assert_eq!(count_blobs(&dir), 1);
+  assert_eq!(
+    collection.get_raw(STORE_ID, "key-1"),
+    collection.get_raw(STORE_ID, "key-2")
+  );
+}
+
+#[test]
+fn collect_blobs() {
+  let dir = temp_dir("blob", "collect");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection
+    .put_blob(STORE_ID, "key-1", b"blob-1")
+    .unwrap();
+  collection
+    .put_blob(STORE_ID, "key-2", b"blob-2")
+    .unwrap();
+
+  assert!(collection.remove_blob(STORE_ID, "key-1").unwrap());
+  assert!(!collection.remove_blob(STORE_ID, "key-1").unwrap());
+  assert!(collection.get_raw(STORE_ID, "key-1").is_none());
+  assert_eq!(count_blobs(&dir), 2);
+
+  let removed = collection.collect_blobs(STORE_ID).unwrap();
+  assert_eq!(removed, 1);
+  assert_eq!(count_blobs(&dir), 1);
+
+  let bytes = collection.get_blob(STORE_ID, "key-2").unwrap();
+  assert_eq!(bytes.as_deref(), Some(b"blob-2".as_slice()));
+}
+
+#[test]
+fn keep_nested_and_history_refs() {
+  let dir = temp_dir("blob", "nested-and-history");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection
+    .with_store(STORE_ID, |store| store.set_history_depth(8))
+    .unwrap()
+    .unwrap();
+
+  collection
+    .put_blob(STORE_ID, "nested", b"nested")
+    .unwrap();
+  let blob_ref = collection.get_raw(STORE_ID, "nested").unwrap();
+  collection
+    .set(STORE_ID, "profile", json!({ "avatars": [blob_ref] }))
+    .unwrap();
+  collection
+    .put_blob(STORE_ID, "removed", b"removed")
+    .unwrap();
+
+  assert!(collection
+    .remove_blob(STORE_ID, "nested")
+    .unwrap());
+  assert!(collection
+    .remove_blob(STORE_ID, "removed")
+    .unwrap());
+
+  // One is referenced inside another value, and undoing would restore the other.
+  assert_eq!(collection.collect_blobs(STORE_ID).unwrap(), 0);
+  assert_eq!(count_blobs(&dir), 2);
+
+  collection.clear_history(STORE_ID).unwrap();
+  assert_eq!(collection.collect_blobs(STORE_ID).unwrap(), 1);
+  assert_eq!(count_blobs(&dir), 1);
+}
+
+fn count_blobs(dir: &Path) -> usize {
+  fs::read_dir(dir.join(format!("{STORE_ID}.blobs")))
+    .unwrap()
+    .count()
+}
@@ -0,0 +1,65 @@
+#![allow(dead_code)]
+
+use std::env::current_dir;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::test::{mock_app, MockRuntime};
+use tauri::{App, Manager};
+use tauri_store::{DefaultMarker, Handle, ManagerExt, Result, StoreCollection};
+
+pub type Builder = tauri_store::Builder<MockRuntime, DefaultMarker>;
+
+/// Builds a collection in `dir` with the default options, except for debug stores,
+/// so the file names don't depend on the build profile.
+pub fn build(dir: &Path) -> App<MockRuntime> {
+  build_with(dir, |builder| builder)
+}
+
+/// Same as [`build`], but lets the test configure the collection further.
+pub fn build_with(dir: &Path, f: impl FnOnce(Builder) -> Builder) -> App<MockRuntime> {
+  try_build_with(dir, f).unwrap()
+}
+
+/// Same as [`build_with`], but returns the error if the collection can't be built.
+pub fn try_build_with(dir: &Path, f: impl FnOnce(Builder) -> Builder) -> Result<App<MockRuntime>> {
+  let app = mock_app();
+  let handle = Handle::new(app.app_handle().clone());
+  let builder = StoreCollection::<_, DefaultMarker>::builder()
+    .path(dir)
+    .enable_debug_stores(false);
+
+  f(builder).build(handle, env!("CARGO_PKG_NAME"))?;
+
+  Ok(app)
+}
+
+/// Returns the directory of a test, after removing whatever a previous run left there.
+pub fn temp_dir(suite: &str, name: &str) -> PathBuf {
+  let dir = current_dir()
+    .unwrap()
+    .join(".temp")
+    .join(suite)
+    .join(name);
+  let _ = fs::remove_dir_all(&dir);
+  dir
+}
+
+pub fn read(path: &Path) -> serde_json::Value {
+  serde_json::from_slice(&fs::read(path).unwrap()).unwrap()
+}
+
+pub fn sorted_ids(app: &App<MockRuntime>) -> Vec<String> {
+  let mut ids = app
+    .store_collection()
+    .ids()
+    .into_iter()
+    .map(|id| id.to_string())
+    .collect::<Vec<_>>();
+
+  ids.sort();
+  ids
+}
+
+pub fn assert_exists(path: &Path, yes: bool) {
+  assert!(path.try_exists().is_ok_and(|it| it == yes));
+}
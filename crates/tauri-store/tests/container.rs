@@ -0,0 +1,81 @@
+mod common;
+
+use common::{assert_exists, build_with, temp_dir, try_build_with};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::{CollectionLayout, ManagerExt};
+
+#[test]
+fn save_to_container() {
+  let dir = temp_dir("container", "save");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection.set("store-1", "key", 1).unwrap();
+  collection.set("store-2", "key", 2).unwrap();
+  collection.save_all_now().unwrap();
+
+  assert_exists(&dir.join("container.tauristore"), true);
+  assert_exists(&dir.join("store-1.json"), false);
+
+  collection
+    .unload_store(&"store-1".into())
+    .unwrap();
+  let value = collection.get_raw("store-1", "key").unwrap();
+  assert_eq!(value, Value::from(1));
+}
+
+#[test]
+fn convert_layout() {
+  let dir = temp_dir("container", "convert");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection.set("store-1", "key", 1).unwrap();
+  collection.set("store-2", "key", 2).unwrap();
+
+  let container = dir.join("container.tauristore");
+
+  collection
+    .convert_layout(CollectionLayout::File)
+    .unwrap();
+
+  assert_eq!(collection.layout(), CollectionLayout::File);
+  assert_exists(&container, false);
+  assert_exists(&dir.join("store-1.json"), true);
+  assert_exists(&dir.join("store-2.json"), true);
+
+  collection
+    .unload_store(&"store-2".into())
+    .unwrap();
+  collection
+    .convert_layout(CollectionLayout::Container)
+    .unwrap();
+
+  assert_eq!(collection.layout(), CollectionLayout::Container);
+  assert_exists(&container, true);
+  assert_exists(&dir.join("store-1.json"), false);
+  assert_exists(&dir.join("store-2.json"), false);
+
+  let value = collection.get_raw("store-2", "key").unwrap();
+  assert_eq!(value, Value::from(2));
+}
+
+#[test]
+fn reject_unknown_version() {
+  let dir = temp_dir("container", "unknown-version");
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(
+    dir.join("container.tauristore"),
+    r#"{ "version": 2, "stores": {} }"#,
+  )
+  .unwrap();
+
+  let result = try_build_with(&dir, |builder| builder.layout(CollectionLayout::Container));
+  assert!(result.is_err());
+}
+
+fn build(dir: &Path) -> App<MockRuntime> {
+  build_with(dir, |builder| builder.layout(CollectionLayout::Container))
+}
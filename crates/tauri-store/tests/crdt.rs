@@ -0,0 +1,199 @@
+mod common;
+
+use common::{build_with, temp_dir};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::ManagerExt;
+
+const STORE_ID: &str = "store";
+
+#[test]
+fn merge_concurrent_changes() {
+  let a = build(&temp_dir("crdt", "merge").join("a"));
+  let b = build(&temp_dir("crdt", "merge").join("b"));
+  let a = a.store_collection();
+  let b = b.store_collection();
+
+  a.set(
+    STORE_ID,
+    "settings",
+    json!({ "theme": "dark", "lang": "en" }),
+  )
+  .unwrap();
+
+  b.import_update(STORE_ID, a.export_update(STORE_ID, None).unwrap())
+    .unwrap();
+
+  // Each replica changes a different field of the same object.
+  a.set(
+    STORE_ID,
+    "settings",
+    json!({ "theme": "light", "lang": "en" }),
+  )
+  .unwrap();
+
+  b.set(
+    STORE_ID,
+    "settings",
+    json!({ "theme": "dark", "lang": "pt" }),
+  )
+  .unwrap();
+
+  let from_a = a.export_update(STORE_ID, None).unwrap();
+  let from_b = b.export_update(STORE_ID, None).unwrap();
+  a.import_update(STORE_ID, from_b).unwrap();
+  b.import_update(STORE_ID, from_a).unwrap();
+
+  let expected = json!({ "theme": "light", "lang": "pt" });
+  assert_eq!(a.get_raw(STORE_ID, "settings"), Some(expected.clone()));
+  assert_eq!(b.get_raw(STORE_ID, "settings"), Some(expected));
+}
+
+#[test]
+fn merge_removals() {
+  let a = build(&temp_dir("crdt", "remove").join("a"));
+  let b = build(&temp_dir("crdt", "remove").join("b"));
+  let a = a.store_collection();
+  let b = b.store_collection();
+
+  a.set(STORE_ID, "key", json!({ "foo": 1, "bar": 2 }))
+    .unwrap();
+
+  b.import_update(STORE_ID, a.export_update(STORE_ID, None).unwrap())
+    .unwrap();
+
+  let clock = a.crdt_clock(STORE_ID).unwrap();
+  a.set(STORE_ID, "key", json!({ "bar": 2 }))
+    .unwrap();
+  b.set(STORE_ID, "key", json!({ "foo": 1, "bar": 2, "baz": 3 }))
+    .unwrap();
+
+  // Only the changes made after the clock are exported.
+  let update = a.export_update(STORE_ID, Some(clock)).unwrap();
+  b.import_update(STORE_ID, &update).unwrap();
+  b.import_update(STORE_ID, &update).unwrap();
+
+  let expected = json!({ "bar": 2, "baz": 3 });
+  assert_eq!(b.get_raw(STORE_ID, "key"), Some(expected));
+}
+
+#[test]
+fn persist_crdt() {
+  let dir = temp_dir("crdt", "persist");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  assert!(collection.is_crdt(STORE_ID));
+
+  collection.set(STORE_ID, "key", 1).unwrap();
+  collection.save_now(STORE_ID).unwrap();
+  let update = collection.export_update(STORE_ID, None).unwrap();
+  assert!(dir.join(format!("{STORE_ID}.crdt")).is_file());
+
+  let app = build(&dir);
+  let collection = app.store_collection();
+  assert_eq!(collection.export_update(STORE_ID, None).unwrap(), update);
+}
+
+#[test]
+fn keep_replica_id() {
+  let dir = temp_dir("crdt", "keep-replica-id");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection.set(STORE_ID, "a", 1).unwrap();
+  collection.save_now(STORE_ID).unwrap();
+
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection.set(STORE_ID, "b", 2).unwrap();
+
+  let replicas = entries(&collection.export_update(STORE_ID, None).unwrap())
+    .iter()
+    .map(|entry| entry["replica"].clone())
+    .collect::<HashSet<_>>();
+
+  assert_eq!(replicas.len(), 1);
+}
+
+#[test]
+fn export_by_received_time() {
+  let dir = temp_dir("crdt", "export-by-received-time");
+  let a = build(&dir.join("a"));
+  let b = build(&dir.join("b"));
+  let a = a.store_collection();
+  let b = b.store_collection();
+
+  // The change is older than the clock of the replica that relays it.
+  b.set(STORE_ID, "key", 1).unwrap();
+  thread::sleep(Duration::from_millis(5));
+  a.set(STORE_ID, "other", 2).unwrap();
+
+  let clock = a.crdt_clock(STORE_ID).unwrap();
+  a.import_update(STORE_ID, b.export_update(STORE_ID, None).unwrap())
+    .unwrap();
+
+  let update = a.export_update(STORE_ID, Some(clock)).unwrap();
+  let paths = entries(&update)
+    .iter()
+    .map(|entry| entry["path"].clone())
+    .collect::<Vec<_>>();
+
+  assert_eq!(paths, [json!(["key"])]);
+}
+
+#[test]
+fn compact_entries() {
+  let dir = temp_dir("crdt", "compact");
+  let sidecar = json!({
+    "replica": "replica",
+    "entries": [
+      { "path": ["removed"], "ts": 1, "replica": "replica", "op": "remove" },
+      { "path": ["key"], "ts": 1, "replica": "replica", "op": "set", "value": 1 },
+    ]
+  });
+
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(dir.join(format!("{STORE_ID}.json")), r#"{ "key": 1 }"#).unwrap();
+  fs::write(dir.join(format!("{STORE_ID}.crdt")), sidecar.to_string()).unwrap();
+
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection
+    .set(STORE_ID, "nested", json!({ "foo": 1 }))
+    .unwrap();
+  collection.set(STORE_ID, "nested", 2).unwrap();
+  collection.save_now(STORE_ID).unwrap();
+
+  // Old removals are forgotten, and so is whatever a newer entry of an ancestor replaced.
+  let app = build(&dir);
+  let collection = app.store_collection();
+  let mut paths = entries(&collection.export_update(STORE_ID, None).unwrap())
+    .iter()
+    .map(|entry| entry["path"].clone())
+    .collect::<Vec<_>>();
+
+  paths.sort_by_key(ToString::to_string);
+  assert_eq!(paths, [json!(["key"]), json!(["nested"])]);
+}
+
+#[test]
+fn reject_non_crdt_store() {
+  let app = build(&temp_dir("crdt", "reject"));
+  let collection = app.store_collection();
+  assert!(collection.export_update("other", None).is_err());
+  assert!(collection.import_update("other", b"{}").is_err());
+}
+
+fn build(dir: &Path) -> App<MockRuntime> {
+  build_with(dir, |builder| builder.crdt([STORE_ID]))
+}
+
+fn entries(update: &[u8]) -> Vec<Value> {
+  let update: Value = serde_json::from_slice(update).unwrap();
+  update["entries"].as_array().unwrap().clone()
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::{build_with, read, temp_dir};
+use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::ManagerExt;
+
+#[derive(Serialize)]
+struct Settings {
+  theme: String,
+  volume: u8,
+}
+
+#[test]
+fn seed_defaults() {
+  let dir = temp_dir("defaults", "seed-defaults");
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(dir.join("store.json"), r#"{ "theme": "dark" }"#).unwrap();
+
+  let app = build(&dir);
+  let collection = app.store_collection();
+  assert_eq!(collection.get_raw("store", "theme"), Some(json!("dark")));
+  assert_eq!(collection.get_raw("store", "volume"), Some(json!(50)));
+}
+
+#[test]
+fn save_seeded_defaults() {
+  let dir = temp_dir("defaults", "save-seeded-defaults");
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(dir.join("store.json"), r#"{ "theme": "dark" }"#).unwrap();
+
+  let app = build(&dir);
+  let collection = app.store_collection();
+  assert!(collection.is_dirty("store").unwrap());
+
+  collection.save_all_now().unwrap();
+  assert_eq!(
+    read(&dir.join("store.json")),
+    json!({ "theme": "dark", "volume": 50 })
+  );
+}
+
+#[test]
+fn save_seeded_defaults_of_sharded_store() {
+  let dir = temp_dir("defaults", "save-seeded-defaults-of-sharded-store");
+  let settings = Settings {
+    theme: String::from("light"),
+    volume: 50,
+  };
+
+  let app = build_with(&dir, |builder| {
+    builder
+      .defaults_of("store", settings)
+      .shard(["store"])
+  });
+
+  let collection = app.store_collection();
+  assert!(collection.is_dirty("store").unwrap());
+
+  collection.save_all_now().unwrap();
+  assert_eq!(
+    read(&dir.join("store").join("theme.json")),
+    json!({ "theme": "light" })
+  );
+  assert_eq!(
+    read(&dir.join("store").join("volume.json")),
+    json!({ "volume": 50 })
+  );
+}
+
+#[test]
+fn reset() {
+  let app = build(&temp_dir("defaults", "reset"));
+  let collection = app.store_collection();
+  collection.set("store", "theme", "dark").unwrap();
+  collection.set("store", "volume", 10).unwrap();
+  collection.set("store", "extra", true).unwrap();
+
+  collection.reset_key("store", "theme").unwrap();
+  assert_eq!(collection.get_raw("store", "theme"), Some(json!("light")));
+
+  collection.reset_key("store", "extra").unwrap();
+  assert!(collection.get_raw("store", "extra").is_none());
+
+  collection.set("store", "extra", true).unwrap();
+  collection.reset("store").unwrap();
+
+  let state = collection.raw_state("store").unwrap();
+  assert_eq!(
+    serde_json::to_value(state).unwrap(),
+    json!({ "theme": "light", "volume": 50 })
+  );
+}
+
+fn build(dir: &Path) -> App<MockRuntime> {
+  let settings = Settings {
+    theme: String::from("light"),
+    volume: 50,
+  };
+
+  build_with(dir, |builder| builder.defaults_of("store", settings))
+}
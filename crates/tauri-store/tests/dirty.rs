@@ -0,0 +1,87 @@
+mod common;
+
+use common::{build, build_with, read, temp_dir};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::ManagerExt;
+
+#[test]
+fn dirty_until_saved() {
+  let dir = temp_dir("dirty", "dirty-until-saved");
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(dir.join("store.json"), r#"{ "key": 1 }"#).unwrap();
+
+  let app = build(&dir);
+  let collection = app.store_collection();
+  assert!(!collection.is_dirty("store").unwrap());
+
+  collection.set("store", "key", 2).unwrap();
+  assert!(collection.is_dirty("store").unwrap());
+
+  collection.save_now("store").unwrap();
+  assert!(!collection.is_dirty("store").unwrap());
+  assert_eq!(read(&dir.join("store.json")), json!({ "key": 2 }));
+
+  let app = build(&dir);
+  let collection = app.store_collection();
+  assert_eq!(collection.get_raw("store", "key"), Some(json!(2)));
+  assert!(!collection.is_dirty("store").unwrap());
+}
+
+#[test]
+fn skip_clean_stores() {
+  let dir = temp_dir("dirty", "skip-clean-stores");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection.set("clean", "key", 1).unwrap();
+  collection.set("dirty", "key", 1).unwrap();
+  collection.save_all_now().unwrap();
+
+  fs::remove_file(dir.join("clean.json")).unwrap();
+  collection.set("dirty", "key", 2).unwrap();
+  collection.save_all_now().unwrap();
+
+  assert!(!dir.join("clean.json").exists());
+  assert_eq!(read(&dir.join("dirty.json")), json!({ "key": 2 }));
+
+  collection.force_save_now("clean").unwrap();
+  assert_eq!(read(&dir.join("clean.json")), json!({ "key": 1 }));
+
+  let app = build(&dir);
+  let collection = app.store_collection();
+  assert_eq!(collection.get_raw("clean", "key"), Some(json!(1)));
+  assert_eq!(collection.get_raw("dirty", "key"), Some(json!(2)));
+}
+
+#[test]
+fn dirty_after_import() {
+  let dir = temp_dir("dirty", "dirty-after-import");
+  let source = build_crdt(&dir.join("source"));
+  let source = source.store_collection();
+  source.set("store", "key", 1).unwrap();
+  let update = source.export_update("store", None).unwrap();
+
+  let replica = dir.join("replica");
+  let app = build_crdt(&replica);
+  let collection = app.store_collection();
+  assert!(!collection.is_dirty("store").unwrap());
+
+  collection.import_update("store", update).unwrap();
+  assert!(collection.is_dirty("store").unwrap());
+
+  collection.save_now("store").unwrap();
+  assert!(!collection.is_dirty("store").unwrap());
+  assert_eq!(read(&replica.join("store.json")), json!({ "key": 1 }));
+
+  let app = build_crdt(&replica);
+  let collection = app.store_collection();
+  assert_eq!(collection.get_raw("store", "key"), Some(json!(1)));
+  assert!(!collection.is_dirty("store").unwrap());
+}
+
+fn build_crdt(dir: &Path) -> App<MockRuntime> {
+  build_with(dir, |builder| builder.crdt(["store"]))
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use common::{build_with, read, sorted_ids, temp_dir};
+use serde_json::json;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::{EvictionPolicy, ManagerExt};
+
+#[test]
+fn max_stores() {
+  let dir = temp_dir("eviction", "max-stores");
+  let app = build(&dir, EvictionPolicy::new().max_stores(2));
+  let collection = app.store_collection();
+  collection.set("a", "key", 1).unwrap();
+  collection.set("b", "key", 2).unwrap();
+  collection.set("a", "key", 3).unwrap();
+  collection.set("c", "key", 4).unwrap();
+
+  // The least recently used store is evicted, and saved before that.
+  assert_eq!(sorted_ids(&app), ["a", "c"]);
+  assert_eq!(read(&dir.join("b.json")), json!({ "key": 2 }));
+  assert_eq!(collection.get_raw("b", "key"), Some(json!(2)));
+}
+
+#[test]
+fn max_bytes() {
+  let dir = temp_dir("eviction", "max-bytes");
+  let app = build(&dir, EvictionPolicy::new().max_bytes(64));
+  let collection = app.store_collection();
+  collection
+    .set("a", "key", "a".repeat(40))
+    .unwrap();
+  collection
+    .set("b", "key", "b".repeat(40))
+    .unwrap();
+
+  // The size is only known after the values are set, so nothing was evicted yet.
+  assert_eq!(sorted_ids(&app), ["a", "b"]);
+
+  collection.evict().unwrap();
+  assert_eq!(sorted_ids(&app), ["b"]);
+  assert_eq!(read(&dir.join("a.json")), json!({ "key": "a".repeat(40) }));
+}
+
+#[test]
+fn idle_timeout() {
+  let dir = temp_dir("eviction", "idle-timeout");
+  let app = build(
+    &dir,
+    EvictionPolicy::new().idle_timeout(Duration::from_millis(50)),
+  );
+  let collection = app.store_collection();
+  collection.set("a", "key", 1).unwrap();
+  collection.set("b", "key", 2).unwrap();
+
+  collection
+    .with_store("b", |store| store.evictable(false))
+    .unwrap();
+
+  thread::sleep(Duration::from_millis(100));
+  collection.evict().unwrap();
+
+  assert_eq!(sorted_ids(&app), ["b"]);
+  assert_eq!(read(&dir.join("a.json")), json!({ "key": 1 }));
+}
+
+#[test]
+fn keep_stores_in_use() {
+  let dir = temp_dir("eviction", "keep-stores-in-use");
+  let app = build(
+    &dir,
+    EvictionPolicy::new().idle_timeout(Duration::from_millis(50)),
+  );
+  let collection = app.store_collection();
+  for id in ["watched", "subscribed", "history", "idle"] {
+    collection.set(id, "key", 1).unwrap();
+  }
+
+  collection
+    .with_store("watched", |store| store.watch(|_| Ok(())))
+    .unwrap();
+
+  let _receiver = collection.subscribe("subscribed");
+
+  collection
+    .with_store("history", |store| {
+      store.set_history_depth(8)?;
+      store.set("key", 2)
+    })
+    .unwrap()
+    .unwrap();
+
+  thread::sleep(Duration::from_millis(100));
+  collection.evict().unwrap();
+
+  assert_eq!(sorted_ids(&app), ["history", "subscribed", "watched"]);
+  assert_eq!(read(&dir.join("idle.json")), json!({ "key": 1 }));
+}
+
+#[test]
+fn keep_changes_made_while_evicting() {
+  let dir = temp_dir("eviction", "keep-changes-while-evicting");
+  let app = build(&dir, EvictionPolicy::new().idle_timeout(Duration::ZERO));
+  let collection = &*app.store_collection();
+
+  thread::scope(|scope| {
+    scope.spawn(|| {
+      for _ in 0..2000 {
+        collection.evict().unwrap();
+      }
+    });
+
+    for value in 0..2000 {
+      collection.set("store", "key", value).unwrap();
+      assert_eq!(collection.get_raw("store", "key"), Some(json!(value)));
+    }
+  });
+
+  collection.evict().unwrap();
+  assert_eq!(read(&dir.join("store.json")), json!({ "key": 1999 }));
+}
+
+fn build(dir: &Path, policy: EvictionPolicy) -> App<MockRuntime> {
+  build_with(dir, |builder| builder.eviction_policy(policy))
+}
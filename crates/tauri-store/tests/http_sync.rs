@@ -0,0 +1,135 @@
+#![cfg(feature = "sync-http")]
+
+mod common;
+
+use common::{build_with, temp_dir};
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tauri_store::{HttpSyncAdapter, ManagerExt};
+
+const STORE_ID: &str = "store";
+
+#[tokio::test]
+async fn sync_with_http_server() {
+  let server = MockServer::start();
+  let adapter = HttpSyncAdapter::new(&format!("http://{}/api", server.addr)).unwrap();
+  let app = build_with(&temp_dir("http-sync", "sync"), |builder| {
+    builder.sync_adapter(Box::new(adapter))
+  });
+
+  let collection = app.store_collection();
+  collection.set(STORE_ID, "local", 1).unwrap();
+  collection.sync_now().await.unwrap();
+  collection.sync_now().await.unwrap();
+
+  assert_eq!(collection.get_raw(STORE_ID, "remote"), Some(Value::from(2)));
+
+  let requests = server.requests.lock().unwrap();
+  assert_eq!(requests.len(), 3);
+
+  let (line, body) = &requests[0];
+  assert_eq!(line, "POST /api/push HTTP/1.1");
+  let body: Value = serde_json::from_str(body).unwrap();
+  assert_eq!(
+    body,
+    json!({ "changes": [{ "op": "set", "store": STORE_ID, "key": "local", "value": 1 }] })
+  );
+
+  assert_eq!(requests[1].0, "GET /api/pull HTTP/1.1");
+  assert_eq!(requests[2].0, "GET /api/pull?cursor=1 HTTP/1.1");
+}
+
+#[tokio::test]
+async fn fail_on_error_status() {
+  let server = MockServer::start();
+  let adapter = HttpSyncAdapter::new(&format!("http://{}/missing", server.addr)).unwrap();
+  let app = build_with(&temp_dir("http-sync", "error"), |builder| {
+    builder.sync_adapter(Box::new(adapter))
+  });
+
+  let err = app
+    .store_collection()
+    .sync_now()
+    .await
+    .unwrap_err();
+
+  assert!(err.is_sync_failure());
+}
+
+struct MockServer {
+  addr: SocketAddr,
+  requests: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl MockServer {
+  fn start() -> Self {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let log = Arc::clone(&requests);
+    thread::spawn(move || {
+      for stream in listener.incoming() {
+        let Ok(stream) = stream else { break };
+        handle(stream, &log);
+      }
+    });
+
+    Self { addr, requests }
+  }
+}
+
+fn handle(mut stream: TcpStream, log: &Mutex<Vec<(String, String)>>) {
+  let mut reader = BufReader::new(stream.try_clone().unwrap());
+  let mut line = String::new();
+  reader.read_line(&mut line).unwrap();
+  let line = line.trim_end().to_owned();
+
+  let mut len = 0;
+  loop {
+    let mut header = String::new();
+    reader.read_line(&mut header).unwrap();
+    let header = header.trim_end();
+    if header.is_empty() {
+      break;
+    }
+
+    if let Some((name, value)) = header.split_once(':') {
+      if name.eq_ignore_ascii_case("content-length") {
+        len = value.trim().parse().unwrap();
+      }
+    }
+  }
+
+  let mut body = vec![0; len];
+  reader.read_exact(&mut body).unwrap();
+  log
+    .lock()
+    .unwrap()
+    .push((line.clone(), String::from_utf8(body).unwrap()));
+
+  let (status, body) = if line.starts_with("POST /api/push ") {
+    ("200 OK", String::new())
+  } else if line.starts_with("GET /api/pull?cursor=") {
+    ("200 OK", json!({ "changes": [] }).to_string())
+  } else if line.starts_with("GET /api/pull ") {
+    let changes = json!({
+      "changes": [{ "op": "set", "store": STORE_ID, "key": "remote", "value": 2 }],
+      "cursor": "1",
+    });
+
+    ("200 OK", changes.to_string())
+  } else {
+    ("404 Not Found", String::new())
+  };
+
+  let response = format!(
+    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+    body.len()
+  );
+
+  stream.write_all(response.as_bytes()).unwrap();
+}
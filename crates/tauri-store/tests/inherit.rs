@@ -0,0 +1,164 @@
+mod common;
+
+use common::{build_with, temp_dir, try_build_with};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::ManagerExt;
+
+#[test]
+fn parent_chain() {
+  let dir = temp_dir("inherit", "parent-chain");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection
+    .set("global", "theme", "light")
+    .unwrap();
+  collection.set("global", "lang", "en").unwrap();
+  collection.set("workspace", "lang", "pt").unwrap();
+  collection.set("project", "tabs", 2).unwrap();
+
+  assert_eq!(collection.get_raw("project", "theme"), Some(json!("light")));
+  assert_eq!(collection.get_raw("project", "lang"), Some(json!("pt")));
+  assert_eq!(
+    collection.state::<Value>("project").unwrap(),
+    json!({ "theme": "light", "lang": "pt", "tabs": 2 })
+  );
+
+  collection
+    .set("project", "theme", "dark")
+    .unwrap();
+  assert_eq!(collection.get_raw("global", "theme"), Some(json!("light")));
+
+  collection.save_now("project").unwrap();
+  let persisted: Value =
+    serde_json::from_slice(&fs::read(dir.join("project.json")).unwrap()).unwrap();
+  assert_eq!(persisted, json!({ "theme": "dark", "tabs": 2 }));
+}
+
+#[tokio::test]
+async fn parent_change() {
+  let app = build(&temp_dir("inherit", "parent-change"));
+  let collection = app.store_collection();
+  collection
+    .set("global", "theme", "light")
+    .unwrap();
+  collection.set("workspace", "lang", "pt").unwrap();
+
+  let mut rx = collection.subscribe("workspace");
+
+  // The workspace has its own value for this key.
+  collection.set("global", "lang", "en").unwrap();
+  collection.set("global", "theme", "dark").unwrap();
+
+  let change = rx.recv().await.unwrap();
+  assert_eq!(change.keys.len(), 1);
+  assert_eq!(change.get("theme").unwrap().old, Some(json!("light")));
+  assert_eq!(change.get("theme").unwrap().new, Some(json!("dark")));
+  assert!(rx.try_recv().is_err());
+
+  assert_eq!(collection.get_raw("project", "theme"), Some(json!("dark")));
+}
+
+#[test]
+fn transaction_with_parent_and_child() {
+  let app = build(&temp_dir("inherit", "transaction-with-parent-and-child"));
+  let collection = app.store_collection();
+  collection
+    .set("global", "theme", "light")
+    .unwrap();
+  collection.set("workspace", "lang", "pt").unwrap();
+  collection.set("project", "tabs", 2).unwrap();
+
+  // A deadlock would otherwise hang the test.
+  let handle = app.handle().clone();
+  let (sender, receiver) = mpsc::channel();
+  thread::spawn(move || {
+    let result = handle
+      .store_collection()
+      .transaction(["global", "workspace", "project"], |tx| {
+        tx.set("global", "theme", "dark")?;
+        tx.set("workspace", "lang", "en")?;
+        Ok(())
+      });
+
+    let _ = sender.send(result);
+  });
+
+  receiver
+    .recv_timeout(Duration::from_secs(10))
+    .expect("transaction should not deadlock")
+    .unwrap();
+
+  assert_eq!(
+    collection.get_raw("workspace", "theme"),
+    Some(json!("dark"))
+  );
+  assert_eq!(collection.get_raw("project", "theme"), Some(json!("dark")));
+  assert_eq!(collection.get_raw("project", "lang"), Some(json!("en")));
+}
+
+#[test]
+fn cyclic_parent_chain() {
+  let dir = temp_dir("inherit", "cyclic-parent-chain");
+  let result = try_build_with(&dir, |builder| {
+    builder
+      .parent_of("a", "b")
+      .parent_of("b", "c")
+      .parent_of("c", "b")
+  });
+
+  assert!(result.is_err());
+}
+
+#[test]
+fn update_inherited_values() {
+  let app = build(&temp_dir("inherit", "update-inherited-values"));
+  let collection = app.store_collection();
+  collection
+    .set("global", "editor", json!({ "font": "mono", "size": 12 }))
+    .unwrap();
+  collection
+    .set("global", "window", json!({ "width": 800, "height": 600 }))
+    .unwrap();
+  collection
+    .put_blob("global", "avatar", [1, 2, 3])
+    .unwrap();
+
+  collection
+    .set_path("project", "/editor/size", 14)
+    .unwrap();
+  collection
+    .merge_patch("project", [("window", json!({ "width": 1024 }))])
+    .unwrap();
+
+  assert_eq!(
+    collection.get_raw("project", "editor"),
+    Some(json!({ "font": "mono", "size": 14 }))
+  );
+  assert_eq!(
+    collection.get_raw("project", "window"),
+    Some(json!({ "width": 1024, "height": 600 }))
+  );
+  assert_eq!(
+    collection.get_raw("global", "editor"),
+    Some(json!({ "font": "mono", "size": 12 }))
+  );
+  assert_eq!(
+    collection.get_blob("project", "avatar").unwrap(),
+    Some(vec![1, 2, 3])
+  );
+}
+
+fn build(dir: &Path) -> App<MockRuntime> {
+  build_with(dir, |builder| {
+    builder
+      .parent_of("workspace", "global")
+      .parent_of("project", "workspace")
+  })
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use common::{assert_exists, build_with, temp_dir};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::ManagerExt;
+
+const STORE_ID: &str = "journaled";
+const JOURNAL_LIMIT: usize = 3;
+
+#[test]
+fn append_to_journal() {
+  let dir = temp_dir("journal", "append");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection.set(STORE_ID, "key", 1).unwrap();
+  collection.set(STORE_ID, "key", 2).unwrap();
+
+  assert!(collection
+    .with_store(STORE_ID, |store| store.is_journaled())
+    .unwrap());
+  assert_exists(&store_path(&dir), false);
+
+  let journal = fs::read_to_string(journal_path(&dir)).unwrap();
+  assert_eq!(journal.lines().count(), 2);
+
+  collection.save_now(STORE_ID).unwrap();
+  assert_exists(&store_path(&dir), true);
+  assert_exists(&journal_path(&dir), false);
+}
+
+#[test]
+fn replay_journal() {
+  let dir = temp_dir("journal", "replay");
+  let journal = concat!(
+    r#"{"op":"set","key":"a","value":1}"#,
+    "\n",
+    r#"{"op":"set","key":"b","value":2}"#,
+    "\n",
+    r#"{"op":"set","key":"a","va"#,
+  );
+
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(journal_path(&dir), journal).unwrap();
+
+  let app = build(&dir);
+  let collection = app.store_collection();
+  assert_eq!(collection.get_raw(STORE_ID, "a"), Some(Value::from(1)));
+  assert_eq!(collection.get_raw(STORE_ID, "b"), Some(Value::from(2)));
+
+  // The incomplete record should have been discarded.
+  let journal = fs::read_to_string(journal_path(&dir)).unwrap();
+  assert!(journal.ends_with('\n'));
+  assert_eq!(journal.lines().count(), 2);
+
+  collection.set(STORE_ID, "c", 3).unwrap();
+  collection.unload_store(&STORE_ID.into()).unwrap();
+  assert_exists(&journal_path(&dir), false);
+
+  assert_eq!(collection.get_raw(STORE_ID, "a"), Some(Value::from(1)));
+  assert_eq!(collection.get_raw(STORE_ID, "c"), Some(Value::from(3)));
+}
+
+#[test]
+fn compact_journal() {
+  let dir = temp_dir("journal", "compact");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  for value in 0..JOURNAL_LIMIT {
+    assert_exists(&store_path(&dir), false);
+    collection.set(STORE_ID, "key", value).unwrap();
+  }
+
+  assert_exists(&store_path(&dir), true);
+  assert_exists(&journal_path(&dir), false);
+}
+
+fn build(dir: &Path) -> App<MockRuntime> {
+  build_with(dir, |builder| {
+    builder
+      .journal([STORE_ID])
+      .journal_limit(JOURNAL_LIMIT)
+  })
+}
+
+fn store_path(dir: &Path) -> PathBuf {
+  dir.join(format!("{STORE_ID}.json"))
+}
+
+fn journal_path(dir: &Path) -> PathBuf {
+  dir.join(format!("{STORE_ID}.wal"))
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::{build_with, temp_dir};
+use serde_json::{json, Value};
+use std::env::set_var;
+use std::fs;
+use std::path::Path;
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::{ManagerExt, StoreState};
+
+#[test]
+fn layers() {
+  set_var("TAURI_STORE_LAYERS__THEME", "dark");
+  set_var("TAURI_STORE_LAYERS__EDITOR__FONT_SIZE", "16");
+
+  let dir = temp_dir("layered", "layers");
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(
+    dir.join("store.json"),
+    r#"{ "editor": { "tabs": 2 }, "lang": "pt" }"#,
+  )
+  .unwrap();
+
+  let app = build(&dir, "TAURI_STORE_LAYERS");
+  let collection = app.store_collection();
+  assert_eq!(
+    state(&collection.raw_state("store").unwrap()),
+    json!({
+      "theme": "dark",
+      "lang": "pt",
+      "editor": { "font_size": 16, "tabs": 2, "wrap": false },
+    })
+  );
+
+  collection
+    .set_override("store", "lang", "en")
+    .unwrap();
+  assert_eq!(collection.get_raw("store", "lang"), Some(json!("en")));
+
+  collection.set("store", "lang", "fr").unwrap();
+  assert_eq!(collection.get_raw("store", "lang"), Some(json!("en")));
+
+  collection
+    .remove_override("store", "lang")
+    .unwrap();
+  assert_eq!(collection.get_raw("store", "lang"), Some(json!("fr")));
+
+  collection.save_now("store").unwrap();
+  let persisted: Value =
+    serde_json::from_slice(&fs::read(dir.join("store.json")).unwrap()).unwrap();
+  assert_eq!(persisted, json!({ "editor": { "tabs": 2 }, "lang": "fr" }));
+}
+
+#[test]
+fn patch_effective_state() {
+  set_var("TAURI_STORE_PATCH__THEME", "dark");
+
+  let dir = temp_dir("layered", "patch-effective-state");
+  let app = build(&dir, "TAURI_STORE_PATCH");
+  let collection = app.store_collection();
+
+  // This is what the frontend sends back after changing a single key.
+  let mut effective = collection.raw_state("store").unwrap();
+  effective.set("lang", "pt");
+  collection.patch("store", effective).unwrap();
+
+  collection.reset_key("store", "theme").unwrap();
+  collection.save_now("store").unwrap();
+
+  let persisted: Value =
+    serde_json::from_slice(&fs::read(dir.join("store.json")).unwrap()).unwrap();
+  assert_eq!(persisted, json!({ "lang": "pt" }));
+  assert_eq!(collection.get_raw("store", "theme"), Some(json!("dark")));
+}
+
+fn state(state: &StoreState) -> Value {
+  serde_json::to_value(state).unwrap()
+}
+
+fn build(dir: &Path, env_prefix: &str) -> App<MockRuntime> {
+  let defaults = json!({
+    "theme": "light",
+    "editor": { "font_size": 12, "wrap": false },
+  });
+
+  build_with(dir, |builder| {
+    builder
+      .defaults_of("store", defaults)
+      .env_prefix_of("store", env_prefix)
+  })
+}
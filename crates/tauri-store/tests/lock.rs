@@ -0,0 +1,74 @@
+mod common;
+
+use common::{temp_dir, try_build_with};
+use std::path::Path;
+use std::time::Duration;
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::{CollectionLayout, LockPolicy, ManagerExt, Result};
+
+#[test]
+fn fail_when_locked() {
+  let dir = temp_dir("lock", "fail");
+  let _app = build(&dir, LockPolicy::Fail).unwrap();
+
+  let err = build(&dir, LockPolicy::Fail).unwrap_err();
+  assert!(err.is_locked());
+}
+
+#[test]
+fn read_only_when_locked() {
+  let dir = temp_dir("lock", "read-only");
+  let app = build(&dir, LockPolicy::ReadOnly).unwrap();
+  assert!(!app.store_collection().is_read_only());
+
+  let other = build(&dir, LockPolicy::ReadOnly).unwrap();
+  let collection = other.store_collection();
+  assert!(collection.is_read_only());
+
+  collection.set("store", "key", 1).unwrap();
+  collection.save_now("store").unwrap();
+  assert!(!dir.join("store.json").exists());
+  assert!(collection
+    .convert_layout(CollectionLayout::Container)
+    .is_err_and(|err| err.is_locked()));
+}
+
+#[test]
+fn wait_until_unlocked() {
+  let dir = temp_dir("lock", "wait");
+  let app = build(&dir, LockPolicy::Fail).unwrap();
+
+  let err = build(&dir, LockPolicy::Wait(Duration::from_millis(100))).unwrap_err();
+  assert!(err.is_locked());
+
+  app.store_collection().on_exit().unwrap();
+  build(&dir, LockPolicy::Wait(Duration::from_secs(5))).unwrap();
+}
+
+#[test]
+fn lock_every_store_directory() {
+  let dir = temp_dir("lock", "every-directory");
+  let other = dir.join("other");
+  let _app = try_build_with(&dir.join("first"), |builder| {
+    builder
+      .path_of("store", &other)
+      .lock_policy(LockPolicy::Fail)
+  })
+  .unwrap();
+
+  let err = build(&other, LockPolicy::Fail).unwrap_err();
+  assert!(err.is_locked());
+
+  let app = try_build_with(&dir.join("second"), |builder| {
+    builder
+      .path_of("store", &other)
+      .lock_policy(LockPolicy::ReadOnly)
+  })
+  .unwrap();
+  assert!(app.store_collection().is_read_only());
+}
+
+fn build(dir: &Path, policy: LockPolicy) -> Result<App<MockRuntime>> {
+  try_build_with(dir, |builder| builder.lock_policy(policy))
+}
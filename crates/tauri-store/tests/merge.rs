@@ -0,0 +1,106 @@
+mod common;
+
+use common::{build_with, temp_dir};
+use serde_json::{json, Value};
+use std::path::Path;
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::{ManagerExt, PatchMode, StoreState};
+
+#[test]
+fn merge_patch() {
+  let app = build(&temp_dir("merge", "merge-patch"), PatchMode::Shallow);
+  let collection = app.store_collection();
+  collection
+    .set(
+      "store",
+      "settings",
+      json!({ "theme": "light", "lang": "en" }),
+    )
+    .unwrap();
+
+  collection.set("store", "draft", "hello").unwrap();
+
+  let patch = json!({ "settings": { "theme": "dark", "lang": null }, "draft": null });
+  collection
+    .merge_patch("store", state(patch))
+    .unwrap();
+
+  assert_eq!(
+    collection.get_raw("store", "settings"),
+    Some(json!({ "theme": "dark" }))
+  );
+  assert!(collection.get_raw("store", "draft").is_none());
+
+  // A regular patch still replaces the whole value.
+  collection
+    .patch("store", state(json!({ "settings": { "lang": "pt" } })))
+    .unwrap();
+
+  assert_eq!(
+    collection.get_raw("store", "settings"),
+    Some(json!({ "lang": "pt" }))
+  );
+}
+
+#[test]
+fn default_patch_mode() {
+  let app = build(&temp_dir("merge", "default-patch-mode"), PatchMode::Merge);
+  let collection = app.store_collection();
+  assert_eq!(collection.default_patch_mode(), PatchMode::Merge);
+
+  collection
+    .set(
+      "store",
+      "settings",
+      json!({ "theme": "light", "lang": "en" }),
+    )
+    .unwrap();
+
+  collection
+    .patch("store", state(json!({ "settings": { "theme": "dark" } })))
+    .unwrap();
+
+  assert_eq!(
+    collection.get_raw("store", "settings"),
+    Some(json!({ "theme": "dark", "lang": "en" }))
+  );
+}
+
+#[test]
+fn diff() {
+  let from = state(json!({
+    "settings": { "theme": "light", "lang": "en", "font": { "size": 12 } },
+    "draft": "hello",
+    "count": 1,
+  }));
+
+  let to = state(json!({
+    "settings": { "theme": "dark", "lang": "en", "font": { "size": 14 } },
+    "count": 1,
+    "tags": ["a"],
+  }));
+
+  let patch = from.diff(&to);
+  assert_eq!(
+    Value::from(&patch),
+    json!({
+      "settings": { "theme": "dark", "font": { "size": 14 } },
+      "draft": null,
+      "tags": ["a"],
+    })
+  );
+
+  let mut state = from.clone();
+  state.merge_patch(patch);
+  assert_eq!(Value::from(state), Value::from(&to));
+  assert!(to.diff(&to).is_empty());
+}
+
+fn state(value: Value) -> StoreState {
+  serde_json::from_value(value).unwrap()
+}
+
+fn build(dir: &Path, mode: PatchMode) -> App<MockRuntime> {
+  build_with(dir, |builder| builder.default_patch_mode(mode))
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::{build_with, sorted_ids, temp_dir};
+use std::fs;
+use std::path::Path;
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::{ManagerExt, StoreId};
+
+#[test]
+fn persisted_ids() {
+  let dir = temp_dir("preload", "persisted-ids");
+  write_stores(&dir);
+
+  let app = common::build(&dir);
+  let collection = app.store_collection();
+  assert!(collection.ids().is_empty());
+
+  let ids = collection.persisted_ids().unwrap();
+  assert_eq!(ids, [StoreId::from("a"), StoreId::from("b")]);
+  assert!(collection.ids().is_empty());
+}
+
+#[test]
+fn preload() {
+  let dir = temp_dir("preload", "preload");
+  write_stores(&dir);
+
+  let app = build(&dir, &["a"], false);
+  assert_eq!(sorted_ids(&app), ["a"]);
+
+  let collection = app.store_collection();
+  collection.preload(&["a", "b"]).unwrap();
+  assert_eq!(sorted_ids(&app), ["a", "b"]);
+}
+
+#[test]
+fn preload_all() {
+  let dir = temp_dir("preload", "preload-all");
+  write_stores(&dir);
+
+  let app = build(&dir, &[], true);
+  assert_eq!(sorted_ids(&app), ["a", "b"]);
+}
+
+fn build(dir: &Path, preload: &[&str], preload_all: bool) -> App<MockRuntime> {
+  build_with(dir, |builder| {
+    let builder = builder.preload(preload);
+    if preload_all {
+      builder.preload_all()
+    } else {
+      builder
+    }
+  })
+}
+
+fn write_stores(dir: &Path) {
+  fs::create_dir_all(dir).unwrap();
+  fs::write(dir.join("a.json"), r#"{ "key": "a" }"#).unwrap();
+  fs::write(dir.join("b.json"), r#"{ "key": "b" }"#).unwrap();
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::{build_with, temp_dir};
+use serde_json::Value;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::ManagerExt;
+
+const STORE_ID: &str = "store";
+
+#[test]
+fn apply_remote_changes() {
+  let dir = temp_dir("process-sync", "apply");
+  let app = build(&dir);
+  let other = build(&dir);
+
+  let called = Arc::new(AtomicBool::new(false));
+  let flag = Arc::clone(&called);
+  other
+    .store_collection()
+    .watch(STORE_ID, move |_| {
+      flag.store(true, Ordering::Release);
+      Ok(())
+    })
+    .unwrap();
+
+  let collection = app.store_collection();
+  collection.set(STORE_ID, "key", 1).unwrap();
+  collection
+    .patch(STORE_ID, [("a", 2), ("b", 3)])
+    .unwrap();
+
+  wait_until(|| other.store_collection().get_raw(STORE_ID, "b") == Some(Value::from(3)));
+
+  let other_collection = other.store_collection();
+  assert_eq!(
+    other_collection.get_raw(STORE_ID, "key"),
+    Some(Value::from(1))
+  );
+  assert_eq!(
+    other_collection.get_raw(STORE_ID, "a"),
+    Some(Value::from(2))
+  );
+  wait_until(|| called.load(Ordering::Acquire));
+}
+
+#[test]
+fn last_writer_wins() {
+  let dir = temp_dir("process-sync", "last-writer-wins");
+  let app = build(&dir);
+  let other = build(&dir);
+
+  app
+    .store_collection()
+    .set(STORE_ID, "key", "first")
+    .unwrap();
+
+  thread::sleep(Duration::from_millis(5));
+  other
+    .store_collection()
+    .set(STORE_ID, "key", "second")
+    .unwrap();
+
+  let expected = Some(Value::from("second"));
+  wait_until(|| app.store_collection().get_raw(STORE_ID, "key") == expected);
+
+  // Give the late change enough time to reach the other process, where it should lose.
+  thread::sleep(Duration::from_millis(200));
+  assert_eq!(other.store_collection().get_raw(STORE_ID, "key"), expected);
+}
+
+#[test]
+fn keep_logs_until_stale() {
+  let dir = temp_dir("process-sync", "exit");
+  let app = build(&dir);
+  let other = build(&dir);
+  let collection = app.store_collection();
+  assert!(collection.is_process_synced());
+
+  collection.set(STORE_ID, "key", 1).unwrap();
+  collection.on_exit().unwrap();
+  assert_eq!(count_logs(&dir), 1);
+
+  // The last change is still read by the process that was running.
+  let expected = Some(Value::from(1));
+  wait_until(|| other.store_collection().get_raw(STORE_ID, "key") == expected);
+
+  // The next process to start removes the logs of the one that exited.
+  let _next = build(&dir);
+  assert_eq!(count_logs(&dir), 0);
+}
+
+fn build(dir: &Path) -> App<MockRuntime> {
+  build_with(dir, |builder| {
+    builder.process_sync(Duration::from_millis(20))
+  })
+}
+
+fn wait_until(f: impl Fn() -> bool) {
+  let start = Instant::now();
+  while !f() {
+    assert!(start.elapsed() < Duration::from_secs(5), "timed out");
+    thread::sleep(Duration::from_millis(10));
+  }
+}
+
+fn count_logs(dir: &Path) -> usize {
+  std::fs::read_dir(dir.join("sync.tauristore"))
+    .unwrap()
+    .filter(|entry| {
+      entry
+        .as_ref()
+        .unwrap()
+        .path()
+        .extension()
+        .is_some_and(|it| it == "log")
+    })
+    .count()
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use common::{build_with, temp_dir};
+use serde_json::{json, Value};
+use std::mem;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::{ManagerExt, RemoteChange, RemoteChanges, SyncAdapter, SyncFuture, SyncStatus};
+
+const STORE_ID: &str = "store";
+
+#[tokio::test]
+async fn push_and_pull() {
+  let dir = temp_dir("remote-sync", "push-and-pull");
+  let remote = MockRemote::default();
+  let app = build(&dir, &remote);
+  let collection = app.store_collection();
+
+  collection.set(STORE_ID, "local", 1).unwrap();
+  assert_eq!(collection.pending_changes(), 1);
+
+  remote.add(RemoteChange::Set {
+    store: STORE_ID.into(),
+    key: "remote".into(),
+    value: json!(2),
+  });
+
+  collection.sync_now().await.unwrap();
+  assert_eq!(collection.pending_changes(), 0);
+  assert_eq!(collection.sync_status(), Some(SyncStatus::Idle));
+  assert_eq!(collection.get_raw(STORE_ID, "remote"), Some(Value::from(2)));
+
+  collection.sync_now().await.unwrap();
+  let server = remote.0.lock().unwrap();
+  assert_eq!(server.pushed.len(), 1);
+  assert_eq!(server.pushed[0].key(), "local");
+  assert_eq!(server.cursors, [None, Some("1".to_owned())]);
+}
+
+#[tokio::test]
+async fn queue_while_offline() {
+  let dir = temp_dir("remote-sync", "offline");
+  let remote = MockRemote::default();
+  remote.0.lock().unwrap().offline = true;
+
+  let app = build(&dir, &remote);
+  let collection = app.store_collection();
+  collection.set(STORE_ID, "key", 1).unwrap();
+
+  let err = collection.sync_now().await.unwrap_err();
+  assert!(err.is_sync_failure());
+  assert_eq!(collection.sync_status(), Some(SyncStatus::Offline));
+  assert_eq!(collection.pending_changes(), 1);
+
+  // The queue should survive a restart.
+  let app = build(&dir, &remote);
+  let collection = app.store_collection();
+  assert_eq!(collection.pending_changes(), 1);
+
+  remote.0.lock().unwrap().offline = false;
+  collection.sync_now().await.unwrap();
+  assert_eq!(collection.pending_changes(), 0);
+  assert_eq!(remote.0.lock().unwrap().pushed.len(), 1);
+}
+
+#[tokio::test]
+async fn remove_key() {
+  let dir = temp_dir("remote-sync", "remove");
+  let remote = MockRemote::default();
+  let app = build(&dir, &remote);
+  let collection = app.store_collection();
+
+  collection.set(STORE_ID, "key", 1).unwrap();
+  collection.sync_now().await.unwrap();
+
+  remote.add(RemoteChange::Remove {
+    store: STORE_ID.into(),
+    key: "key".into(),
+  });
+
+  collection.sync_now().await.unwrap();
+  assert!(collection.get_raw(STORE_ID, "key").is_none());
+}
+
+#[tokio::test]
+async fn queue_recovered_transaction() {
+  let dir = temp_dir("remote-sync", "recover-transaction");
+  let log = json!({
+    "stores": [[STORE_ID, [{ "op": "set", "key": "key", "value": 1 }]]]
+  });
+
+  let logs = dir.join("transactions.tauristore");
+  std::fs::create_dir_all(&logs).unwrap();
+  std::fs::write(logs.join("1.json"), log.to_string()).unwrap();
+
+  // The app may have been killed before the changes were queued.
+  let remote = MockRemote::default();
+  let app = build(&dir, &remote);
+  let collection = app.store_collection();
+  assert_eq!(collection.get_raw(STORE_ID, "key"), Some(Value::from(1)));
+  assert_eq!(collection.pending_changes(), 1);
+}
+
+#[tokio::test]
+async fn skip_own_changes() {
+  let dir = temp_dir("remote-sync", "skip-own-changes");
+  let remote = MockRemote::default();
+  remote.0.lock().unwrap().echo = true;
+
+  let app = build(&dir, &remote);
+  let collection = app.store_collection();
+  let calls = Arc::new(AtomicUsize::new(0));
+  let counter = Arc::clone(&calls);
+  collection
+    .watch(STORE_ID, move |_| {
+      counter.fetch_add(1, Ordering::AcqRel);
+      Ok(())
+    })
+    .unwrap();
+
+  collection.set(STORE_ID, "own", 1).unwrap();
+  collection.sync_now().await.unwrap();
+  assert_eq!(calls.load(Ordering::Acquire), 1);
+
+  remote.add(RemoteChange::Set {
+    store: STORE_ID.into(),
+    key: "own".into(),
+    value: json!(2),
+  });
+
+  // The change made elsewhere reached the remote before the one of this device, which wins.
+  collection.set(STORE_ID, "own", 3).unwrap();
+  collection.sync_now().await.unwrap();
+  assert_eq!(calls.load(Ordering::Acquire), 2);
+  assert_eq!(collection.get_raw(STORE_ID, "own"), Some(Value::from(3)));
+
+  collection.sync_now().await.unwrap();
+  assert_eq!(calls.load(Ordering::Acquire), 2);
+}
+
+#[derive(Clone, Default)]
+struct MockRemote(Arc<Mutex<Server>>);
+
+#[derive(Default)]
+struct Server {
+  offline: bool,
+  /// Whether pushed changes are sent back on the next pull, as a server shared by several devices would.
+  echo: bool,
+  pushed: Vec<RemoteChange>,
+  pending: Vec<RemoteChange>,
+  cursors: Vec<Option<String>>,
+}
+
+impl MockRemote {
+  fn add(&self, change: RemoteChange) {
+    self.0.lock().unwrap().pending.push(change);
+  }
+}
+
+impl SyncAdapter for MockRemote {
+  fn push<'a>(&'a self, changes: &'a [RemoteChange]) -> SyncFuture<'a, ()> {
+    Box::pin(async move {
+      let mut server = self.0.lock().unwrap();
+      if server.offline {
+        return Err("offline".into());
+      }
+
+      server.pushed.extend_from_slice(changes);
+      if server.echo {
+        server.pending.extend_from_slice(changes);
+      }
+
+      Ok(())
+    })
+  }
+
+  fn pull<'a>(&'a self, cursor: Option<&'a str>) -> SyncFuture<'a, RemoteChanges> {
+    Box::pin(async move {
+      let mut server = self.0.lock().unwrap();
+      if server.offline {
+        return Err("offline".into());
+      }
+
+      server.cursors.push(cursor.map(ToOwned::to_owned));
+      Ok(RemoteChanges {
+        changes: mem::take(&mut server.pending),
+        cursor: Some(server.cursors.len().to_string()),
+      })
+    })
+  }
+}
+
+fn build(dir: &Path, remote: &MockRemote) -> App<MockRuntime> {
+  build_with(dir, |builder| {
+    builder.sync_adapter(Box::new(remote.clone()))
+  })
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::{assert_exists, build_with, temp_dir};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::ManagerExt;
+
+const STORE_ID: &str = "sharded";
+
+#[test]
+fn save_keys_to_own_files() {
+  let dir = temp_dir("shard", "save");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  assert!(collection.is_sharded(STORE_ID));
+
+  collection.set(STORE_ID, "key", 1).unwrap();
+  collection.set(STORE_ID, "nested/key", 2).unwrap();
+  collection.save_now(STORE_ID).unwrap();
+
+  assert_eq!(collection.path_of(STORE_ID).join(STORE_ID), shard_dir(&dir));
+  assert_exists(&shard_dir(&dir).join("key.json"), true);
+  assert_exists(&shard_dir(&dir).join("nested%2Fkey.json"), true);
+
+  collection.unload_store(&STORE_ID.into()).unwrap();
+  assert_eq!(collection.get_raw(STORE_ID, "key"), Some(Value::from(1)));
+  assert_eq!(
+    collection.get_raw(STORE_ID, "nested/key"),
+    Some(Value::from(2))
+  );
+}
+
+#[test]
+fn rewrite_changed_keys() {
+  let dir = temp_dir("shard", "rewrite");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection.set(STORE_ID, "key-1", 1).unwrap();
+  collection.set(STORE_ID, "key-2", 2).unwrap();
+  collection.save_now(STORE_ID).unwrap();
+
+  // If the unchanged key were rewritten, its file would be recreated.
+  fs::remove_file(shard_dir(&dir).join("key-2.json")).unwrap();
+
+  collection.set(STORE_ID, "key-1", 3).unwrap();
+  collection.save_now(STORE_ID).unwrap();
+
+  assert_exists(&shard_dir(&dir).join("key-1.json"), true);
+  assert_exists(&shard_dir(&dir).join("key-2.json"), false);
+}
+
+#[test]
+fn distinct_file_names() {
+  let dir = temp_dir("shard", "distinct-file-names");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  let long_key = "key".repeat(100);
+  collection.set(STORE_ID, "theme", 1).unwrap();
+  collection.set(STORE_ID, "Theme", 2).unwrap();
+  collection.set(STORE_ID, &long_key, 3).unwrap();
+  collection.save_now(STORE_ID).unwrap();
+
+  assert_exists(&shard_dir(&dir).join("theme.json"), true);
+  assert_exists(&shard_dir(&dir).join("%54heme.json"), true);
+
+  for entry in fs::read_dir(shard_dir(&dir)).unwrap() {
+    let name = entry.unwrap().file_name();
+    assert!(name.len() <= 255);
+  }
+
+  collection.unload_store(&STORE_ID.into()).unwrap();
+  assert_eq!(collection.get_raw(STORE_ID, "theme"), Some(Value::from(1)));
+  assert_eq!(collection.get_raw(STORE_ID, "Theme"), Some(Value::from(2)));
+  assert_eq!(
+    collection.get_raw(STORE_ID, &long_key),
+    Some(Value::from(3))
+  );
+
+  // The shard of a long key is still removed with it.
+  collection
+    .remove_path(STORE_ID, format!("/{long_key}"))
+    .unwrap();
+  collection.save_now(STORE_ID).unwrap();
+  assert_eq!(fs::read_dir(shard_dir(&dir)).unwrap().count(), 2);
+}
+
+#[test]
+fn destroy_sharded() {
+  let dir = temp_dir("shard", "destroy");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection.set(STORE_ID, "key", 1).unwrap();
+  collection.save_now(STORE_ID).unwrap();
+  assert_exists(&shard_dir(&dir), true);
+
+  collection.destroy(STORE_ID).unwrap();
+  assert_exists(&shard_dir(&dir), false);
+}
+
+fn build(dir: &Path) -> App<MockRuntime> {
+  build_with(dir, |builder| builder.shard([STORE_ID]))
+}
+
+fn shard_dir(dir: &Path) -> PathBuf {
+  dir.join(STORE_ID)
+}
@@ -1,18 +1,21 @@
-#![allow(unused_must_use)]
+#![allow(unused_must_use, clippy::incompatible_msrv)]
 
 use anyhow::Result;
 use itertools::Itertools;
 use serde::Deserialize;
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::env::current_dir;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, LazyLock, OnceLock};
 use tauri::test::{mock_app, MockRuntime};
 use tauri::{AppHandle, Manager};
 use tauri_store::{
-  DefaultMarker, Handle, ManagerExt, SaveStrategy, Store, StoreCollection, StoreId,
+  io_err, AtomicOp, DefaultMarker, Handle, ManagerExt, SaveStrategy, Store, StoreCollection,
+  StoreId,
 };
 use tokio::fs;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::{Notify, Semaphore, SemaphorePermit};
 use tokio::time::{sleep, timeout, Duration};
 
@@ -216,13 +219,15 @@ async fn patch_many() {
 
 #[tokio::test]
 async fn save() {
-  with_store(|store| {
+  let (path, _permit) = with_store(|store| {
     assert_exists(&store.path(), false);
     store.set("key", 42).unwrap();
     store.save_now().unwrap();
-    assert_exists(&store.path(), true);
+    store.path()
   })
   .await;
+
+  assert_exists(&path, true);
 }
 
 #[tokio::test]
@@ -249,16 +254,18 @@ async fn save_debounced() {
 
 #[tokio::test]
 async fn save_now() {
-  with_store(|store| {
+  let (path, _permit) = with_store(|store| {
     assert_exists(&store.path(), false);
     store.save_on_change(true);
     store.set_save_strategy(SaveStrategy::debounce_millis(100));
 
     store.set("key", 42).unwrap();
     store.save_now().unwrap();
-    assert_exists(&store.path(), true);
+    store.path()
   })
   .await;
+
+  assert_exists(&path, true);
 }
 
 #[tokio::test]
@@ -280,13 +287,16 @@ async fn set_save_strategy() {
 
 #[tokio::test]
 async fn save_on_change() {
-  with_store(|store| {
+  let (path, _permit) = with_store(|store| {
     assert_exists(&store.path(), false);
     store.save_on_change(true);
     store.set("key", 42).unwrap();
-    assert_exists(&store.path(), true);
+    store.path()
   })
   .await;
+
+  // The file is written once the store is released.
+  assert_exists(&path, true);
 }
 
 #[tokio::test]
@@ -317,6 +327,359 @@ async fn unwatch() {
   .await;
 }
 
+#[tokio::test]
+async fn watch_key() {
+  let (tx, mut rx) = unbounded_channel();
+  let _permit = with_store(|store| {
+    store.set("key", 1).unwrap();
+    store.watch_key("key", move |_, change| {
+      tx.send(change.clone()).unwrap();
+      Ok(())
+    });
+
+    store.set("other", 42).unwrap();
+    store.set("key", 2).unwrap();
+  })
+  .await;
+
+  let change = timeout(Duration::from_millis(200), rx.recv())
+    .await
+    .unwrap()
+    .unwrap();
+
+  assert_eq!(change.id, *STORE_ID);
+  assert_eq!(change.revision, 3);
+  assert!(change.source.is_backend());
+  assert_eq!(change.changed().collect_vec(), ["key"]);
+  assert_eq!(change.removed().count(), 0);
+
+  let key = change.get("key").unwrap();
+  assert_eq!(key.old, Some(Value::from(1)));
+  assert_eq!(key.new, Some(Value::from(2)));
+
+  sleep(Duration::from_millis(50)).await;
+  assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn subscribe() {
+  let _permit = acquire_permit().await.unwrap();
+  let collection = HANDLE.store_collection();
+  let mut rx = collection.subscribe(&*STORE_ID);
+
+  for i in 0..3 {
+    collection.set(&*STORE_ID, "key", i).unwrap();
+  }
+
+  for i in 0..3 {
+    let change = rx.recv().await.unwrap();
+    assert_eq!(change.get("key").unwrap().new, Some(Value::from(i)));
+  }
+}
+
+#[tokio::test]
+async fn subscribe_lagged() {
+  let _permit = acquire_permit().await.unwrap();
+  let collection = HANDLE.store_collection();
+  let mut rx = collection.subscribe(&*STORE_ID);
+
+  for i in 0..300 {
+    collection.set(&*STORE_ID, "key", i).unwrap();
+  }
+
+  assert!(matches!(rx.recv().await, Err(RecvError::Lagged(44))));
+  let change = rx.recv().await.unwrap();
+  assert_eq!(change.get("key").unwrap().new, Some(Value::from(44)));
+}
+
+#[tokio::test]
+async fn batch() {
+  let (tx, mut rx) = unbounded_channel();
+  let _permit = with_store(|store| {
+    store.set("key", 1).unwrap();
+    store.watch_changes(move |_, change| {
+      tx.send(change.clone()).unwrap();
+      Ok(())
+    });
+
+    let len = store
+      .batch(|tx| {
+        tx.set("foo", 1).set("bar", 2).remove("key");
+        assert_eq!(tx.get_raw("foo"), Some(&Value::from(1)));
+        assert!(tx.get_raw("key").is_none());
+        tx.len()
+      })
+      .unwrap();
+
+    assert_eq!(len, 3);
+    assert!(!store.has("key"));
+    assert_eq!(store.len(), 2);
+  })
+  .await;
+
+  let change = timeout(Duration::from_millis(200), rx.recv())
+    .await
+    .unwrap()
+    .unwrap();
+
+  assert_eq!(change.revision, 2);
+  assert_eq!(change.changed().collect_vec(), ["foo", "bar"]);
+  assert_eq!(change.removed().collect_vec(), ["key"]);
+
+  sleep(Duration::from_millis(50)).await;
+  assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn update() {
+  with_store(|store| {
+    let value = store
+      .update("key", |current| {
+        assert!(current.is_none());
+        Ok(Value::from(1))
+      })
+      .unwrap();
+
+    assert_eq!(value, Value::from(1));
+
+    let value = store
+      .update("key", |current| {
+        Ok(Value::from(current.unwrap().as_i64().unwrap() * 10))
+      })
+      .unwrap();
+
+    assert_eq!(value, Value::from(10));
+    assert_eq!(store.get_raw("key"), Some(&Value::from(10)));
+    assert!(store
+      .update("key", |_| io_err!(Other, "nope"))
+      .is_err());
+    assert_eq!(store.get_raw("key"), Some(&Value::from(10)));
+  })
+  .await;
+}
+
+#[tokio::test]
+async fn apply_op() {
+  with_store(|store| {
+    let mut apply = |key: &str, op: Value| {
+      let op: AtomicOp = serde_json::from_value(op).unwrap();
+      store.apply_op(key, op)
+    };
+
+    let value = apply("count", json!({ "op": "increment" })).unwrap();
+    assert_eq!(value, Value::from(1));
+    let value = apply("count", json!({ "op": "decrement", "by": 3 })).unwrap();
+    assert_eq!(value, Value::from(-2));
+    let value = apply("count", json!({ "op": "increment", "by": 0.5 })).unwrap();
+    assert_eq!(value, Value::from(-1.5));
+
+    apply("list", json!({ "op": "push", "value": "a" })).unwrap();
+    apply("list", json!({ "op": "push", "value": "b" })).unwrap();
+    let value = apply("list", json!({ "op": "removeAt", "index": 0 })).unwrap();
+    assert_eq!(value, json!(["b"]));
+    assert!(apply("list", json!({ "op": "removeAt", "index": 5 })).is_err());
+
+    let value = apply("flag", json!({ "op": "toggle" })).unwrap();
+    assert_eq!(value, Value::Bool(true));
+
+    apply("obj", json!({ "op": "merge", "value": { "a": 1 } })).unwrap();
+    let value = apply("obj", json!({ "op": "merge", "value": { "b": 2 } })).unwrap();
+    assert_eq!(value, json!({ "a": 1, "b": 2 }));
+
+    assert!(apply("flag", json!({ "op": "increment" })).is_err());
+    assert!(apply("count", json!({ "op": "push", "value": 1 })).is_err());
+    assert_eq!(store.get_raw("flag"), Some(&Value::Bool(true)));
+  })
+  .await;
+}
+
+#[tokio::test]
+async fn set_path() {
+  with_store(|store| {
+    store
+      .set_path("/settings/editor/fontSize", 14)
+      .unwrap();
+
+    assert_eq!(
+      store
+        .get_path::<u8>("/settings/editor/fontSize")
+        .unwrap(),
+      14
+    );
+    assert_eq!(
+      store.get_raw("settings"),
+      Some(&json!({ "editor": { "fontSize": 14 } }))
+    );
+
+    store
+      .set_path("/settings/tabs", json!([]))
+      .unwrap();
+    store.set_path("/settings/tabs/-", "a").unwrap();
+    store.set_path("/settings/tabs/0", "b").unwrap();
+    store.set_path("/settings/a~1b", true).unwrap();
+    assert_eq!(
+      store.get_raw("settings"),
+      Some(&json!({ "editor": { "fontSize": 14 }, "tabs": ["b"], "a/b": true }))
+    );
+
+    assert!(store.set_path("/settings/tabs/5", 1).is_err());
+    assert!(store
+      .set_path("/settings/editor/fontSize/x", 1)
+      .is_err());
+    assert!(store.set_path("settings", 1).is_err());
+    assert!(store.get_path::<u8>("/settings/missing").is_err());
+  })
+  .await;
+}
+
+#[tokio::test]
+async fn remove_path() {
+  with_store(|store| {
+    let settings = json!({ "editor": { "fontSize": 14, "theme": "dark" }, "tabs": [1, 2] });
+    store.set("settings", settings).unwrap();
+
+    let removed = store
+      .remove_path("/settings/editor/fontSize")
+      .unwrap();
+
+    assert_eq!(removed, Some(Value::from(14)));
+    assert_eq!(
+      store.remove_path("/settings/tabs/0").unwrap(),
+      Some(Value::from(1))
+    );
+    assert!(store
+      .remove_path("/settings/missing")
+      .unwrap()
+      .is_none());
+    assert_eq!(
+      store.get_raw("settings"),
+      Some(&json!({ "editor": { "theme": "dark" }, "tabs": [2] }))
+    );
+
+    assert!(store.remove_path("/settings").unwrap().is_some());
+    assert!(!store.has("settings"));
+  })
+  .await;
+}
+
+#[tokio::test]
+async fn watch_path() {
+  let (tx, mut rx) = unbounded_channel();
+  let _permit = with_store(|store| {
+    store
+      .set(
+        "settings",
+        json!({ "editor": { "fontSize": 12 }, "theme": "light" }),
+      )
+      .unwrap();
+
+    store
+      .watch_path("/settings/editor/fontSize", move |_, change| {
+        tx.send(change.clone()).unwrap();
+        Ok(())
+      })
+      .unwrap();
+
+    store.set_path("/settings/theme", "dark").unwrap();
+    store
+      .set_path("/settings/editor/fontSize", 14)
+      .unwrap();
+  })
+  .await;
+
+  let change = timeout(Duration::from_millis(200), rx.recv())
+    .await
+    .unwrap()
+    .unwrap();
+
+  assert!(change.contains_path("/settings/editor/fontSize"));
+  assert!(!change.contains_path("/settings/theme"));
+
+  let key = change.get("settings").unwrap();
+  assert_eq!(key.old_path("/editor/fontSize"), Some(&Value::from(12)));
+  assert_eq!(key.new_path("/editor/fontSize"), Some(&Value::from(14)));
+
+  sleep(Duration::from_millis(50)).await;
+  assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn undo_redo() {
+  with_store(|store| {
+    store.set("key", 1).unwrap();
+    assert!(!store.can_undo());
+
+    store.set_history_depth(10).unwrap();
+    store.set("key", 2).unwrap();
+    store.set("other", 3).unwrap();
+    assert!(store.can_undo());
+
+    assert!(store.undo().unwrap());
+    assert!(!store.has("other"));
+    assert!(store.can_redo());
+
+    assert!(store.undo().unwrap());
+    assert_eq!(store.get_raw("key"), Some(&Value::from(1)));
+    assert!(!store.undo().unwrap());
+
+    assert!(store.redo().unwrap());
+    assert_eq!(store.get_raw("key"), Some(&Value::from(2)));
+
+    // A new change discards what could be redone.
+    store.set("key", 4).unwrap();
+    assert!(!store.can_redo());
+    assert!(!store.redo().unwrap());
+
+    store.clear_history().unwrap();
+    assert!(!store.can_undo());
+  })
+  .await;
+}
+
+#[tokio::test]
+async fn undo_batch() {
+  with_store(|store| {
+    store.set_history_depth(10).unwrap();
+    store.set("key", 1).unwrap();
+    store
+      .batch(|tx| {
+        tx.set("key", 2).set("foo", 3).remove("key");
+      })
+      .unwrap();
+
+    assert!(!store.has("key"));
+    assert!(store.undo().unwrap());
+    assert_eq!(store.get_raw("key"), Some(&Value::from(1)));
+    assert!(!store.has("foo"));
+
+    assert!(store.redo().unwrap());
+    assert!(!store.has("key"));
+    assert_eq!(store.get_raw("foo"), Some(&Value::from(3)));
+  })
+  .await;
+}
+
+#[tokio::test]
+async fn history_depth() {
+  with_store(|store| {
+    store.set_history_depth(2).unwrap();
+    for i in 0..5 {
+      store.set("key", i).unwrap();
+    }
+
+    assert!(store.undo().unwrap());
+    assert!(store.undo().unwrap());
+    assert!(!store.undo().unwrap());
+    assert_eq!(store.get_raw("key"), Some(&Value::from(2)));
+
+    store.set_history_depth(0).unwrap();
+    store.set("key", 10).unwrap();
+    assert!(!store.can_undo());
+    assert!(!store.can_redo());
+  })
+  .await;
+}
+
 #[tokio::test]
 async fn has() {
   with_store(|store| {
@@ -362,9 +725,9 @@ async fn entries() {
 #[tokio::test]
 async fn len() {
   with_store(|store| {
-    assert!(store.len() == 0);
+    assert_eq!(store.len(), 0);
     store.set("key", 42).unwrap();
-    assert!(store.len() == 1);
+    assert_eq!(store.len(), 1);
   })
   .await;
 }
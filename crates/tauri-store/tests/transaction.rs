@@ -0,0 +1,148 @@
+mod common;
+
+use common::{build, temp_dir, try_build_with};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use std::thread;
+use tauri_store::{io_err, ManagerExt};
+
+#[test]
+fn commit() {
+  let dir = temp_dir("transaction", "commit");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection
+    .set("accounts", "balance", 100)
+    .unwrap();
+
+  let balance = collection
+    .transaction(["session", "accounts"], |tx| {
+      let balance = tx.get::<i64>("accounts", "balance")? - 30;
+      tx.set("accounts", "balance", balance)?;
+      tx.set("session", "last", 30)?;
+      Ok(balance)
+    })
+    .unwrap();
+
+  assert_eq!(balance, 70);
+  assert_eq!(
+    collection.get_raw("accounts", "balance"),
+    Some(Value::from(70))
+  );
+  assert_eq!(collection.get_raw("session", "last"), Some(Value::from(30)));
+
+  // Both stores should have been saved.
+  let state = fs::read(dir.join("accounts.json")).unwrap();
+  let state: Value = serde_json::from_slice(&state).unwrap();
+  assert_eq!(state, json!({ "balance": 70 }));
+  assert!(dir.join("session.json").is_file());
+  assert_eq!(count_intent_logs(&dir), 0);
+}
+
+#[test]
+fn rollback() {
+  let app = build(&temp_dir("transaction", "rollback"));
+  let collection = app.store_collection();
+  collection
+    .set("accounts", "balance", 100)
+    .unwrap();
+
+  let result = collection.transaction::<_, _, _, ()>(["accounts", "session"], |tx| {
+    tx.set("accounts", "balance", 0)?;
+    tx.set("session", "last", 100)?;
+    io_err!(Other, "insufficient funds")
+  });
+
+  assert!(result.is_err());
+  assert_eq!(
+    collection.get_raw("accounts", "balance"),
+    Some(Value::from(100))
+  );
+  assert!(collection.get_raw("session", "last").is_none());
+}
+
+#[test]
+fn reject_unknown_store() {
+  let app = build(&temp_dir("transaction", "unknown"));
+  let collection = app.store_collection();
+  let result = collection.transaction(["accounts"], |tx| {
+    tx.set("session", "last", 1)?;
+    Ok(())
+  });
+
+  assert!(result.is_err());
+  assert!(collection.get_raw("session", "last").is_none());
+}
+
+#[test]
+fn recover_interrupted() {
+  let dir = temp_dir("transaction", "recover");
+  let log = json!({
+    "stores": [
+      ["accounts", [{ "op": "set", "key": "balance", "value": 70 }]],
+      ["session", [{ "op": "set", "key": "last", "value": 30 }]],
+    ]
+  });
+
+  let logs = dir.join("transactions.tauristore");
+  fs::create_dir_all(&logs).unwrap();
+  fs::write(logs.join("1.json"), log.to_string()).unwrap();
+
+  // A later transaction, which was also interrupted.
+  let log = json!({
+    "stores": [["accounts", [{ "op": "set", "key": "balance", "value": 40 }]]]
+  });
+
+  fs::write(logs.join("2.json"), log.to_string()).unwrap();
+  fs::write(logs.join("3.json.tmp"), "{").unwrap();
+
+  let app = build(&dir);
+  let collection = app.store_collection();
+  assert_eq!(
+    collection.get_raw("accounts", "balance"),
+    Some(Value::from(40))
+  );
+  assert_eq!(collection.get_raw("session", "last"), Some(Value::from(30)));
+  assert!(dir.join("session.json").is_file());
+  assert_eq!(count_intent_logs(&dir), 0);
+}
+
+#[test]
+fn reject_corrupt_intent_log() {
+  let dir = temp_dir("transaction", "corrupt");
+  let logs = dir.join("transactions.tauristore");
+  fs::create_dir_all(&logs).unwrap();
+  fs::write(logs.join("1.json"), "{").unwrap();
+
+  assert!(try_build_with(&dir, |builder| builder).is_err());
+  assert!(logs.join("1.json").is_file());
+}
+
+#[test]
+fn concurrent_transactions() {
+  let dir = temp_dir("transaction", "concurrent");
+  let app = build(&dir);
+  let collection = &*app.store_collection();
+
+  thread::scope(|scope| {
+    for id in ["first", "second", "third", "fourth"] {
+      scope.spawn(move || {
+        for value in 0..20 {
+          collection
+            .transaction([id], |tx| {
+              tx.set(id, "key", value)?;
+              Ok(())
+            })
+            .unwrap();
+        }
+      });
+    }
+  });
+
+  assert_eq!(count_intent_logs(&dir), 0);
+}
+
+fn count_intent_logs(dir: &Path) -> usize {
+  fs::read_dir(dir.join("transactions.tauristore")).map_or(0, Iterator::count)
+}
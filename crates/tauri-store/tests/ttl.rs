@@ -0,0 +1,85 @@
+mod common;
+
+use common::{build, temp_dir};
+use serde_json::json;
+use std::thread::sleep;
+use std::time::Duration;
+use tauri_store::ManagerExt;
+
+#[test]
+fn set_with_ttl() {
+  let app = build(&temp_dir("ttl", "set-with-ttl"));
+  let collection = app.store_collection();
+  collection
+    .set_with_ttl("store", "token", "abc", Duration::from_millis(100))
+    .unwrap();
+
+  collection.set("store", "key", 1).unwrap();
+
+  let ttl = collection.ttl("store", "token").unwrap().unwrap();
+  assert!(ttl <= Duration::from_millis(100));
+  assert!(collection.ttl("store", "key").unwrap().is_none());
+  assert_eq!(collection.get_raw("store", "token"), Some(json!("abc")));
+
+  sleep(Duration::from_millis(150));
+
+  assert!(collection.get_raw("store", "token").is_none());
+  assert!(collection
+    .ttl("store", "token")
+    .unwrap()
+    .is_none());
+  assert_eq!(collection.get_raw("store", "key"), Some(json!(1)));
+}
+
+#[test]
+fn set_clears_ttl() {
+  let app = build(&temp_dir("ttl", "set-clears-ttl"));
+  let collection = app.store_collection();
+  collection
+    .set_with_ttl("store", "token", "abc", Duration::from_millis(100))
+    .unwrap();
+
+  collection.set("store", "token", "def").unwrap();
+  assert!(collection
+    .ttl("store", "token")
+    .unwrap()
+    .is_none());
+
+  sleep(Duration::from_millis(150));
+
+  assert_eq!(collection.purge_expired().unwrap(), 0);
+  assert_eq!(collection.get_raw("store", "token"), Some(json!("def")));
+}
+
+#[test]
+fn ttl_persists() {
+  let dir = temp_dir("ttl", "ttl-persists");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection
+    .set_with_ttl("store", "token", "abc", Duration::from_millis(200))
+    .unwrap();
+
+  collection
+    .set_with_ttl("store", "session", "xyz", Duration::from_secs(3600))
+    .unwrap();
+
+  collection.save_now("store").unwrap();
+  drop(app);
+
+  let app = build(&dir);
+  let collection = app.store_collection();
+  assert!(collection
+    .ttl("store", "token")
+    .unwrap()
+    .is_some());
+  assert!(collection
+    .ttl("store", "session")
+    .unwrap()
+    .is_some());
+
+  sleep(Duration::from_millis(250));
+
+  assert!(collection.get_raw("store", "token").is_none());
+  assert_eq!(collection.get_raw("store", "session"), Some(json!("xyz")));
+}
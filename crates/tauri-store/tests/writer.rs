@@ -0,0 +1,129 @@
+mod common;
+
+use common::{build_with, read, temp_dir};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use tauri::test::MockRuntime;
+use tauri::App;
+use tauri_store::{Error, ManagerExt, SaveStrategy};
+
+#[test]
+fn save_now_waits_for_its_write() {
+  let dir = temp_dir("writer", "save-now-waits-for-its-write");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection.set("store", "key", 1).unwrap();
+  collection.save_now("store").unwrap();
+
+  assert!(!collection.is_dirty("store").unwrap());
+  assert_eq!(read(&dir.join("store.json")), json!({ "key": 1 }));
+}
+
+#[test]
+fn save_now_while_locked() {
+  let dir = temp_dir("writer", "save-now-while-locked");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection
+    .with_store("store", |store| {
+      store.set("key", 1)?;
+      store.save_now()
+    })
+    .unwrap()
+    .unwrap();
+
+  // The file is written by the time the store is released.
+  assert!(!collection.is_dirty("store").unwrap());
+  assert_eq!(read(&dir.join("store.json")), json!({ "key": 1 }));
+}
+
+#[test]
+fn concurrent_saves() {
+  let dir = temp_dir("writer", "concurrent-saves");
+  let app = build(&dir);
+  let handle = app.handle();
+
+  // Concurrent loads of the same store are not what this is testing.
+  for id in ["store-0", "store-1"] {
+    handle
+      .store_collection()
+      .set(id, "loaded", true)
+      .unwrap();
+  }
+
+  thread::scope(|scope| {
+    for i in 0..8 {
+      scope.spawn(move || {
+        let collection = handle.store_collection();
+        for j in 0..16 {
+          let id = format!("store-{}", i % 2);
+          collection
+            .set(&id, format!("key-{i}"), j)
+            .unwrap();
+          collection.save_now(&id).unwrap();
+          assert!(collection
+            .get_raw(&id, format!("key-{i}"))
+            .is_some());
+        }
+      });
+    }
+  });
+
+  let collection = app.store_collection();
+  for id in ["store-0", "store-1"] {
+    assert!(!collection.is_dirty(id).unwrap());
+    let state = serde_json::to_value(collection.raw_state(id).unwrap()).unwrap();
+    assert_eq!(read(&dir.join(format!("{id}.json"))), state);
+  }
+}
+
+#[test]
+fn save_all_reports_every_failure() {
+  let dir = temp_dir("writer", "save-all-reports-every-failure");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  for id in ["bad-0", "good", "bad-1"] {
+    collection.set(id, "key", 1).unwrap();
+  }
+
+  // A directory in place of the file makes the write fail.
+  fs::create_dir_all(dir.join("bad-0.json")).unwrap();
+  fs::create_dir_all(dir.join("bad-1.json")).unwrap();
+
+  let Err(Error::Multiple(errors)) = collection.save_all_now() else {
+    panic!("expected every failure to be reported");
+  };
+
+  let mut failed = errors
+    .iter()
+    .map(|(id, _)| id.to_string())
+    .collect::<Vec<_>>();
+
+  failed.sort();
+  assert_eq!(failed, ["bad-0", "bad-1"]);
+  assert_eq!(read(&dir.join("good.json")), json!({ "key": 1 }));
+}
+
+#[test]
+fn save_after_exit() {
+  let dir = temp_dir("writer", "save-after-exit");
+  let app = build(&dir);
+  let collection = app.store_collection();
+  collection.set("store", "key", 1).unwrap();
+  collection.on_exit().unwrap();
+  assert_eq!(read(&dir.join("store.json")), json!({ "key": 1 }));
+
+  // The writer threads are gone, so the file is written on this one.
+  collection.set("store", "key", 2).unwrap();
+  collection.save_now("store").unwrap();
+  assert!(!collection.is_dirty("store").unwrap());
+  assert_eq!(read(&dir.join("store.json")), json!({ "key": 2 }));
+}
+
+fn build(dir: &Path) -> App<MockRuntime> {
+  build_with(dir, |builder| {
+    builder.default_save_strategy(SaveStrategy::debounce_secs(60))
+  })
+}
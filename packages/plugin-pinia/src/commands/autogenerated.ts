@@ -19,11 +19,55 @@ export const allowSave = commands.allowSave('pinia');
  */
 export const allowSync = commands.allowSync('pinia');
 
+/**
+ * Atomically updates the value of a key in the Rust side,
+ * returning the new value. This is safe even if multiple windows update the key at once.
+ *
+ * @example
+ * ```ts
+ * import { applyOp } from '@tauri-store/pinia';
+ *
+ * const count = await applyOp('my-store', 'counter', { op: 'increment', by: 2 });
+ * ```
+ */
+export const applyOp = commands.applyOp('pinia');
+
+/**
+ * Applies multiple changes to a store at once,
+ * emitting a single change event and saving the store only once.
+ *
+ * @example
+ * ```ts
+ * import { batch } from '@tauri-store/pinia';
+ *
+ * await batch('my-store', [
+ *   { op: 'set', key: 'theme', value: 'dark' },
+ *   { op: 'remove', key: 'draft' },
+ * ]);
+ * ```
+ */
+export const batch = commands.batch('pinia');
+
+/**
+ * Whether there is a change to redo in a store.
+ */
+export const canRedo = commands.canRedo('pinia');
+
+/**
+ * Whether there is a change to undo in a store.
+ */
+export const canUndo = commands.canUndo('pinia');
+
 /**
  * Stops the autosave.
  */
 export const clearAutosave = commands.clearAutosave('pinia');
 
+/**
+ * Discards every change that could be undone or redone in a store.
+ */
+export const clearHistory = commands.clearHistory('pinia');
+
 /**
  * Adds one or more stores to the save denylist.
  */
@@ -34,12 +78,31 @@ export const denySave = commands.denySave('pinia');
  */
 export const denySync = commands.denySync('pinia');
 
+/**
+ * Reads a blob referenced by a key in a store.
+ * Rejects if the key does not reference a blob.
+ */
+export const getBlob = commands.getBlob('pinia');
+
 /**
  * Gets the default save strategy for the stores.
  * It can be overridden on a per-store basis.
  */
 export const getDefaultSaveStrategy = commands.getDefaultSaveStrategy('pinia');
 
+/**
+ * Gets the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) of a store,
+ * where the first token is the key, or `null` if it does not exist.
+ *
+ * @example
+ * ```ts
+ * import { getPath } from '@tauri-store/pinia';
+ *
+ * const fontSize = await getPath<number>('my-store', '/settings/editor/fontSize');
+ * ```
+ */
+export const getPath = commands.getPath('pinia');
+
 /**
  * Directory where the stores are saved.
  */
@@ -50,6 +113,11 @@ export const getStoreCollectionPath = commands.getStoreCollectionPath('pinia');
  **/
 export const getSaveStrategy = commands.getSaveStrategy('pinia');
 
+/**
+ * Lists the ids of the stores saved on the disk, including those that are not loaded.
+ */
+export const getPersistedStoreIds = commands.getPersistedStoreIds('pinia');
+
 /**
  * Lists all the store ids.
  */
@@ -65,6 +133,60 @@ export const getStorePath = commands.getStorePath('pinia');
  */
 export const getStoreState = commands.getStoreState('pinia');
 
+/**
+ * Applies a [JSON Merge Patch](https://datatracker.ietf.org/doc/html/rfc7396) to a store,
+ * merging nested objects and removing the keys set to `null`.
+ *
+ * @example
+ * ```ts
+ * import { mergePatch } from '@tauri-store/pinia';
+ *
+ * await mergePatch('my-store', { settings: { theme: 'dark' }, draft: null });
+ * ```
+ */
+export const mergePatch = commands.mergePatch('pinia');
+
+/**
+ * Saves a blob to a store and sets a reference to it under the given key.
+ *
+ * @example
+ * ```ts
+ * import { putBlob } from '@tauri-store/pinia';
+ *
+ * const response = await fetch('https://example.com/avatar.png');
+ * await putBlob('my-store', 'avatar', await response.arrayBuffer());
+ * ```
+ */
+export const putBlob = commands.putBlob('pinia');
+
+/**
+ * Reapplies the last change to a store that was undone, returning whether there was anything to redo.
+ */
+export const redo = commands.redo('pinia');
+
+/**
+ * Removes the reference to a blob from a store, returning whether it existed.
+ */
+export const removeBlob = commands.removeBlob('pinia');
+
+/**
+ * Removes the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) of a store,
+ * returning it, or `null` if it did not exist.
+ */
+export const removePath = commands.removePath('pinia');
+
+/**
+ * Restores a store to its default values, removing every key that has none.
+ *
+ * @example
+ * ```ts
+ * import { reset } from '@tauri-store/pinia';
+ *
+ * await reset('my-store');
+ * ```
+ */
+export const reset = commands.reset('pinia');
+
 /**
  * Saves a store to the disk.
  *
@@ -121,6 +243,19 @@ export const saveNow = commands.saveNow('pinia');
  */
 export const setAutosave = commands.setAutosave('pinia');
 
+/**
+ * Sets the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) of a store,
+ * creating the intermediate objects that don't exist.
+ *
+ * @example
+ * ```ts
+ * import { setPath } from '@tauri-store/pinia';
+ *
+ * await setPath('my-store', '/settings/editor/fontSize', 14);
+ * ```
+ */
+export const setPath = commands.setPath('pinia');
+
 /**
  * Sets the save strategy for a store.
  * Calling this will abort any pending save operation.
@@ -150,6 +285,20 @@ export const setSaveStrategy = commands.setSaveStrategy('pinia');
  */
 export const setStoreOptions = commands.setStoreOptions('pinia');
 
+/**
+ * Reverts the last change to a store, returning whether there was anything to undo.
+ * This requires the store to have a history depth.
+ *
+ * @example
+ * ```ts
+ * import { setStoreOptions, undo } from '@tauri-store/pinia';
+ *
+ * await setStoreOptions('my-store', { historyDepth: 50 });
+ * await undo('my-store');
+ * ```
+ */
+export const undo = commands.undo('pinia');
+
 /////////////////////////
 // INTERNAL COMMANDS
 /////////////////////////
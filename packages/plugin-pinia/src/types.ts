@@ -2,6 +2,8 @@ import type { WatchOptions } from 'vue';
 import type { StoreOptions } from '@tauri-store/shared';
 
 export type {
+  AtomicOp,
+  BatchOp,
   LooseTimeStrategyKind,
   MaybePromise,
   nil,
@@ -5,25 +5,43 @@ export { RuneStore, runeStore } from './runes';
 export {
   allowSave,
   allowSync,
+  applyOp,
+  batch,
+  canRedo,
+  canUndo,
   clearAutosave,
+  clearHistory,
   denySave,
   denySync,
+  getBlob,
   getDefaultSaveStrategy,
+  getPath,
+  getPersistedStoreIds,
   getSaveStrategy,
   getStoreCollectionPath,
   getStoreIds,
   getStorePath,
   getStoreState,
+  mergePatch,
+  putBlob,
+  redo,
+  removeBlob,
+  removePath,
+  reset,
   save,
   saveAll,
   saveAllNow,
   saveNow,
   setAutosave,
+  setPath,
   setSaveStrategy,
   setStoreOptions,
+  undo,
 } from './commands';
 
 export type {
+  AtomicOp,
+  BatchOp,
   LooseTimeStrategyKind,
   MaybePromise,
   nil,
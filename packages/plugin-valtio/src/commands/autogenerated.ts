@@ -19,11 +19,55 @@ export const allowSave = commands.allowSave('valtio');
  */
 export const allowSync = commands.allowSync('valtio');
 
+/**
+ * Atomically updates the value of a key in the Rust side,
+ * returning the new value. This is safe even if multiple windows update the key at once.
+ *
+ * @example
+ * ```ts
+ * import { applyOp } from '@tauri-store/valtio';
+ *
+ * const count = await applyOp('my-store', 'counter', { op: 'increment', by: 2 });
+ * ```
+ */
+export const applyOp = commands.applyOp('valtio');
+
+/**
+ * Applies multiple changes to a store at once,
+ * emitting a single change event and saving the store only once.
+ *
+ * @example
+ * ```ts
+ * import { batch } from '@tauri-store/valtio';
+ *
+ * await batch('my-store', [
+ *   { op: 'set', key: 'theme', value: 'dark' },
+ *   { op: 'remove', key: 'draft' },
+ * ]);
+ * ```
+ */
+export const batch = commands.batch('valtio');
+
+/**
+ * Whether there is a change to redo in a store.
+ */
+export const canRedo = commands.canRedo('valtio');
+
+/**
+ * Whether there is a change to undo in a store.
+ */
+export const canUndo = commands.canUndo('valtio');
+
 /**
  * Stops the autosave.
  */
 export const clearAutosave = commands.clearAutosave('valtio');
 
+/**
+ * Discards every change that could be undone or redone in a store.
+ */
+export const clearHistory = commands.clearHistory('valtio');
+
 /**
  * Adds one or more stores to the save denylist.
  */
@@ -34,12 +78,31 @@ export const denySave = commands.denySave('valtio');
  */
 export const denySync = commands.denySync('valtio');
 
+/**
+ * Reads a blob referenced by a key in a store.
+ * Rejects if the key does not reference a blob.
+ */
+export const getBlob = commands.getBlob('valtio');
+
 /**
  * Gets the default save strategy for the stores.
  * It can be overridden on a per-store basis.
  */
 export const getDefaultSaveStrategy = commands.getDefaultSaveStrategy('valtio');
 
+/**
+ * Gets the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) of a store,
+ * where the first token is the key, or `null` if it does not exist.
+ *
+ * @example
+ * ```ts
+ * import { getPath } from '@tauri-store/valtio';
+ *
+ * const fontSize = await getPath<number>('my-store', '/settings/editor/fontSize');
+ * ```
+ */
+export const getPath = commands.getPath('valtio');
+
 /**
  * Directory where the stores are saved.
  */
@@ -50,6 +113,11 @@ export const getStoreCollectionPath = commands.getStoreCollectionPath('valtio');
  **/
 export const getSaveStrategy = commands.getSaveStrategy('valtio');
 
+/**
+ * Lists the ids of the stores saved on the disk, including those that are not loaded.
+ */
+export const getPersistedStoreIds = commands.getPersistedStoreIds('valtio');
+
 /**
  * Lists all the store ids.
  */
@@ -65,6 +133,60 @@ export const getStorePath = commands.getStorePath('valtio');
  */
 export const getStoreState = commands.getStoreState('valtio');
 
+/**
+ * Applies a [JSON Merge Patch](https://datatracker.ietf.org/doc/html/rfc7396) to a store,
+ * merging nested objects and removing the keys set to `null`.
+ *
+ * @example
+ * ```ts
+ * import { mergePatch } from '@tauri-store/valtio';
+ *
+ * await mergePatch('my-store', { settings: { theme: 'dark' }, draft: null });
+ * ```
+ */
+export const mergePatch = commands.mergePatch('valtio');
+
+/**
+ * Saves a blob to a store and sets a reference to it under the given key.
+ *
+ * @example
+ * ```ts
+ * import { putBlob } from '@tauri-store/valtio';
+ *
+ * const response = await fetch('https://example.com/avatar.png');
+ * await putBlob('my-store', 'avatar', await response.arrayBuffer());
+ * ```
+ */
+export const putBlob = commands.putBlob('valtio');
+
+/**
+ * Reapplies the last change to a store that was undone, returning whether there was anything to redo.
+ */
+export const redo = commands.redo('valtio');
+
+/**
+ * Removes the reference to a blob from a store, returning whether it existed.
+ */
+export const removeBlob = commands.removeBlob('valtio');
+
+/**
+ * Removes the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) of a store,
+ * returning it, or `null` if it did not exist.
+ */
+export const removePath = commands.removePath('valtio');
+
+/**
+ * Restores a store to its default values, removing every key that has none.
+ *
+ * @example
+ * ```ts
+ * import { reset } from '@tauri-store/valtio';
+ *
+ * await reset('my-store');
+ * ```
+ */
+export const reset = commands.reset('valtio');
+
 /**
  * Saves a store to the disk.
  *
@@ -121,6 +243,19 @@ export const saveNow = commands.saveNow('valtio');
  */
 export const setAutosave = commands.setAutosave('valtio');
 
+/**
+ * Sets the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) of a store,
+ * creating the intermediate objects that don't exist.
+ *
+ * @example
+ * ```ts
+ * import { setPath } from '@tauri-store/valtio';
+ *
+ * await setPath('my-store', '/settings/editor/fontSize', 14);
+ * ```
+ */
+export const setPath = commands.setPath('valtio');
+
 /**
  * Sets the save strategy for a store.
  * Calling this will abort any pending save operation.
@@ -150,6 +285,20 @@ export const setSaveStrategy = commands.setSaveStrategy('valtio');
  */
 export const setStoreOptions = commands.setStoreOptions('valtio');
 
+/**
+ * Reverts the last change to a store, returning whether there was anything to undo.
+ * This requires the store to have a history depth.
+ *
+ * @example
+ * ```ts
+ * import { setStoreOptions, undo } from '@tauri-store/valtio';
+ *
+ * await setStoreOptions('my-store', { historyDepth: 50 });
+ * await undo('my-store');
+ * ```
+ */
+export const undo = commands.undo('valtio');
+
 /////////////////////////
 // INTERNAL COMMANDS
 /////////////////////////
@@ -2,6 +2,8 @@ import type { Store } from './store';
 import type { State, StoreOptions, TauriStoreContract } from '@tauri-store/shared';
 
 export type {
+  AtomicOp,
+  BatchOp,
   LooseTimeStrategyKind,
   MaybePromise,
   nil,
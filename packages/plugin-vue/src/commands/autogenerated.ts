@@ -19,11 +19,55 @@ export const allowSave = commands.allowSave('vue');
  */
 export const allowSync = commands.allowSync('vue');
 
+/**
+ * Atomically updates the value of a key in the Rust side,
+ * returning the new value. This is safe even if multiple windows update the key at once.
+ *
+ * @example
+ * ```ts
+ * import { applyOp } from '@tauri-store/vue';
+ *
+ * const count = await applyOp('my-store', 'counter', { op: 'increment', by: 2 });
+ * ```
+ */
+export const applyOp = commands.applyOp('vue');
+
+/**
+ * Applies multiple changes to a store at once,
+ * emitting a single change event and saving the store only once.
+ *
+ * @example
+ * ```ts
+ * import { batch } from '@tauri-store/vue';
+ *
+ * await batch('my-store', [
+ *   { op: 'set', key: 'theme', value: 'dark' },
+ *   { op: 'remove', key: 'draft' },
+ * ]);
+ * ```
+ */
+export const batch = commands.batch('vue');
+
+/**
+ * Whether there is a change to redo in a store.
+ */
+export const canRedo = commands.canRedo('vue');
+
+/**
+ * Whether there is a change to undo in a store.
+ */
+export const canUndo = commands.canUndo('vue');
+
 /**
  * Stops the autosave.
  */
 export const clearAutosave = commands.clearAutosave('vue');
 
+/**
+ * Discards every change that could be undone or redone in a store.
+ */
+export const clearHistory = commands.clearHistory('vue');
+
 /**
  * Adds one or more stores to the save denylist.
  */
@@ -34,12 +78,31 @@ export const denySave = commands.denySave('vue');
  */
 export const denySync = commands.denySync('vue');
 
+/**
+ * Reads a blob referenced by a key in a store.
+ * Rejects if the key does not reference a blob.
+ */
+export const getBlob = commands.getBlob('vue');
+
 /**
  * Gets the default save strategy for the stores.
  * It can be overridden on a per-store basis.
  */
 export const getDefaultSaveStrategy = commands.getDefaultSaveStrategy('vue');
 
+/**
+ * Gets the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) of a store,
+ * where the first token is the key, or `null` if it does not exist.
+ *
+ * @example
+ * ```ts
+ * import { getPath } from '@tauri-store/vue';
+ *
+ * const fontSize = await getPath<number>('my-store', '/settings/editor/fontSize');
+ * ```
+ */
+export const getPath = commands.getPath('vue');
+
 /**
  * Directory where the stores are saved.
  */
@@ -50,6 +113,11 @@ export const getStoreCollectionPath = commands.getStoreCollectionPath('vue');
  **/
 export const getSaveStrategy = commands.getSaveStrategy('vue');
 
+/**
+ * Lists the ids of the stores saved on the disk, including those that are not loaded.
+ */
+export const getPersistedStoreIds = commands.getPersistedStoreIds('vue');
+
 /**
  * Lists all the store ids.
  */
@@ -65,6 +133,60 @@ export const getStorePath = commands.getStorePath('vue');
  */
 export const getStoreState = commands.getStoreState('vue');
 
+/**
+ * Applies a [JSON Merge Patch](https://datatracker.ietf.org/doc/html/rfc7396) to a store,
+ * merging nested objects and removing the keys set to `null`.
+ *
+ * @example
+ * ```ts
+ * import { mergePatch } from '@tauri-store/vue';
+ *
+ * await mergePatch('my-store', { settings: { theme: 'dark' }, draft: null });
+ * ```
+ */
+export const mergePatch = commands.mergePatch('vue');
+
+/**
+ * Saves a blob to a store and sets a reference to it under the given key.
+ *
+ * @example
+ * ```ts
+ * import { putBlob } from '@tauri-store/vue';
+ *
+ * const response = await fetch('https://example.com/avatar.png');
+ * await putBlob('my-store', 'avatar', await response.arrayBuffer());
+ * ```
+ */
+export const putBlob = commands.putBlob('vue');
+
+/**
+ * Reapplies the last change to a store that was undone, returning whether there was anything to redo.
+ */
+export const redo = commands.redo('vue');
+
+/**
+ * Removes the reference to a blob from a store, returning whether it existed.
+ */
+export const removeBlob = commands.removeBlob('vue');
+
+/**
+ * Removes the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) of a store,
+ * returning it, or `null` if it did not exist.
+ */
+export const removePath = commands.removePath('vue');
+
+/**
+ * Restores a store to its default values, removing every key that has none.
+ *
+ * @example
+ * ```ts
+ * import { reset } from '@tauri-store/vue';
+ *
+ * await reset('my-store');
+ * ```
+ */
+export const reset = commands.reset('vue');
+
 /**
  * Saves a store to the disk.
  *
@@ -121,6 +243,19 @@ export const saveNow = commands.saveNow('vue');
  */
 export const setAutosave = commands.setAutosave('vue');
 
+/**
+ * Sets the value at a [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) of a store,
+ * creating the intermediate objects that don't exist.
+ *
+ * @example
+ * ```ts
+ * import { setPath } from '@tauri-store/vue';
+ *
+ * await setPath('my-store', '/settings/editor/fontSize', 14);
+ * ```
+ */
+export const setPath = commands.setPath('vue');
+
 /**
  * Sets the save strategy for a store.
  * Calling this will abort any pending save operation.
@@ -150,6 +285,20 @@ export const setSaveStrategy = commands.setSaveStrategy('vue');
  */
 export const setStoreOptions = commands.setStoreOptions('vue');
 
+/**
+ * Reverts the last change to a store, returning whether there was anything to undo.
+ * This requires the store to have a history depth.
+ *
+ * @example
+ * ```ts
+ * import { setStoreOptions, undo } from '@tauri-store/vue';
+ *
+ * await setStoreOptions('my-store', { historyDepth: 50 });
+ * await undo('my-store');
+ * ```
+ */
+export const undo = commands.undo('vue');
+
 /////////////////////////
 // INTERNAL COMMANDS
 /////////////////////////
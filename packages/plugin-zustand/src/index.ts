@@ -1,6 +1,8 @@
 export { createTauriStore, tauri, type TauriStore } from './store';
 
 export type {
+  AtomicOp,
+  BatchOp,
   LooseTimeStrategyKind,
   MaybePromise,
   nil,
@@ -21,20 +23,36 @@ export type {
 export {
   allowSave,
   allowSync,
+  applyOp,
+  batch,
+  canRedo,
+  canUndo,
   clearAutosave,
+  clearHistory,
   denySave,
   denySync,
+  getBlob,
   getDefaultSaveStrategy,
+  getPath,
+  getPersistedStoreIds,
   getSaveStrategy,
   getStoreCollectionPath,
   getStoreIds,
   getStorePath,
   getStoreState,
+  mergePatch,
+  putBlob,
+  redo,
+  removeBlob,
+  removePath,
+  reset,
   save,
   saveAll,
   saveAllNow,
   saveNow,
   setAutosave,
+  setPath,
   setSaveStrategy,
   setStoreOptions,
+  undo,
 } from './commands';
@@ -1,6 +1,6 @@
 import { flatten } from '../utils';
 import { invoke } from '@tauri-apps/api/core';
-import type { Option, State, StoreBackendOptions, StoreBackendRawOptions } from '../types';
+import type { AtomicOp, BatchOp, Option, State, StoreBackendOptions, StoreBackendRawOptions } from '../types';
 import {
   isValidInterval,
   TimeStrategy,
@@ -20,12 +20,42 @@ export function allowSync(plugin: string) {
   };
 }
 
+export function applyOp(plugin: string) {
+  return function (storeId: string, key: string, op: AtomicOp): Promise<unknown> {
+    return invoke(`plugin:${plugin}|apply_op`, { id: storeId, key, op });
+  };
+}
+
+export function batch(plugin: string) {
+  return function (storeId: string, ops: BatchOp[]): Promise<void> {
+    return invoke(`plugin:${plugin}|batch`, { id: storeId, ops });
+  };
+}
+
+export function canRedo(plugin: string) {
+  return function (storeId: string): Promise<boolean> {
+    return invoke(`plugin:${plugin}|can_redo`, { id: storeId });
+  };
+}
+
+export function canUndo(plugin: string) {
+  return function (storeId: string): Promise<boolean> {
+    return invoke(`plugin:${plugin}|can_undo`, { id: storeId });
+  };
+}
+
 export function clearAutosave(plugin: string) {
   return function (): Promise<void> {
     return invoke(`plugin:${plugin}|clear_autosave`);
   };
 }
 
+export function clearHistory(plugin: string) {
+  return function (storeId: string): Promise<void> {
+    return invoke(`plugin:${plugin}|clear_history`, { id: storeId });
+  };
+}
+
 export function denySave(plugin: string) {
   return function (...storeId: string[]): Promise<void> {
     return invoke(`plugin:${plugin}|deny_save`, { ids: storeId });
@@ -38,6 +68,12 @@ export function denySync(plugin: string) {
   };
 }
 
+export function getBlob(plugin: string) {
+  return function (storeId: string, key: string): Promise<ArrayBuffer> {
+    return invoke(`plugin:${plugin}|get_blob`, { id: storeId, key });
+  };
+}
+
 export function getDefaultSaveStrategy(plugin: string) {
   return async function (): Promise<TimeStrategy> {
     return TimeStrategy.parse(
@@ -46,6 +82,18 @@ export function getDefaultSaveStrategy(plugin: string) {
   };
 }
 
+export function getPath(plugin: string) {
+  return function <T = unknown>(storeId: string, pointer: string): Promise<T | null> {
+    return invoke(`plugin:${plugin}|get_path`, { id: storeId, pointer });
+  };
+}
+
+export function getPersistedStoreIds(plugin: string) {
+  return function (): Promise<string[]> {
+    return invoke(`plugin:${plugin}|get_persisted_store_ids`);
+  };
+}
+
 export function getSaveStrategy(plugin: string) {
   return async function (storeId: string): Promise<TimeStrategy> {
     return TimeStrategy.parse(
@@ -78,6 +126,49 @@ export function getStoreState(plugin: string) {
   };
 }
 
+export function mergePatch(plugin: string) {
+  return function (storeId: string, patch: State): Promise<void> {
+    return invoke(`plugin:${plugin}|merge_patch`, { id: storeId, patch });
+  };
+}
+
+export function putBlob(plugin: string) {
+  return function (storeId: string, key: string, bytes: ArrayBuffer | Uint8Array): Promise<void> {
+    // The bytes are sent as they are, so the store and the key go in the headers.
+    const body = bytes instanceof Uint8Array ? bytes : new Uint8Array(bytes);
+    return invoke(`plugin:${plugin}|put_blob`, body, {
+      headers: {
+        'x-store-id': encodeURIComponent(storeId),
+        'x-store-key': encodeURIComponent(key),
+      },
+    });
+  };
+}
+
+export function redo(plugin: string) {
+  return function (storeId: string): Promise<boolean> {
+    return invoke(`plugin:${plugin}|redo`, { id: storeId });
+  };
+}
+
+export function removeBlob(plugin: string) {
+  return function (storeId: string, key: string): Promise<boolean> {
+    return invoke(`plugin:${plugin}|remove_blob`, { id: storeId, key });
+  };
+}
+
+export function removePath(plugin: string) {
+  return function <T = unknown>(storeId: string, pointer: string): Promise<T | null> {
+    return invoke(`plugin:${plugin}|remove_path`, { id: storeId, pointer });
+  };
+}
+
+export function reset(plugin: string) {
+  return function (storeId: string): Promise<void> {
+    return invoke(`plugin:${plugin}|reset`, { id: storeId });
+  };
+}
+
 export function save(plugin: string) {
   // TODO: use a saner signature.
   return function (...storeId: (string | string[])[]): Promise<void> {
@@ -116,6 +207,12 @@ export function setAutosave(plugin: string) {
   };
 }
 
+export function setPath(plugin: string) {
+  return function (storeId: string, pointer: string, value: unknown): Promise<void> {
+    return invoke(`plugin:${plugin}|set_path`, { id: storeId, pointer, value });
+  };
+}
+
 // eslint-disable-next-line @typescript-eslint/explicit-module-boundary-types
 export function setSaveStrategy(plugin: string) {
   function set(storeId: string, strategy: 'immediate'): Promise<void>;
@@ -149,6 +246,8 @@ export function setStoreOptions(plugin: string) {
       saveOnChange: typeof options.saveOnChange === 'boolean' ? options.saveOnChange : null,
       saveOnExit: typeof options.saveOnExit === 'boolean' ? options.saveOnExit : null,
       saveStrategy,
+      historyDepth: typeof options.historyDepth === 'number' ? options.historyDepth : null,
+      evictable: typeof options.evictable === 'boolean' ? options.evictable : null,
     };
 
     return invoke(`plugin:${plugin}|set_store_options`, {
@@ -157,3 +256,9 @@ export function setStoreOptions(plugin: string) {
     });
   };
 }
+
+export function undo(plugin: string) {
+  return function (storeId: string): Promise<boolean> {
+    return invoke(`plugin:${plugin}|undo`, { id: storeId });
+  };
+}
@@ -3,7 +3,9 @@ import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
 
 export enum StoreEvent {
   ConfigChange = 'tauri-store://config-change',
+  HistoryChange = 'tauri-store://history-change',
   StateChange = 'tauri-store://state-change',
+  SyncStatus = 'tauri-store://sync-status',
   Unload = 'tauri-store://unload',
 }
 
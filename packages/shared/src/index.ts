@@ -24,7 +24,10 @@ export {
 } from './defaults';
 
 export type {
+  AtomicOp,
+  BatchOp,
   ConfigChangePayload,
+  HistoryChangePayload,
   Fn,
   MaybePromise,
   nil,
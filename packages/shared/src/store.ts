@@ -163,6 +163,8 @@ export abstract class BaseStore<S extends State = State> {
         saveOnChange: this.options.saveOnChange,
         saveOnExit: this.options.saveOnExit,
         saveStrategy: this.options.saveStrategy,
+        historyDepth: this.options.historyDepth,
+        evictable: this.options.evictable,
       });
     } catch (err) {
       if (this.onError) {
@@ -183,6 +185,14 @@ export abstract class BaseStore<S extends State = State> {
       this.options.saveInterval = saveStrategy.interval;
       this.options.saveStrategy = saveStrategy.strategy;
     }
+
+    if (typeof config.historyDepth === 'number') {
+      this.options.historyDepth = config.historyDepth;
+    }
+
+    if (typeof config.evictable === 'boolean') {
+      this.options.evictable = config.evictable;
+    }
   }
 
   protected applyKeyFilters(state: Partial<S>): Partial<S> {
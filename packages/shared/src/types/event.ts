@@ -6,6 +6,13 @@ export interface ConfigChangePayload {
   id: string;
 }
 
+/** @internal */
+export interface HistoryChangePayload {
+  canRedo: boolean;
+  canUndo: boolean;
+  id: string;
+}
+
 /** @internal */
 export interface StateChangePayload<T extends State = State> {
   id: string;
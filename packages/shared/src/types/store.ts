@@ -32,6 +32,21 @@ export interface StoreBackendOptions {
    * @default 'immediate'
    */
   saveStrategy?: LooseTimeStrategyKind;
+
+  /**
+   * Maximum amount of changes that can be undone.
+   * The history is disabled when this is zero.
+   *
+   * @default 0
+   */
+  historyDepth?: number;
+
+  /**
+   * Whether the store can be unloaded by the eviction policy of the collection.
+   *
+   * @default true
+   */
+  evictable?: boolean;
 }
 
 /** @internal */
@@ -39,6 +54,8 @@ export interface StoreBackendRawOptions {
   readonly saveOnChange?: Option<boolean>;
   readonly saveOnExit?: Option<boolean>;
   readonly saveStrategy?: Option<TimeStrategyRawTuple>;
+  readonly historyDepth?: Option<number>;
+  readonly evictable?: Option<boolean>;
 }
 
 /** Options that can only be set from JavaScript. */
@@ -173,6 +190,18 @@ export interface TauriStoreContract {
   readonly destroy: () => Promise<void>;
 }
 
+/** Atomic operation applied to the value of a key. */
+export type AtomicOp =
+  | { op: 'increment'; by?: number }
+  | { op: 'decrement'; by?: number }
+  | { op: 'push'; value: unknown }
+  | { op: 'removeAt'; index: number }
+  | { op: 'toggle' }
+  | { op: 'merge'; value: Record<string, unknown> };
+
+/** Change applied to a store as part of a batch. */
+export type BatchOp = { op: 'set'; key: string; value: unknown } | { op: 'remove'; key: string };
+
 /** State of a store. */
 export type State = Record<string, unknown>;
 
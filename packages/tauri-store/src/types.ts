@@ -1,6 +1,8 @@
 import type { State, StoreOptions } from '@tauri-store/shared';
 
 export type {
+  AtomicOp,
+  BatchOp,
   LooseTimeStrategyKind,
   MaybePromise,
   nil,
@@ -11,4 +11,4 @@ if ($Ubuntu) {
 }
 
 cargo clippy --workspace
-cargo test -p tauri-store --tests -- --test-threads=1
+cargo test -p tauri-store --tests --features sync-http -- --test-threads=1